@@ -6,6 +6,7 @@ use std::process::Command;
 use tempdir::TempDir;
 
 // Import our modules
+use xerg::options::SearchOptions;
 use xerg::output::colors::Color;
 use xerg::search::crawler::get_files;
 use xerg::search::default::search_files;
@@ -58,7 +59,7 @@ fn create_test_files(temp_dir: &TempDir) -> Vec<(String, PathBuf)> {
         writeln!(file, "    use std::collections::HashMap;").unwrap();
         writeln!(file, "    println!(\"Debug message {}\");", i).unwrap();
         writeln!(file, "}}").unwrap();
-        writeln!(file, "").unwrap();
+        writeln!(file).unwrap();
     }
     test_files.push(("code_rust".to_string(), code_file));
 
@@ -67,7 +68,11 @@ fn create_test_files(temp_dir: &TempDir) -> Vec<(String, PathBuf)> {
 
 /// Benchmark our channel-based search
 fn bench_xerg_regular(files: &[PathBuf], pattern: &str) {
-    let rx = search_files(files, pattern, &Color::Blue, false);
+    let options = SearchOptions {
+        color: Color::Blue,
+        ..SearchOptions::new(pattern)
+    };
+    let rx = search_files(files, &options);
     // Consume all results
     while rx.recv().is_ok() {}
 }
@@ -75,7 +80,11 @@ fn bench_xerg_regular(files: &[PathBuf], pattern: &str) {
 /// Benchmark our xtreme mode
 fn bench_xerg_xtreme(files: &[PathBuf], pattern: &str) {
     // Capture stdout to avoid polluting benchmark output
-    let _result = search_files_xtreme(files, pattern, &Color::Blue, false);
+    let options = SearchOptions {
+        color: Color::Blue,
+        ..SearchOptions::new(pattern)
+    };
+    let _result = search_files_xtreme(files, &options);
 }
 
 /// Benchmark system grep for comparison
@@ -164,10 +173,10 @@ fn benchmark_file_reading_strategies(c: &mut Criterion) {
                     let mut matches = 0;
 
                     for line_result in reader.lines() {
-                        if let Ok(line) = line_result {
-                            if line.contains(black_box(pattern)) {
-                                matches += 1;
-                            }
+                        if let Ok(line) = line_result
+                            && line.contains(black_box(pattern))
+                        {
+                            matches += 1;
                         }
                     }
                     matches
@@ -182,11 +191,11 @@ fn benchmark_file_reading_strategies(c: &mut Criterion) {
             |b, (file, pattern)| {
                 b.iter(|| {
                     let contents = std::fs::read_to_string(black_box(file)).unwrap();
-                    let matches = contents
+
+                    contents
                         .lines()
                         .filter(|line| line.contains(black_box(pattern)))
-                        .count();
-                    matches
+                        .count()
                 })
             },
         );
@@ -227,11 +236,11 @@ fn bench_head_to_head_comparison(c: &mut Criterion) {
         let pattern = "function";
 
         group.bench_function("single_file/xerg_regular", |b| {
-            b.iter(|| bench_xerg_regular(&vec![file_path.clone()], pattern))
+            b.iter(|| bench_xerg_regular(std::slice::from_ref(file_path), pattern))
         });
 
         group.bench_function("single_file/xerg_xtreme", |b| {
-            b.iter(|| bench_xerg_xtreme(&vec![file_path.clone()], pattern))
+            b.iter(|| bench_xerg_xtreme(std::slice::from_ref(file_path), pattern))
         });
 
         group.bench_function("single_file/system_grep", |b| {
@@ -242,7 +251,7 @@ fn bench_head_to_head_comparison(c: &mut Criterion) {
                     .arg(file_path)
                     .output()
                     .expect("Failed to execute grep");
-                output.stdout.len() > 0
+                !output.stdout.is_empty()
             })
         });
     }
@@ -274,7 +283,7 @@ fn bench_head_to_head_comparison(c: &mut Criterion) {
                 .arg(&multi_dir)
                 .output()
                 .expect("Failed to execute grep");
-            output.stdout.len() > 0
+            !output.stdout.is_empty()
         })
     });
 
@@ -297,7 +306,7 @@ fn bench_head_to_head_comparison(c: &mut Criterion) {
                 .arg("src/")
                 .output()
                 .expect("Failed to execute grep");
-            output.stdout.len() > 0
+            !output.stdout.is_empty()
         })
     });
 
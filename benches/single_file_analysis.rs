@@ -4,6 +4,7 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 use tempdir::TempDir;
+use xerg::options::SearchOptions;
 use xerg::output::colors::Color;
 use xerg::search::default::search_files;
 
@@ -37,10 +38,10 @@ fn bench_current_approach(file_path: &Path, pattern: &str) {
     let mut match_count = 0;
 
     for line in reader.lines() {
-        if let Ok(line) = line {
-            if regex.is_match(&line) {
-                match_count += 1;
-            }
+        if let Ok(line) = line
+            && regex.is_match(&line)
+        {
+            match_count += 1;
         }
     }
     // Return count to prevent optimization away
@@ -162,19 +163,17 @@ fn bench_with_without_threading(c: &mut Criterion) {
     let file_path = create_test_file(&temp_dir, "small");
     let files = vec![file_path.clone()];
     let pattern = "use";
-    let color = Color::Red;
+    let options = SearchOptions {
+        color: Color::Red,
+        ..SearchOptions::new(pattern)
+    };
 
     let mut group = c.benchmark_group("threading_overhead");
 
     // Current xerg with threading
     group.bench_function("with_threading", |b| {
         b.iter(|| {
-            let rx = search_files(
-                black_box(&files),
-                black_box(pattern),
-                black_box(&color),
-                false,
-            );
+            let rx = search_files(black_box(&files), black_box(&options));
             while rx.recv().is_ok() {}
         })
     });
@@ -216,7 +215,7 @@ fn bench_memory_usage_patterns(c: &mut Criterion) {
         let bench_name = format!("memory_test_{}_{}_bytes", name, actual_size);
 
         // Test read_to_string memory allocation
-        group.bench_function(&format!("{}_read_to_string", bench_name), |b| {
+        group.bench_function(format!("{}_read_to_string", bench_name), |b| {
             b.iter(|| {
                 let _contents = std::fs::read_to_string(black_box(&file_path)).unwrap();
                 // Measure allocation + deallocation time
@@ -224,7 +223,7 @@ fn bench_memory_usage_patterns(c: &mut Criterion) {
         });
 
         // Test BufReader streaming approach
-        group.bench_function(&format!("{}_bufreader_stream", bench_name), |b| {
+        group.bench_function(format!("{}_bufreader_stream", bench_name), |b| {
             b.iter(|| {
                 let file = File::open(black_box(&file_path)).unwrap();
                 let reader = BufReader::new(file);
@@ -238,7 +237,7 @@ fn bench_memory_usage_patterns(c: &mut Criterion) {
         });
 
         // Test memory mapping approach (should use minimal memory)
-        group.bench_function(&format!("{}_memory_mapping", bench_name), |b| {
+        group.bench_function(format!("{}_memory_mapping", bench_name), |b| {
             b.iter(|| {
                 let file = File::open(black_box(&file_path)).unwrap();
                 let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
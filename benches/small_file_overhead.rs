@@ -1,9 +1,11 @@
-use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
 use memmap2::MmapOptions;
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tempdir::TempDir;
+use xerg::options::SearchOptions;
+use xerg::search::default::search_files;
 
 // Test memory mapping overhead on small files
 fn create_small_test_file(temp_dir: &TempDir, size: &str) -> std::path::PathBuf {
@@ -117,5 +119,47 @@ fn benchmark_small_file_overhead(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, benchmark_small_file_overhead);
+fn bench_search_batch(files: &[PathBuf], sequential_threshold: usize) {
+    let options = SearchOptions {
+        sequential_threshold,
+        ..SearchOptions::new("use")
+    };
+    let rx = search_files(files, &options);
+    while rx.recv().is_ok() {}
+}
+
+// Compare sequential vs pooled processing for batches of tiny files
+fn benchmark_small_batch(c: &mut Criterion) {
+    let temp_dir = TempDir::new("small_batch_bench").unwrap();
+    let template = create_small_test_file(&temp_dir, "tiny");
+
+    let mut group = c.benchmark_group("small_batch");
+    group.sample_size(100);
+
+    for count in 2..=8 {
+        let files: Vec<PathBuf> = (0..count)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("batch_{}.txt", i));
+                std::fs::copy(&template, &path).unwrap();
+                path
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("sequential", count), &files, |b, files| {
+            b.iter(|| bench_search_batch(black_box(files), usize::MAX))
+        });
+
+        group.bench_with_input(BenchmarkId::new("pooled", count), &files, |b, files| {
+            b.iter(|| bench_search_batch(black_box(files), 0))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_small_file_overhead,
+    benchmark_small_batch
+);
 criterion_main!(benches);
@@ -9,76 +9,438 @@
 //! - **Pattern Matching**: Regular expression engine with optimized performance
 //! - **Structured Streaming**: Organized results with comprehensive statistics and timing
 //! - **Directory Traversal**: Recursive scanning with symlink support
-//! - **Colorized Output**: Customizable syntax highlighting (red, green, blue, bold)
+//! - **Colorized Output**: Customizable syntax highlighting with named colors or `rgb:R,G,B` truecolor
 //! - **Search Statistics**: Structured result format with timing metrics using `--stats`
+//! - **Word Matching**: Restrict matches to whole words with `-w`
+//! - **JSON Output**: Machine-readable JSON Lines results with `--json`
+//! - **Standard Input**: Searches piped input when no path is given
+//! - **Multiline Matching**: Patterns can span line breaks with `-U`
+//! - **File Lists**: [`run_files`] searches a given list of files without walking directories
+//! - **File Preview**: [`list_files`] prints the files a search would read, without searching them
+//! - **Name Search**: [`search_names`] matches the pattern against file paths instead of contents
+//! - **In-Place Replacement**: [`run_in_place`] rewrites files with `--replace` applied
+//! - **Dry Runs**: [`run_dry_run`] previews the lines a replacement would change, writing nothing
+//! - **Captured Output**: [`run_to`], [`run_files_to`], [`run_stdin_to`] and their `run_xtreme*_to` counterparts write to any `Write` instead of stdout
 //!
 //! ## Usage
 //!
 //! ```no_run
-//! use xerg::{run, output::colors::Color};
+//! use xerg::{run, options::SearchOptions, output::colors::Color};
 //! use std::path::PathBuf;
 //!
-//! let dir = PathBuf::from(".");
-//! let pattern = "use";
-//! let color = Color::Blue;
-//! let show_stats = true;
+//! let options = SearchOptions {
+//!     color: Color::Blue,
+//!     show_stats: true,
+//!     ..SearchOptions::new("use")
+//! };
 //!
-//! run(&dir, pattern, &color, show_stats);
+//! run(&PathBuf::from("."), &options);
 //! ```
 //!
 //! ## Architecture
 //!
 //! The library is organized into several focused modules:
 //!
-//! - [`colors`]: ANSI color management and formatting
-//! - [`crawler`]: Directory traversal with symlink support
-//! - [`highlighter`]: Regex-based text highlighting
-//! - [`result`]: Message handling and statistics result formatting
-//! - [`search`]: Formatted parallel file processing (use --formatted flag)
-//! - [`search_xtreme`]: **Ultra-fast raw output mode for maximum speed** (default)
+//! - [`output::colors`]: ANSI color management and formatting
+//! - [`output::highlighter`]: Regex-based text highlighting
+//! - [`output::result`]: Message handling and statistics result formatting
+//! - [`options`]: The pattern, highlight color, stats switch and every optional search behavior
+//! - [`search::crawler`]: Directory traversal with symlink support
+//! - [`search::multiline`]: Line blocks for matches spanning line breaks (`-U`)
+//! - [`search::context`]: Lines shown around matches (`-A`, `-B`, `-C`)
+//! - [`search::limit`]: Shared cap for `--max-matches-total`
+//! - [`search::deadline`]: Watchdog that stops new files after `--timeout`
+//! - [`search::in_place`]: Atomic file rewriting for `--in-place`
+//! - [`search::default`]: Structured parallel file processing (default)
+//! - [`search::xtreme`]: **Ultra-fast raw output mode for maximum speed** (`-x`)
 
+pub mod options;
 pub mod output;
 pub mod search;
 
-use crate::output::{
-    colors::Color,
-    result::{print_result, print_xtreme_stats},
+use crate::options::SearchOptions;
+use crate::output::highlighter::TextHighlighter;
+use crate::output::result::{
+    FileMatchResult, display_path, expect_stdout, print_result_grouped_to, print_result_json_to,
+    print_result_summary_json_to, print_result_to, print_result_total_to, print_xtreme_stats_to,
+    relative_path, shown_path, sort_results,
 };
-use crate::search::xtreme::search_files as search_files_xtreme;
-use crate::search::{crawler::get_files, default::search_files};
+use crate::search::xtreme::{
+    search_files_iter_to as search_files_iter_xtreme_to, search_files_to as search_files_xtreme_to,
+    search_stdin_to as search_stdin_xtreme_to,
+};
+use crate::search::{
+    crawler::{get_files_from_paths, get_files_iter, get_files_with_options},
+    default::{search_files, search_stdin},
+    in_place::{FilePreview, preview_files, rewrite_files},
+    progress::ProgressWriter,
+};
+use std::collections::HashSet;
+use std::io::{self, IsTerminal, Write};
 use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
 use std::time::Instant;
 
 /// Run xerg in default mode with formatted output
 ///
 /// This function provides the standard xerg experience with structured,
-/// human-readable output formatting and file headers.
-pub fn run(dir: &PathBuf, pattern: &str, color: &Color, show_stats: bool) {
+/// human-readable output formatting and file headers. When
+/// `options.group_by_content` is set, identical matching lines are grouped
+/// together instead of being printed per file. With `options.json`, matches
+/// are emitted as JSON Lines instead.
+///
+/// Returns `true` if any line matched, so callers can exit like grep.
+pub fn run(dir: &PathBuf, options: &SearchOptions) -> bool {
+    expect_stdout(run_to(&mut _stdout(options), dir, options))
+}
+
+/// Like [`run`], but writes the output to `out` instead of stdout
+///
+/// ```no_run
+/// use xerg::{run_to, options::SearchOptions};
+/// use std::path::PathBuf;
+///
+/// let mut out = Vec::new();
+/// let options = SearchOptions { use_color: false, ..SearchOptions::new("use") };
+/// run_to(&mut out, &PathBuf::from("src"), &options).unwrap();
+/// let text = String::from_utf8(out).unwrap();
+/// ```
+pub fn run_to<W: Write>(out: &mut W, dir: &PathBuf, options: &SearchOptions) -> io::Result<bool> {
+    let start_time = Instant::now();
+    let files = get_files_with_options(dir, &options.crawl);
+    let rx = search_files(&files, options);
+
+    _print_results(out, rx, start_time, options)
+}
+
+/// Run xerg in default mode over an explicit list of files
+///
+/// The files are searched as given, without directory traversal or the crawl
+/// filters in `options.crawl`. Paths that cannot be read are reported as
+/// errors while the remaining files are still searched.
+pub fn run_files(files: &[PathBuf], options: &SearchOptions) -> bool {
+    expect_stdout(run_files_to(&mut _stdout(options), files, options))
+}
+
+/// Like [`run_files`], but writes the output to `out` instead of stdout
+pub fn run_files_to<W: Write>(
+    out: &mut W,
+    files: &[PathBuf],
+    options: &SearchOptions,
+) -> io::Result<bool> {
     let start_time = Instant::now();
-    let files = get_files(dir);
-    let rx = search_files(&files, pattern, color, show_stats);
+    let rx = search_files(files, options);
+
+    _print_results(out, rx, start_time, options)
+}
+
+/// List the files a search of `paths` would read, without searching them
+///
+/// Every crawl filter in `options.crawl` applies, so this shows why a file is
+/// or isn't searched. Each path ends with a newline, or a NUL byte under
+/// `options.null` for `xargs -0`.
+///
+/// Returns `true` if any file was listed.
+pub fn list_files(paths: &[PathBuf], options: &SearchOptions) -> bool {
+    expect_stdout(list_files_to(&mut io::stdout(), paths, options))
+}
+
+/// Like [`list_files`], but writes the paths to `out` instead of stdout
+pub fn list_files_to<W: Write>(
+    out: &mut W,
+    paths: &[PathBuf],
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let files = get_files_from_paths(paths, &options.crawl);
+    let terminator = if options.null { '\0' } else { '\n' };
+    for file in &files {
+        write!(out, "{}{}", shown_path(file, options), terminator)?;
+    }
+
+    Ok(!files.is_empty())
+}
+
+/// Replace every match of `options.pattern` with `template` in `files`, rewriting them in place
+///
+/// Files without matches are left untouched and binary files are skipped.
+/// A summary line with the number of modified files is printed at the end.
+///
+/// Returns `true` if any file was modified.
+pub fn run_in_place(files: &[PathBuf], template: &str, options: &SearchOptions) -> bool {
+    let stats = rewrite_files(files, template, options);
+    expect_stdout(writeln!(_stdout(options), "{}", stats));
+
+    stats.modified > 0
+}
+
+/// Show what [`run_in_place`] would change in `files`, without modifying them
+///
+/// Each file that would be rewritten gets a `path: N replacements` line
+/// followed by its changed lines as unified-diff style hunks, and the summary
+/// line of a real run is printed at the end.
+///
+/// Returns `true` if any file would be modified.
+pub fn run_dry_run(files: &[PathBuf], template: &str, options: &SearchOptions) -> bool {
+    let (stats, previews) = preview_files(files, template, options);
+    let mut out = _stdout(options);
+    expect_stdout(
+        _print_previews(&mut out, &previews, options).and_then(|_| writeln!(out, "{}", stats)),
+    );
 
-    print_result(rx, show_stats, start_time);
+    stats.modified > 0
+}
+
+/// Write the changed lines of each file, with `@@ -old +new @@` headers tracking added lines
+fn _print_previews<W: Write>(
+    out: &mut W,
+    previews: &[FilePreview],
+    options: &SearchOptions,
+) -> io::Result<()> {
+    for preview in previews {
+        let noun = if preview.replacements == 1 {
+            "replacement"
+        } else {
+            "replacements"
+        };
+        writeln!(
+            out,
+            "{}: {} {}",
+            shown_path(&preview.path, options),
+            preview.replacements,
+            noun
+        )?;
+
+        // A template with `\n` turns one line into several, shifting the lines after it
+        let mut offset = 0;
+        for change in &preview.changes {
+            let added = change.after.split('\n').count();
+            let new_line = change.line_number + offset;
+            if added == 1 {
+                writeln!(out, "@@ -{} +{} @@", change.line_number, new_line)?;
+            } else {
+                writeln!(out, "@@ -{} +{},{} @@", change.line_number, new_line, added)?;
+            }
+            writeln!(out, "-{}", change.before)?;
+            for line in change.after.split('\n') {
+                writeln!(out, "+{}", line)?;
+            }
+            offset += added - 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the files under `paths` whose path matches `options.pattern`, without reading them
+///
+/// Like `find | grep`, the pattern is matched against each path below the
+/// searched directory, or the file name for a file given directly, so the
+/// search root itself never matches. The matched part is highlighted and paths
+/// are shown and terminated as in [`list_files`].
+///
+/// Returns `true` if any path matched.
+pub fn search_names(paths: &[PathBuf], options: &SearchOptions) -> bool {
+    expect_stdout(search_names_to(&mut io::stdout(), paths, options))
+}
+
+/// Like [`search_names`], but writes the paths to `out` instead of stdout
+pub fn search_names_to<W: Write>(
+    out: &mut W,
+    paths: &[PathBuf],
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let highlighter = TextHighlighter::from_options(options);
+    let terminator = if options.null { '\0' } else { '\n' };
+    let mut seen = HashSet::new();
+    let mut any_match = false;
+
+    for root in paths {
+        let base = if root.is_dir() {
+            root.as_path()
+        } else {
+            root.parent().unwrap_or(root)
+        };
+        for file in get_files_with_options(root, &options.crawl) {
+            if !seen.insert(file.clone()) {
+                continue;
+            }
+            let name = display_path(relative_path(&file, Some(base)), options.path_separator);
+            if !highlighter.is_match(&name) {
+                continue;
+            }
+
+            // The displayed path ends with the matched part unless --relative cut into it
+            let shown = shown_path(&file, options);
+            let (prefix, name) = match shown.strip_suffix(name.as_ref()) {
+                Some(prefix) => (prefix, name.as_ref()),
+                None => ("", shown.as_ref()),
+            };
+            write!(
+                out,
+                "{}{}{}",
+                prefix,
+                highlighter.highlight(name),
+                terminator
+            )?;
+            any_match = true;
+        }
+    }
+
+    Ok(any_match)
+}
+
+/// Run xerg in default mode over lines piped into standard input
+///
+/// Matches are reported under a `<stdin>` header and printed as each line is
+/// read, so unbounded streams produce output immediately.
+pub fn run_stdin(options: &SearchOptions) -> bool {
+    expect_stdout(run_stdin_to(&mut _stdout(options), options))
+}
+
+/// Like [`run_stdin`], but writes the output to `out` instead of stdout
+pub fn run_stdin_to<W: Write>(out: &mut W, options: &SearchOptions) -> io::Result<bool> {
+    let start_time = Instant::now();
+    let rx = search_stdin(options);
+
+    _print_results(out, rx, start_time, options)
+}
+
+/// Standard output, keeping the `--progress` status line off the results
+///
+/// Results only meet the status line when stdout and stderr are the same
+/// terminal; redirected output is written through without pausing it.
+fn _stdout(options: &SearchOptions) -> ProgressWriter<'_, io::Stdout> {
+    let stdout = io::stdout();
+    let progress = options
+        .scan_progress
+        .as_deref()
+        .filter(|_| stdout.is_terminal() && io::stderr().is_terminal());
+    ProgressWriter::new(stdout, progress)
+}
+
+/// Print default-mode results in the format selected by `options`
+fn _print_results<W: Write>(
+    out: &mut W,
+    rx: Receiver<FileMatchResult>,
+    start_time: Instant,
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let rx = match options.sort {
+        Some(sort) => sort_results(rx, sort, options.sort_reverse),
+        None => rx,
+    };
+
+    if options.total {
+        print_result_total_to(out, rx)
+    } else if options.summary_json {
+        print_result_summary_json_to(out, rx, start_time, options)
+    } else if options.count_matches {
+        print_result_to(out, rx, options.show_stats, start_time, options)
+    } else if options.json {
+        print_result_json_to(out, rx, options.show_stats, start_time)
+    } else if options.group_by_content {
+        print_result_grouped_to(out, rx, options.show_stats, start_time, options)
+    } else {
+        print_result_to(out, rx, options.show_stats, start_time, options)
+    }
 }
 
 /// Run xerg in xtreme mode for maximum performance
 ///
 /// This function provides raw, unformatted output optimized for speed.
 /// Output format: `filepath: line_number: content`
-pub fn run_xtreme(dir: &PathBuf, pattern: &str, color: &Color, show_stats: bool) {
+///
+/// Directories are searched while they are still being walked, unless the
+/// results must be sorted or an `on_progress` callback needs the file total,
+/// which take the full file list. The `--progress` counter works either way.
+///
+/// Returns `true` if any line matched.
+pub fn run_xtreme(dir: &PathBuf, options: &SearchOptions) -> bool {
+    let mut stdout = _stdout(options);
+    expect_stdout(run_xtreme_to(&mut stdout, dir, options))
+}
+
+/// Like [`run_xtreme`], but writes the output to `out` instead of stdout
+///
+/// Errors writing matches are ignored as they are for stdout; only a failure
+/// to write the `--stats` summary is returned.
+pub fn run_xtreme_to<W: Write + Send>(
+    out: &mut W,
+    dir: &PathBuf,
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let start_time = Instant::now();
+    let stats = if dir.is_dir() && options.sort.is_none() && options.progress.is_none() {
+        let files = get_files_iter(dir, &options.crawl);
+        search_files_iter_xtreme_to(out, files, options)
+    } else {
+        let files = get_files_with_options(dir, &options.crawl);
+        search_files_xtreme_to(out, &files, options)
+    };
+
+    if options.show_stats {
+        print_xtreme_stats_to(out, &stats, start_time, options.stats_format)?;
+    }
+
+    Ok(stats.matches > 0)
+}
+
+/// Run xerg in xtreme mode over an explicit list of files
+///
+/// Like [`run_files`], the files are searched without directory traversal.
+pub fn run_xtreme_files(files: &[PathBuf], options: &SearchOptions) -> bool {
+    let mut stdout = _stdout(options);
+    expect_stdout(run_xtreme_files_to(&mut stdout, files, options))
+}
+
+/// Like [`run_xtreme_files`], but writes the output to `out` instead of stdout
+///
+/// As with [`run_xtreme_to`], only a failure to write the `--stats` summary is returned.
+pub fn run_xtreme_files_to<W: Write + Send>(
+    out: &mut W,
+    files: &[PathBuf],
+    options: &SearchOptions,
+) -> io::Result<bool> {
     let start_time = Instant::now();
-    let files = get_files(dir);
-    let (files_processed, lines, matches, skipped) =
-        search_files_xtreme(&files, pattern, color, show_stats);
+    let stats = search_files_xtreme_to(out, files, options);
 
-    if show_stats {
-        print_xtreme_stats(files_processed, lines, matches, skipped, start_time);
+    if options.show_stats {
+        print_xtreme_stats_to(out, &stats, start_time, options.stats_format)?;
     }
+
+    Ok(stats.matches > 0)
+}
+
+/// Run xerg in xtreme mode over lines piped into standard input
+///
+/// Output format: `<stdin>:line_number: content`
+pub fn run_xtreme_stdin(options: &SearchOptions) -> bool {
+    let mut stdout = _stdout(options);
+    expect_stdout(run_xtreme_stdin_to(&mut stdout, options))
+}
+
+/// Like [`run_xtreme_stdin`], but writes the output to `out` instead of stdout
+///
+/// As with [`run_xtreme_to`], only a failure to write the `--stats` summary is returned.
+pub fn run_xtreme_stdin_to<W: Write + Send>(
+    out: &mut W,
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let start_time = Instant::now();
+    let stats = search_stdin_xtreme_to(out, options);
+
+    if options.show_stats {
+        print_xtreme_stats_to(out, &stats, start_time, options.stats_format)?;
+    }
+
+    Ok(stats.matches > 0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::output::colors::Color;
+    use crate::search::crawler::{CrawlOptions, GlobFilter};
     use std::fs::File;
     use std::io::Write;
     use tempdir::TempDir;
@@ -98,7 +460,13 @@ mod tests {
 
         // Test that run function completes without panicking
         // This tests integration of crawler::get_files and search::search_files
-        run(&temp_dir.path().to_path_buf(), pattern, &color, false);
+        run(
+            &temp_dir.path().to_path_buf(),
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -114,7 +482,13 @@ mod tests {
         let color = Color::Blue;
 
         // Test run with single file path
-        run(&test_file, pattern, &color, false);
+        run(
+            &test_file,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -130,7 +504,13 @@ mod tests {
         let color = Color::Green;
 
         // Should handle no matches gracefully
-        run(&temp_dir.path().to_path_buf(), pattern, &color, false);
+        run(
+            &temp_dir.path().to_path_buf(),
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -145,14 +525,137 @@ mod tests {
         let pattern = "pattern";
 
         // Test all color variants
-        run(&temp_dir.path().to_path_buf(), pattern, &Color::Red, false);
         run(
             &temp_dir.path().to_path_buf(),
-            pattern,
-            &Color::Green,
-            false,
+            &SearchOptions {
+                color: Color::Red,
+                ..SearchOptions::new(pattern)
+            },
+        );
+        run(
+            &temp_dir.path().to_path_buf(),
+            &SearchOptions {
+                color: Color::Green,
+                ..SearchOptions::new(pattern)
+            },
+        );
+        run(
+            &temp_dir.path().to_path_buf(),
+            &SearchOptions {
+                color: Color::Blue,
+                ..SearchOptions::new(pattern)
+            },
+        );
+        run(
+            &temp_dir.path().to_path_buf(),
+            &SearchOptions {
+                color: Color::Bold,
+                ..SearchOptions::new(pattern)
+            },
+        );
+    }
+
+    #[test]
+    fn test_run_to_captures_output() {
+        let temp_dir = TempDir::new("lib_run_to_test").unwrap();
+        let test_file = temp_dir.path().join("capture.txt");
+        std::fs::write(&test_file, "skip\nfind me\n").unwrap();
+        let options = SearchOptions {
+            use_color: false,
+            decorate: false,
+            ..SearchOptions::new("find")
+        };
+
+        let mut out = Vec::new();
+        let matched = run_to(&mut out, &test_file, &options).unwrap();
+        assert!(matched);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("--- {} ---\n    2:  find me\n", test_file.display())
         );
-        run(&temp_dir.path().to_path_buf(), pattern, &Color::Blue, false);
-        run(&temp_dir.path().to_path_buf(), pattern, &Color::Bold, false);
+
+        let mut out = Vec::new();
+        let matched = run_xtreme_to(&mut out, &test_file, &options).unwrap();
+        assert!(matched);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}:2: find me\n", test_file.display())
+        );
+
+        let mut out = Vec::new();
+        let files = vec![test_file.clone()];
+        let matched = run_files_to(&mut out, &files, &options).unwrap();
+        assert!(matched);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("--- {} ---\n    2:  find me\n", test_file.display())
+        );
+
+        let mut out = Vec::new();
+        let matched = run_xtreme_files_to(
+            &mut out,
+            &files,
+            &SearchOptions {
+                show_stats: true,
+                ..options
+            },
+        )
+        .unwrap();
+        assert!(matched);
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.starts_with(&format!("{}:2: find me\n", test_file.display())));
+        assert!(output.contains("# Summary:"), "{}", output);
+    }
+
+    #[test]
+    fn test_list_files_to_applies_filters() {
+        let temp_dir = TempDir::new("lib_list_files_test").unwrap();
+        let dir = temp_dir.path().to_path_buf();
+        std::fs::write(dir.join("a.rs"), "").unwrap();
+        std::fs::write(dir.join("b.txt"), "").unwrap();
+        let options = SearchOptions {
+            null: true,
+            crawl: CrawlOptions {
+                globs: GlobFilter::new(&["*.rs".to_string()]).unwrap(),
+                ..CrawlOptions::default()
+            },
+            ..SearchOptions::default()
+        };
+
+        let mut out = Vec::new();
+        assert!(list_files_to(&mut out, std::slice::from_ref(&dir), &options).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}\0", dir.join("a.rs").display())
+        );
+    }
+
+    #[test]
+    fn test_search_names_to_matches_below_root() {
+        let temp_dir = TempDir::new("lib_search_names_test").unwrap();
+        let dir = temp_dir.path().join("root_config");
+        std::fs::create_dir_all(dir.join("config")).unwrap();
+        std::fs::write(dir.join("config").join("app.toml"), "").unwrap();
+        std::fs::write(dir.join("main.rs"), "").unwrap();
+        let options = SearchOptions {
+            use_color: false,
+            ..SearchOptions::new("config")
+        };
+
+        let mut out = Vec::new();
+        let paths = std::slice::from_ref(&dir);
+        assert!(search_names_to(&mut out, paths, &options).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}\n", dir.join("config").join("app.toml").display())
+        );
+
+        let mut out = Vec::new();
+        let options = SearchOptions {
+            pattern: "root".to_string(),
+            ..options
+        };
+        assert!(!search_names_to(&mut out, paths, &options).unwrap());
+        assert!(out.is_empty());
     }
 }
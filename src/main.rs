@@ -1,83 +1,1418 @@
 use clap::Parser;
+use encoding_rs::Encoding;
 use rayon::ThreadPoolBuilder;
 use std::env::current_dir;
+use std::ffi::OsString;
 use std::fs::canonicalize;
+use std::io::{IsTerminal, Read};
 use std::path::{Path, PathBuf};
-use xerg::{output::colors::Color, run, run_xtreme};
+use std::sync::Arc;
+use std::time::Duration;
+use xerg::{
+    list_files,
+    options::{DEFAULT_UI_COLOR, Engine, LineRange, SearchOptions, SortBy, StatsFormat},
+    output::colors::{Color, ColorChoice, TermCaps},
+    output::highlighter::TextHighlighter,
+    run, run_dry_run, run_files, run_in_place, run_stdin, run_xtreme, run_xtreme_files,
+    run_xtreme_stdin,
+    search::crawler::{
+        CrawlOptions, GlobFilter, count_filtered_files, get_files_from_paths, get_files_iter,
+    },
+    search::deadline::Deadline,
+    search::file_types::{self, TypeFilter},
+    search::progress::{ProgressTicker, ScanProgress},
+    search::reader::{BULK_READ_SIZE_THRESHOLD, MEMORY_MAP_SIZE_THRESHOLD},
+    search_names,
+};
 
 fn resolve_path(path: Option<PathBuf>) -> Result<PathBuf, std::io::Error> {
     let final_path = match path {
-        Some(path) => path,
+        Some(path) => expand_path(path)?,
         None => current_dir()?,
     };
 
     canonicalize(final_path)
 }
 
+/// Expand `~`, `~user`, `$VAR` and `${VAR}` in a path the shell passed through unexpanded, e.g. when quoted
+///
+/// A path that already exists is returned as it is, so names that only look
+/// like variables keep working. An unset variable is reported as not found.
+fn expand_path(path: PathBuf) -> Result<PathBuf, std::io::Error> {
+    if path.exists() {
+        return Ok(path);
+    }
+    let Some(text) = path.to_str() else {
+        return Ok(path);
+    };
+
+    let text = expand_user_home(text)?;
+    match shellexpand::full(&text) {
+        Ok(expanded) => Ok(PathBuf::from(expanded.as_ref())),
+        Err(e) => Err(std::io::Error::new(std::io::ErrorKind::NotFound, e)),
+    }
+}
+
+/// Replace a leading `~user` with that user's home directory, leaving `~` alone
+///
+/// An unknown user is an `InvalidInput` error naming them.
+#[cfg(unix)]
+fn expand_user_home(path: &str) -> Result<std::borrow::Cow<'_, str>, std::io::Error> {
+    let Some(rest) = path.strip_prefix('~') else {
+        return Ok(path.into());
+    };
+    let (user, tail) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    if user.is_empty() {
+        return Ok(path.into());
+    }
+
+    match user_home(user) {
+        Some(home) => Ok(format!("{}{}", home.display(), tail).into()),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown user in {}: {}", path, user),
+        )),
+    }
+}
+
+#[cfg(not(unix))]
+fn expand_user_home(path: &str) -> Result<std::borrow::Cow<'_, str>, std::io::Error> {
+    Ok(path.into())
+}
+
+/// The home directory of `user` from the system user database
+#[cfg(unix)]
+fn user_home(user: &str) -> Option<PathBuf> {
+    use std::ffi::{CStr, CString, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+
+    let name = CString::new(user).ok()?;
+    let mut buffer = vec![0 as libc::c_char; 1024];
+    loop {
+        // SAFETY: `passwd` is plain data, for which all zeroes is a valid value
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut found = std::ptr::null_mut();
+        // SAFETY: every pointer refers to live storage of the size given, and
+        // the strings in `entry` point into `buffer`, which outlives their use
+        let code = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                &mut entry,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut found,
+            )
+        };
+        if code == libc::ERANGE {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || found.is_null() || entry.pw_dir.is_null() {
+            return None;
+        }
+
+        // SAFETY: a successful lookup leaves `pw_dir` a NUL-terminated string in `buffer`
+        let home = unsafe { CStr::from_ptr(entry.pw_dir) };
+        return Some(PathBuf::from(OsStr::from_bytes(home.to_bytes())));
+    }
+}
+
+/// Read newline-separated patterns from a file, or from stdin when the path is `-`
+///
+/// Empty lines are ignored.
+fn read_pattern_file(path: &Path) -> Result<Vec<String>, std::io::Error> {
+    let content = if path == Path::new("-") {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Read the newline-separated file paths given to `--files-from`, where `-` means stdin
+fn read_file_list(path: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    Ok(read_pattern_file(path)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Whether standard input is a pipe or redirected file rather than a terminal
+///
+/// Character devices such as `/dev/null` are not treated as input, so a
+/// detached stdin (cron, CI) still searches the current directory.
+#[cfg(unix)]
+fn stdin_is_piped() -> bool {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::FileTypeExt;
+
+    std::io::stdin()
+        .as_fd()
+        .try_clone_to_owned()
+        .and_then(|fd| std::fs::File::from(fd).metadata())
+        .map(|meta| {
+            let file_type = meta.file_type();
+            file_type.is_fifo() || file_type.is_file() || file_type.is_socket()
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn stdin_is_piped() -> bool {
+    !std::io::stdin().is_terminal()
+}
+
+/// Whether `pattern` was given right after `--`, as in `xerg -- src`
+///
+/// Everything after `--` is positional, so the argument there is the pattern
+/// even when a file or directory of that name exists.
+fn pattern_follows_double_dash(args: &[OsString], pattern: &str) -> bool {
+    args.windows(2)
+        .any(|pair| pair[0] == "--" && pair[1] == pattern)
+}
+
+/// Parse the `--threads` value, where `0` means all available cores
+fn parse_threads(value: &str) -> Result<usize, String> {
+    value.trim().parse::<usize>().map_err(|_| {
+        format!(
+            "'{}' is not a valid thread count; expected a whole number (0 uses all cores)",
+            value
+        )
+    })
+}
+
+/// Parse the `--timeout` value, a non-negative number of seconds such as `30` or `0.5`
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| {
+            format!(
+                "'{}' is not a valid timeout; expected a non-negative number of seconds",
+                value
+            )
+        })
+}
+
+/// Parse the `--fuzzy` value, the number of edits a match may differ by
+fn parse_fuzzy(value: &str) -> Result<usize, String> {
+    value.trim().parse::<usize>().map_err(|_| {
+        format!(
+            "'{}' is not a valid edit distance; expected a whole number such as 1",
+            value
+        )
+    })
+}
+
+/// Parse the `--max-columns` value, where `0` lifts the limit
+fn parse_max_columns(value: &str) -> Result<Option<usize>, String> {
+    match value.trim().parse::<usize>() {
+        Ok(0) => Ok(None),
+        Ok(columns) => Ok(Some(columns)),
+        Err(_) => Err(format!(
+            "'{}' is not a valid column count; expected a whole number such as 200",
+            value
+        )),
+    }
+}
+
+/// Parse the `--path-separator` value, which must be a single character
+fn parse_path_separator(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(separator), None) => Ok(separator),
+        _ => Err(format!(
+            "'{}' is not a valid path separator; expected a single character",
+            value
+        )),
+    }
+}
+
+/// Parse a byte size such as `500`, `500K`, `10M` or `1G` (binary multiples, case-insensitive)
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&value[..i], 1u64 << 10),
+        Some((i, 'm' | 'M')) => (&value[..i], 1u64 << 20),
+        Some((i, 'g' | 'G')) => (&value[..i], 1u64 << 30),
+        _ => (value, 1),
+    };
+
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| {
+            format!(
+                "'{}' is not a valid size; expected bytes or a number with a K, M or G suffix",
+                value
+            )
+        })
+}
+
+/// Environment variable overriding the bulk read threshold when `--bulk-threshold` is absent
+const BULK_THRESHOLD_ENV: &str = "XERG_BULK_THRESHOLD";
+/// Environment variable overriding the memory map threshold when `--mmap-threshold` is absent
+const MMAP_THRESHOLD_ENV: &str = "XERG_MMAP_THRESHOLD";
+
+/// Resolve the `(bulk read, memory map)` size thresholds
+///
+/// Each comes from its flag, then its environment variable (looked up through
+/// `env`), then the built-in default. The memory map threshold must stay above
+/// the bulk read one, or no file would ever be memory mapped.
+fn reader_thresholds(
+    bulk: Option<&str>,
+    mmap: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<(u64, u64), String> {
+    let resolve = |flag: Option<&str>, name: &str, default: u64| match flag
+        .map(str::to_string)
+        .or_else(|| env(name))
+    {
+        Some(value) => parse_size(&value),
+        None => Ok(default),
+    };
+
+    let bulk = resolve(bulk, BULK_THRESHOLD_ENV, BULK_READ_SIZE_THRESHOLD)?;
+    let mmap = resolve(mmap, MMAP_THRESHOLD_ENV, MEMORY_MAP_SIZE_THRESHOLD)?;
+    if mmap <= bulk {
+        return Err(format!(
+            "memory map threshold ({} bytes) must be greater than bulk read threshold ({} bytes)",
+            mmap, bulk
+        ));
+    }
+
+    Ok((bulk, mmap))
+}
+
+/// Resolve the worker thread count, defaulting to `cores - 1` so the system stays responsive
+fn thread_count(requested: Option<usize>, cores: usize) -> usize {
+    match requested {
+        Some(0) => cores,
+        Some(n) => n,
+        None => std::cmp::max(1, cores.saturating_sub(1)),
+    }
+}
+
 #[derive(Parser)]
 #[command(
     author,
     version,
     about = "Ultra-fast parallel grep with structured output",
-    long_about = "XErg provides fast parallel grep with pretty formatted output by default.\nUse --xtreme for maximum raw speed when structured output isn't needed."
+    long_about = "XErg provides fast parallel grep with pretty formatted output by default.\nUse --xtreme for maximum raw speed when structured output isn't needed.",
+    disable_help_flag = true
 )]
 struct Cli {
-    pattern: String,
-    path: Option<PathBuf>,
+    #[arg(required_unless_present_any = ["file", "regexp", "type_list", "list_files"])]
+    pattern: Option<String>,
+    #[arg(value_name = "PATH")]
+    paths: Vec<PathBuf>,
 
-    #[arg(long, value_name = "COLOR_NAME", default_value = "red")]
+    #[arg(
+        long,
+        value_name = "WHEN|COLOR_NAME",
+        default_value = "auto",
+        help = "When to color output (auto, always, never); a color name implies always"
+    )]
     color: String,
 
-    #[arg(long, help = "Show search stats per file and total stats summary")]
-    stats: bool,
+    #[arg(long = "no-color", help = "Never color output, same as --color never")]
+    no_color: bool,
+
+    #[arg(
+        long,
+        value_name = "WHEN",
+        help = "When to color headers, line numbers and stats (auto, always, never); defaults to following --color"
+    )]
+    decorations: Option<String>,
+
+    #[arg(
+        long = "ui-color",
+        value_name = "COLOR",
+        help = "Color of headers, line numbers and stats: a color name, rgb:R,G,B or a 256-color code (default 245, gray)"
+    )]
+    ui_color: Option<String>,
+
+    #[arg(long, action = clap::ArgAction::Help, help = "Print help (-h is --no-filename)")]
+    help: Option<bool>,
+
+    #[arg(
+        short = 'H',
+        long = "with-filename",
+        overrides_with = "no_filename",
+        help = "In xtreme mode, print the file path on each line (the default unless a single file is searched)"
+    )]
+    with_filename: bool,
+
+    #[arg(
+        short = 'h',
+        long = "no-filename",
+        overrides_with = "with_filename",
+        help = "In xtreme mode, leave the file path off each line"
+    )]
+    no_filename: bool,
+
+    #[arg(long, help = "Show search stats per file and total stats summary")]
+    stats: bool,
+
+    #[arg(
+        long = "stats-format",
+        value_name = "FORMAT",
+        help = "Print the --stats summary as text (default) or a plain json object; implies --stats"
+    )]
+    stats_format: Option<String>,
+
+    #[arg(
+        long = "stats-by-type",
+        help = "Follow the --stats summary with matches per file extension; implies --stats (default mode only)"
+    )]
+    stats_by_type: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "total", "group_by_content"],
+        help = "After the results, list how long each file took to search, slowest first (default mode only)"
+    )]
+    timing: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "total", "group_by_content"],
+        help = "After the results, list skipped and failed files with the reason on stderr (default mode only)"
+    )]
+    verbose: bool,
+
+    #[arg(
+        short = 'x',
+        long,
+        help = "Use raw speed mode with unformatted output for maximum performance"
+    )]
+    xtreme: bool,
+
+    #[arg(
+        short = 'w',
+        long = "word-regexp",
+        help = "Only match the pattern as a whole word"
+    )]
+    word_regexp: bool,
+
+    #[arg(
+        long = "line-regexp",
+        help = "Only match the pattern against whole lines"
+    )]
+    line_regexp: bool,
+
+    #[arg(
+        short = 'F',
+        long = "fixed-strings",
+        help = "Treat the pattern as a literal string instead of a regex"
+    )]
+    fixed_strings: bool,
+
+    #[arg(
+        short = 'f',
+        long = "file",
+        value_name = "PATH",
+        help = "Read newline-separated patterns from a file ('-' for stdin)"
+    )]
+    file: Option<PathBuf>,
+
+    #[arg(
+        long = "files-from",
+        value_name = "PATH",
+        help = "Search only the newline-separated file paths listed in a file ('-' for stdin)"
+    )]
+    files_from: Option<PathBuf>,
+
+    #[arg(
+        short = 'e',
+        long = "regexp",
+        value_name = "PATTERN",
+        help = "Search for this pattern; repeat to match any of several patterns"
+    )]
+    regexp: Vec<String>,
+
+    #[arg(
+        long = "group-by-content",
+        help = "Group identical matching lines across files, listing each location"
+    )]
+    group_by_content: bool,
+
+    #[arg(
+        long = "max-depth",
+        value_name = "N",
+        help = "Descend at most N directory levels (1 searches only the given directory's files)"
+    )]
+    max_depth: Option<usize>,
+
+    #[arg(
+        long,
+        overrides_with = "no_recursive",
+        help = "Search subdirectories of the given directories (the default)"
+    )]
+    recursive: bool,
+
+    #[arg(
+        long = "no-recursive",
+        overrides_with = "recursive",
+        conflicts_with = "max_depth",
+        help = "Search only the files directly inside the given directories, like --max-depth 1"
+    )]
+    no_recursive: bool,
+
+    #[arg(
+        long = "no-ignore",
+        help = "Don't skip files matched by .gitignore and .ignore rules"
+    )]
+    no_ignore: bool,
+
+    #[arg(
+        long,
+        help = "Search hidden files and directories (names starting with '.')"
+    )]
+    hidden: bool,
+
+    #[arg(
+        long = "follow-symlinks",
+        help = "Follow symbolic links while walking directories (cycles are detected)"
+    )]
+    follow_symlinks: bool,
+
+    #[arg(
+        short = 'g',
+        long = "glob",
+        value_name = "GLOB",
+        help = "Only search files matching this glob, relative to the search path; prefix with '!' to exclude (repeatable)"
+    )]
+    glob: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Only search files whose name or relative path matches this glob (repeatable)"
+    )]
+    include: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Skip files whose name or relative path matches this glob (repeatable)"
+    )]
+    exclude: Vec<String>,
+
+    #[arg(
+        short = 't',
+        long = "type",
+        value_name = "NAME",
+        help = "Only search files of this type, e.g. rust or py (repeatable, see --type-list)"
+    )]
+    file_type: Vec<String>,
+
+    #[arg(
+        short = 'T',
+        long = "type-not",
+        value_name = "NAME",
+        help = "Skip files of this type (repeatable)"
+    )]
+    type_not: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Print the reader chosen for each file (BulkRead, MemoryMap, Streaming, ...) and why to stderr"
+    )]
+    debug: bool,
+
+    #[arg(long = "type-list", help = "List the built-in file types and exit")]
+    type_list: bool,
+
+    #[arg(
+        long = "files",
+        conflicts_with_all = ["regexp", "file", "files_from"],
+        help = "List the files that would be searched, with every filter applied, then exit; positional arguments are all paths"
+    )]
+    list_files: bool,
+
+    #[arg(
+        long = "name-only",
+        conflicts_with_all = ["list_files", "files_from", "json", "total", "replace", "multiline"],
+        help = "Match the pattern against file paths below the searched directories instead of file contents, printing matching paths"
+    )]
+    name_only: bool,
+
+    #[arg(
+        short = 'm',
+        long = "max-count",
+        value_name = "N",
+        help = "Show at most N matching lines per file; with --stats the rest of the file is still read so the counts stay exact"
+    )]
+    max_count: Option<usize>,
+
+    #[arg(
+        long = "max-count-stops-scan",
+        requires = "max_count",
+        help = "Stop reading a file after --max-count matching lines even with --stats, trading exact counts for speed"
+    )]
+    max_count_stops_scan: bool,
+
+    #[arg(
+        short = 'A',
+        long = "after-context",
+        value_name = "N",
+        conflicts_with_all = ["json", "count_matches", "total", "summary_json", "group_by_content", "multiline", "in_place", "dry_run"],
+        help = "Show N lines after each match"
+    )]
+    after_context: Option<usize>,
+
+    #[arg(
+        short = 'B',
+        long = "before-context",
+        value_name = "N",
+        conflicts_with_all = ["json", "count_matches", "total", "summary_json", "group_by_content", "multiline", "in_place", "dry_run"],
+        help = "Show N lines before each match"
+    )]
+    before_context: Option<usize>,
+
+    #[arg(
+        short = 'C',
+        long = "context",
+        value_name = "N",
+        conflicts_with_all = ["json", "count_matches", "total", "summary_json", "group_by_content", "multiline", "in_place", "dry_run"],
+        help = "Show N lines before and after each match; -A and -B take precedence"
+    )]
+    context: Option<usize>,
+
+    #[arg(
+        long = "context-separator",
+        value_name = "STR",
+        default_value = "--",
+        help = "Print STR between groups of context lines that are not adjacent"
+    )]
+    context_separator: String,
+
+    #[arg(
+        long = "no-context-separator",
+        conflicts_with = "context_separator",
+        help = "Print nothing between groups of context lines"
+    )]
+    no_context_separator: bool,
+
+    #[arg(
+        long,
+        value_name = "START:END",
+        conflicts_with = "multiline",
+        help = "Only search lines START to END of each file (1-based, inclusive); either side may be left out, as in 1000: or :500"
+    )]
+    lines: Option<String>,
+
+    #[arg(
+        long = "max-matches-total",
+        value_name = "N",
+        help = "Stop the whole search after about N matching lines across all files (best-effort when searching in parallel)"
+    )]
+    max_matches_total: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Stop opening new files after SECONDS (fractions allowed), print the partial results and exit with 124"
+    )]
+    timeout: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Decode files from this encoding, e.g. utf-16le or latin1 (a byte order mark takes precedence)"
+    )]
+    encoding: Option<String>,
+
+    #[arg(
+        long = "byte-regexp",
+        conflicts_with_all = ["encoding", "multiline"],
+        help = "Search lines that are not valid UTF-8 as raw bytes instead of skipping them, showing invalid bytes as U+FFFD (default mode only)"
+    )]
+    byte_regexp: bool,
+
+    #[arg(
+        short = 'z',
+        long = "search-zip",
+        help = "Search inside gzip-compressed (.gz) files"
+    )]
+    search_zip: bool,
+
+    #[arg(
+        long = "max-filesize",
+        value_name = "SIZE",
+        help = "Skip files larger than SIZE, e.g. 500K or 10M, counting them as skipped"
+    )]
+    max_filesize: Option<String>,
+
+    #[arg(
+        long = "bulk-threshold",
+        value_name = "SIZE",
+        help = "Read single files up to SIZE into memory at once (default: 7000000, env: XERG_BULK_THRESHOLD)"
+    )]
+    bulk_threshold: Option<String>,
+
+    #[arg(
+        long = "mmap-threshold",
+        value_name = "SIZE",
+        help = "Memory map single files up to SIZE and stream larger ones (default: 100000000, env: XERG_MMAP_THRESHOLD)"
+    )]
+    mmap_threshold: Option<String>,
+
+    #[arg(
+        long,
+        overrides_with = "no_mmap",
+        help = "Memory map every single file searched, whatever its size"
+    )]
+    mmap: bool,
+
+    #[arg(
+        long = "no-mmap",
+        overrides_with = "mmap",
+        help = "Never memory map files, reading or streaming them instead (safer on NFS/CIFS mounts)"
+    )]
+    no_mmap: bool,
+
+    #[arg(
+        long,
+        value_name = "KEY",
+        help = "Print results sorted by KEY (path, modified) instead of as they are found; disables streaming"
+    )]
+    sort: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "KEY",
+        conflicts_with = "sort",
+        help = "Like --sort, in reverse order (--sortr modified puts the newest files first)"
+    )]
+    sortr: Option<String>,
+
+    #[arg(
+        short = 'j',
+        long,
+        value_name = "N",
+        help = "Number of worker threads for default and xtreme modes (0 = all cores, default: cores - 1)"
+    )]
+    threads: Option<String>,
+
+    #[arg(
+        long,
+        help = "Print each match as a JSON object per line, with a summary under --stats"
+    )]
+    json: bool,
+
+    #[arg(
+        short = 'r',
+        long,
+        value_name = "TEMPLATE",
+        help = "Print matching lines with each match replaced by TEMPLATE ($1, ${name} refer to capture groups)"
+    )]
+    replace: Option<String>,
+
+    #[arg(
+        long = "in-place",
+        requires = "replace",
+        conflicts_with_all = ["json", "count_matches", "total", "list_files", "lines", "max_count", "encoding"],
+        help = "Rewrite matching files with --replace applied instead of printing them; binary files are skipped"
+    )]
+    in_place: bool,
+
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        requires = "in_place",
+        help = "With --in-place, first copy each rewritten file to its path plus SUFFIX (e.g. .bak)"
+    )]
+    backup: Option<String>,
+
+    #[arg(
+        long = "dry-run",
+        requires = "replace",
+        conflicts_with_all = ["in_place", "json", "count_matches", "total", "list_files", "multiline", "lines", "max_count", "encoding"],
+        help = "With --replace, show how many replacements each file would get and a diff of the changed lines, without writing anything"
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long,
+        help = "Show the 1-based byte column of the first match on each line"
+    )]
+    column: bool,
+
+    #[arg(
+        long = "show-line-match-count",
+        conflicts_with_all = ["json", "count_matches", "total", "group_by_content"],
+        help = "Lead each printed line with how many matches it holds, e.g. (3x)"
+    )]
+    show_line_match_count: bool,
+
+    #[arg(
+        long = "count-matches",
+        help = "Print path:N per matching file, where N counts every match occurrence (grep -c would count matching lines)"
+    )]
+    count_matches: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["count_matches", "json", "group_by_content", "stats", "stats_format", "stats_by_type"],
+        help = "Print only the total number of matches across all files, as a bare number"
+    )]
+    total: bool,
+
+    #[arg(
+        long = "summary-json",
+        conflicts_with_all = ["count_matches", "json", "group_by_content", "total", "stats", "stats_format", "stats_by_type", "timing", "verbose", "in_place", "dry_run"],
+        help = "Print one JSON object with each file's match, line and skipped counts, the totals and the elapsed time, instead of the matches"
+    )]
+    summary_json: bool,
+
+    #[arg(
+        short = 'U',
+        long,
+        help = "Match across line breaks by searching whole file contents; ^ and $ still match at line boundaries"
+    )]
+    multiline: bool,
+
+    #[arg(
+        long = "multiline-dotall",
+        requires = "multiline",
+        help = "With --multiline, let '.' also match newlines"
+    )]
+    multiline_dotall: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["multiline", "byte_regexp", "replace", "word_regexp", "line_regexp"],
+        help = "Match the pattern literally, allowing up to N inserted, deleted or substituted characters (e.g. --fuzzy 1 databse finds database)"
+    )]
+    fuzzy: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ENGINE",
+        conflicts_with = "fuzzy",
+        help = "Matcher for the pattern: auto (substring search unless it has regex syntax), regex, or literal (like -F)"
+    )]
+    engine: Option<String>,
+
+    #[arg(
+        long = "max-columns",
+        value_name = "N",
+        conflicts_with = "multiline",
+        help = "Cut printed lines longer than N characters to a window around the first match (0 = no limit)"
+    )]
+    max_columns: Option<String>,
+
+    #[arg(
+        short = 'Z',
+        long,
+        help = "Follow each printed file path with a NUL byte instead of ':' (for xargs -0)"
+    )]
+    null: bool,
+
+    #[arg(
+        long = "path-separator",
+        value_name = "CHAR",
+        help = "Show this character between path components in output instead of the platform's"
+    )]
+    path_separator: Option<String>,
+
+    #[arg(
+        long,
+        overrides_with = "absolute",
+        help = "Show paths relative to the searched directory, or to the current directory when searching files or several paths"
+    )]
+    relative: bool,
+
+    #[arg(
+        long,
+        overrides_with = "relative",
+        help = "Show full absolute paths (default); overrides --relative"
+    )]
+    absolute: bool,
+
+    #[arg(
+        short = 'b',
+        long,
+        help = "Show the byte offset of the first match on each line"
+    )]
+    byte_offset: bool,
+
+    #[arg(
+        short = 'N',
+        long = "no-line-number",
+        overrides_with = "line_number",
+        help = "Don't print line numbers before matching lines"
+    )]
+    no_line_number: bool,
+
+    #[arg(
+        long = "no-headers",
+        help = "Don't print the --- path --- header above each matching file in default mode"
+    )]
+    no_headers: bool,
+
+    #[arg(
+        short = 'n',
+        long = "line-number",
+        overrides_with = "no_line_number",
+        help = "Print line numbers before matching lines (the default)"
+    )]
+    line_number: bool,
+
+    #[arg(
+        long = "line-number-width",
+        value_name = "N",
+        help = "Pad line numbers to N characters instead of the widest line number in each file (0 disables padding)"
+    )]
+    line_number_width: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Strip leading whitespace from printed lines; columns count from the trimmed text"
+    )]
+    trim: bool,
+
+    #[arg(
+        long,
+        help = "In xtreme mode, print each file's matches under a single path heading"
+    )]
+    heading: bool,
 
     #[arg(
-        short = 'x',
         long,
-        help = "Use raw speed mode with unformatted output for maximum performance"
+        help = "Show a running count of scanned files on stderr (only when stderr is a terminal)"
     )]
-    xtreme: bool,
+    progress: bool,
 }
 
 fn main() {
-    let cores = num_cpus::get();
-    let num_threads = std::cmp::max(1, cores - 1);
+    let cli = Cli::parse();
+
+    if cli.type_list {
+        println!("{}", file_types::type_list());
+        return;
+    }
+
+    let threads = match cli.threads.as_deref().map(parse_threads).transpose() {
+        Ok(threads) => threads,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
     ThreadPoolBuilder::new()
-        .num_threads(num_threads)
+        .num_threads(thread_count(threads, num_cpus::get()))
         .build_global()
         .unwrap();
 
-    let cli = Cli::parse();
+    // With -e or -f, every positional argument is a search path. Multiple
+    // patterns are joined with newlines, which the highlighter treats as alternatives.
+    // --files takes no pattern at all, so its positional arguments are paths too.
+    let positional_paths = || {
+        cli.pattern
+            .iter()
+            .map(PathBuf::from)
+            .chain(cli.paths.iter().cloned())
+            .collect::<Vec<_>>()
+    };
+    let (pattern, paths) = if cli.list_files {
+        (String::new(), positional_paths())
+    } else if !cli.regexp.is_empty() || cli.file.is_some() {
+        let mut patterns = cli.regexp.clone();
 
-    if cli.path.is_none() && Path::new(&cli.pattern).exists() {
-        eprintln!("error: Pattern missing. You provided a path but no search pattern.");
-        eprintln!("Usage: xerg <PATTERN> [PATH] [-- <options>...]");
-        std::process::exit(1)
-    }
+        if let Some(file) = &cli.file {
+            match read_pattern_file(file) {
+                Ok(from_file) if from_file.is_empty() && patterns.is_empty() => {
+                    eprintln!(
+                        "error: pattern file '{}' contains no patterns",
+                        file.display()
+                    );
+                    std::process::exit(2);
+                }
+                Ok(from_file) => patterns.extend(from_file),
+                Err(e) => {
+                    eprintln!(
+                        "error: failed to read pattern file '{}': {}",
+                        file.display(),
+                        e
+                    );
+                    std::process::exit(2);
+                }
+            }
+        }
+
+        (patterns.join("\n"), positional_paths())
+    } else {
+        let pattern = cli.pattern.clone().unwrap_or_default();
 
-    let path = match resolve_path(cli.path) {
-        Ok(path) => path,
-        Err(_) => {
-            eprintln!("error: file or directory does not exist");
-            std::process::exit(1);
+        // A lone argument naming an existing path is most likely a forgotten
+        // pattern, unless `--` marked it as the pattern on purpose
+        let args: Vec<OsString> = std::env::args_os().collect();
+        if cli.paths.is_empty()
+            && cli.files_from.is_none()
+            && Path::new(&pattern).exists()
+            && !pattern_follows_double_dash(&args, &pattern)
+            && !stdin_is_piped()
+        {
+            eprintln!("error: Pattern missing. You provided a path but no search pattern.");
+            eprintln!(
+                "hint: to search for '{}' itself, put it after '--': xerg -- {}",
+                pattern, pattern
+            );
+            eprintln!("Usage: xerg [OPTIONS] <PATTERN> [PATH]...");
+            std::process::exit(2)
         }
+
+        (pattern, cli.paths.clone())
     };
 
-    let color = Color::from_string(&cli.color).unwrap_or_else(|| {
-        eprintln!(
-            "Warning: Invalid color name '{}'. Defaulting to Red.",
-            &cli.color
-        );
-        Color::Red
+    // The listed files replace directory traversal, so a search path makes no sense
+    let files = match &cli.files_from {
+        Some(_) if !paths.is_empty() => {
+            eprintln!("error: --files-from cannot be combined with a search path");
+            std::process::exit(2);
+        }
+        Some(list) if list == Path::new("-") && cli.file.as_deref() == Some(list) => {
+            eprintln!("error: --files-from and --file cannot both read from stdin");
+            std::process::exit(2);
+        }
+        Some(list) => match read_file_list(list) {
+            Ok(files) => Some(files),
+            Err(e) => {
+                eprintln!(
+                    "error: failed to read file list '{}': {}",
+                    list.display(),
+                    e
+                );
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    // Without a path, piped input is searched instead of the current directory,
+    // unless stdin was already consumed by `-f -` or `--files-from -`
+    let read_stdin = paths.is_empty()
+        && files.is_none()
+        && !cli.list_files
+        && !cli.name_only
+        && !cli.in_place
+        && !cli.dry_run
+        && cli.file.as_deref() != Some(Path::new("-"))
+        && stdin_is_piped();
+
+    // Without any path the current directory is searched
+    let requested = if paths.is_empty() {
+        vec![None]
+    } else {
+        paths.into_iter().map(Some).collect()
+    };
+    let paths: Vec<PathBuf> = requested
+        .into_iter()
+        .map(|path| match resolve_path(path.clone()) {
+            Ok(path) => path,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+                eprintln!("error: {}", e);
+                std::process::exit(2);
+            }
+            Err(_) => {
+                match path {
+                    Some(path) => eprintln!(
+                        "error: file or directory does not exist: {}",
+                        path.display()
+                    ),
+                    None => eprintln!("error: file or directory does not exist"),
+                }
+                std::process::exit(2);
+            }
+        })
+        .collect();
+
+    // Like grep, a single explicit file is printed without its path unless -H is given
+    let with_filename = if cli.with_filename || cli.no_filename {
+        cli.with_filename
+    } else {
+        read_stdin || files.is_some() || paths.len() > 1 || !paths[0].is_file()
+    };
+
+    // --color takes either a mode or a highlight color name, which forces color on
+    let (color, color_choice) = match ColorChoice::from_string(&cli.color) {
+        Some(choice) => (Color::Red, choice),
+        None => {
+            let color = cli.color.parse::<Color>().unwrap_or_else(|e| {
+                eprintln!("Warning: {}. Defaulting to Red.", e);
+                Color::Red
+            });
+            (color, ColorChoice::Always)
+        }
+    };
+    let color_choice = if cli.no_color {
+        ColorChoice::Never
+    } else {
+        color_choice
+    };
+    // Structural decoration is colored independently of the match highlights
+    let decoration_choice = match cli.decorations.as_deref() {
+        Some(when) => ColorChoice::from_string(when).unwrap_or_else(|| {
+            eprintln!(
+                "error: invalid --decorations value '{}' (expected auto, always or never)",
+                when
+            );
+            std::process::exit(2);
+        }),
+        None => color_choice,
+    };
+    let ui_color = match cli.ui_color.as_deref().map(Color::parse) {
+        Some(Ok(color)) => color,
+        Some(Err(e)) => {
+            eprintln!("error: invalid --ui-color: {}", e);
+            std::process::exit(2);
+        }
+        None => DEFAULT_UI_COLOR,
+    };
+
+    let globs = match GlobFilter::new(&cli.glob) {
+        Ok(globs) => globs,
+        Err(e) => {
+            eprintln!("error: invalid glob: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let name_globs = match GlobFilter::from_lists(&cli.include, &cli.exclude) {
+        Ok(globs) => globs,
+        Err(e) => {
+            eprintln!("error: invalid --include/--exclude glob: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let types = match TypeFilter::new(&cli.file_type, &cli.type_not) {
+        Ok(types) => types,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let timeout = match cli.timeout.as_deref().map(parse_timeout).transpose() {
+        Ok(timeout) => timeout,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let lines = match cli.lines.as_deref().map(LineRange::parse).transpose() {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let fuzzy = match cli.fuzzy.as_deref().map(parse_fuzzy).transpose() {
+        Ok(fuzzy) => fuzzy,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let max_columns = match cli
+        .max_columns
+        .as_deref()
+        .map(parse_max_columns)
+        .transpose()
+    {
+        Ok(max_columns) => max_columns.flatten(),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let engine = match cli.engine.as_deref() {
+        Some(engine) => match Engine::from_string(engine) {
+            Some(engine) => engine,
+            None => {
+                eprintln!(
+                    "error: unknown engine '{}'; expected 'auto', 'regex' or 'literal'",
+                    engine
+                );
+                std::process::exit(2);
+            }
+        },
+        None => Engine::Auto,
+    };
+
+    // A single searched directory is the root; files and several paths fall back to the working directory
+    let relative_to = cli.relative.then(|| match paths.as_slice() {
+        [dir] if dir.is_dir() => dir.clone(),
+        _ => resolve_path(None).unwrap_or_default(),
     });
 
-    if cli.xtreme {
+    let path_separator = match cli.path_separator.as_deref().map(parse_path_separator) {
+        Some(Ok(separator)) => Some(separator),
+        Some(Err(e)) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+        None => None,
+    };
+
+    let max_filesize = match cli.max_filesize.as_deref().map(parse_size).transpose() {
+        Ok(size) => size,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let (bulk_read_threshold, memory_map_threshold) = match reader_thresholds(
+        cli.bulk_threshold.as_deref(),
+        cli.mmap_threshold.as_deref(),
+        |name| std::env::var(name).ok(),
+    ) {
+        Ok(thresholds) => thresholds,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let encoding = match cli.encoding.as_deref() {
+        Some(label) => match Encoding::for_label(label.as_bytes()) {
+            Some(encoding) => Some(encoding),
+            None => {
+                eprintln!("error: unknown encoding '{}'", label);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    let sort = match cli.sort.as_deref().or(cli.sortr.as_deref()) {
+        Some(key) => match SortBy::from_string(key) {
+            Some(sort) => Some(sort),
+            None => {
+                eprintln!(
+                    "error: unknown sort key '{}'; expected 'path' or 'modified'",
+                    key
+                );
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    let stats_format = match cli.stats_format.as_deref() {
+        Some(format) => match StatsFormat::from_string(format) {
+            Some(format) => format,
+            None => {
+                eprintln!(
+                    "error: unknown stats format '{}'; expected 'text' or 'json'",
+                    format
+                );
+                std::process::exit(2);
+            }
+        },
+        None => StatsFormat::Text,
+    };
+    let show_stats = cli.stats || cli.stats_format.is_some() || cli.stats_by_type;
+
+    let options = SearchOptions {
+        pattern,
+        color,
+        show_stats,
+        word_regexp: cli.word_regexp,
+        line_regexp: cli.line_regexp,
+        fixed_strings: cli.fixed_strings,
+        group_by_content: cli.group_by_content,
+        json: cli.json,
+        use_color: color_choice.enabled(),
+        decorate: decoration_choice.enabled(),
+        ui_color,
+        replace: cli.replace.clone(),
+        backup_suffix: cli.backup.clone(),
+        column: cli.column,
+        line_match_count: cli.show_line_match_count,
+        line_number: !cli.no_line_number,
+        headers: !cli.no_headers,
+        byte_offset: cli.byte_offset,
+        null: cli.null,
+        path_separator,
+        relative_to,
+        with_filename,
+        term_caps: TermCaps::detect(),
+        line_number_width: cli.line_number_width,
+        trim: cli.trim,
+        count_matches: cli.count_matches,
+        total: cli.total,
+        summary_json: cli.summary_json,
+        multiline: cli.multiline,
+        multiline_dotall: cli.multiline_dotall,
+        fuzzy,
+        engine,
+        max_columns,
+        heading: cli.heading,
+        max_count: cli.max_count,
+        max_count_stops_scan: cli.max_count_stops_scan,
+        before_context: cli.before_context.or(cli.context).unwrap_or(0),
+        after_context: cli.after_context.or(cli.context).unwrap_or(0),
+        context_separator: (!cli.no_context_separator).then_some(cli.context_separator),
+        // Progress goes to stderr, so it is only shown where someone is watching
+        scan_progress: (cli.progress && std::io::stderr().is_terminal())
+            .then(|| Arc::new(ScanProgress::new())),
+        lines,
+        max_matches_total: cli.max_matches_total,
+        encoding,
+        byte_regexp: cli.byte_regexp,
+        search_zip: cli.search_zip,
+        max_filesize,
+        sort,
+        sort_reverse: cli.sortr.is_some(),
+        stats_format,
+        stats_by_type: cli.stats_by_type,
+        timing: cli.timing,
+        verbose: cli.verbose,
+        bulk_read_threshold,
+        memory_map_threshold,
+        mmap: match (cli.mmap, cli.no_mmap) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        },
+        debug: cli.debug,
+        // The watchdog starts here, so the time spent walking directories counts too
+        deadline: timeout.map(Deadline::after),
+        crawl: CrawlOptions {
+            max_depth: if cli.no_recursive {
+                Some(1)
+            } else {
+                cli.max_depth
+            },
+            respect_gitignore: !cli.no_ignore,
+            include_hidden: cli.hidden,
+            follow_symlinks: cli.follow_symlinks,
+            globs,
+            name_globs,
+            types,
+        },
+        ..SearchOptions::default()
+    };
+
+    // A directory that filters emptied would otherwise look like one without matches;
+    // finding out takes an extra walk, so only --verbose pays for it
+    if options.verbose && files.is_none() && !read_stdin {
+        for dir in paths.iter().filter(|path| path.is_dir()) {
+            if get_files_iter(dir, &options.crawl).next().is_none() {
+                eprintln!(
+                    "note: no files to search under {} ({} filtered out)",
+                    dir.display(),
+                    count_filtered_files(dir, &options.crawl)
+                );
+            }
+        }
+    }
+
+    if cli.list_files {
+        std::process::exit(if list_files(&paths, &options) { 0 } else { 1 });
+    }
+
+    if let Err(e) = TextHighlighter::try_with_options(&options.pattern, &options.color, &options) {
+        eprintln!("error: invalid pattern: {}", e);
+        std::process::exit(2);
+    }
+
+    if cli.name_only {
+        let matched = search_names(&paths, &options);
+        std::process::exit(if matched { 0 } else { 1 });
+    }
+
+    // Grouping, JSON, totals, timing, verbose and context output need the structured results of the default mode,
+    // as do stats counting past --max-count
+    let counts_past_max =
+        options.show_stats && options.max_count.is_some() && !options.max_count_stops_scan;
+    let xtreme = cli.xtreme
+        && !options.group_by_content
+        && !options.json
+        && !options.total
+        && !options.summary_json
+        && !options.timing
+        && !options.verbose
+        && options.before_context == 0
+        && options.after_context == 0
+        && !counts_past_max;
+
+    // The status line is redrawn from the file counter until the search ends
+    let ticker = options.scan_progress.clone().map(ProgressTicker::start);
+
+    // Several paths are walked up front and searched as one list of files
+    let files = match files {
+        None if paths.len() > 1 => Some(get_files_from_paths(&paths, &options.crawl)),
+        files => files,
+    };
+    let path = &paths[0];
+
+    let matched = if cli.in_place || cli.dry_run {
+        let files = files.unwrap_or_else(|| get_files_from_paths(&paths, &options.crawl));
+        let template = options.replace.as_deref().unwrap_or_default();
+        if cli.dry_run {
+            run_dry_run(&files, template, &options)
+        } else {
+            run_in_place(&files, template, &options)
+        }
+    } else if let Some(files) = &files {
+        if xtreme {
+            run_xtreme_files(files, &options)
+        } else {
+            run_files(files, &options)
+        }
+    } else if read_stdin {
+        if xtreme {
+            run_xtreme_stdin(&options)
+        } else {
+            run_stdin(&options)
+        }
+    } else if xtreme {
         // Use xtreme mode for maximum speed when structured output isn't needed
-        run_xtreme(&path, &cli.pattern, &color, cli.stats);
+        run_xtreme(path, &options)
     } else {
         // Default to formatted output for most users
-        run(&path, &cli.pattern, &color, cli.stats);
+        run(path, &options)
+    };
+    if let Some(ticker) = ticker {
+        ticker.stop();
+    }
+
+    // Like GNU timeout, a search cut short exits with 124 after printing what it found
+    if let (Some(deadline), Some(timeout)) = (&options.deadline, timeout)
+        && deadline.interrupted()
+    {
+        eprintln!(
+            "warning: search timed out after {}s; results are partial",
+            timeout.as_secs_f64()
+        );
+        std::process::exit(124);
     }
+
+    // Like grep: 0 when something matched, 1 when nothing did, 2 for errors
+    std::process::exit(if matched { 0 } else { 1 });
 }
 
 #[cfg(test)]
@@ -96,6 +1431,42 @@ mod tests {
         assert!(result.exists());
     }
 
+    #[test]
+    fn test_expand_path_handles_tilde_and_variables() {
+        let home = std::env::var("HOME").unwrap();
+
+        assert_eq!(
+            expand_path(PathBuf::from("~/missing")).unwrap(),
+            Path::new(&home).join("missing")
+        );
+        assert_eq!(
+            expand_path(PathBuf::from("${HOME}/missing")).unwrap(),
+            Path::new(&home).join("missing")
+        );
+        assert!(expand_path(PathBuf::from("$XERG_SURELY_UNSET_VAR/x")).is_err());
+
+        // Existing paths are never rewritten
+        let temp_dir = TempDir::new("expand_test").unwrap();
+        let literal = temp_dir.path().join("$HOME");
+        File::create(&literal).unwrap();
+        assert_eq!(expand_path(literal.clone()).unwrap(), literal);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_user_home_looks_up_user() {
+        let home = user_home("root").unwrap();
+        assert_eq!(
+            expand_user_home("~root/x").unwrap(),
+            format!("{}/x", home.display())
+        );
+        assert_eq!(expand_user_home("~/x").unwrap(), "~/x");
+
+        let error = expand_user_home("~xerg-no-such-user/x").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("xerg-no-such-user"));
+    }
+
     #[test]
     fn test_resolve_path_with_valid_path() {
         // Test resolve_path with a valid path
@@ -141,9 +1512,9 @@ mod tests {
         let args = vec!["xerg", "pattern", "/path"];
         let cli = Cli::try_parse_from(args).unwrap();
 
-        assert_eq!(cli.pattern, "pattern");
-        assert_eq!(cli.path, Some(PathBuf::from("/path")));
-        assert_eq!(cli.color, "red"); // default value
+        assert_eq!(cli.pattern.as_deref(), Some("pattern"));
+        assert_eq!(cli.paths, vec![PathBuf::from("/path")]);
+        assert_eq!(cli.color, "auto"); // default value
     }
 
     #[test]
@@ -152,19 +1523,374 @@ mod tests {
         let args = vec!["xerg", "pattern", "/path", "--color", "blue"];
         let cli = Cli::try_parse_from(args).unwrap();
 
-        assert_eq!(cli.pattern, "pattern");
-        assert_eq!(cli.path, Some(PathBuf::from("/path")));
+        assert_eq!(cli.pattern.as_deref(), Some("pattern"));
+        assert_eq!(cli.paths, vec![PathBuf::from("/path")]);
         assert_eq!(cli.color, "blue");
     }
 
+    #[test]
+    fn test_cli_multiple_paths() {
+        let args = vec!["xerg", "pattern", "src", "tests", "build.rs"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.pattern.as_deref(), Some("pattern"));
+        assert_eq!(
+            cli.paths,
+            vec![
+                PathBuf::from("src"),
+                PathBuf::from("tests"),
+                PathBuf::from("build.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cli_no_color_flag() {
+        let args = vec!["xerg", "pattern", "--no-color"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert!(cli.no_color);
+        assert_eq!(cli.color, "auto");
+    }
+
+    #[test]
+    fn test_cli_decorations_flag() {
+        let cli = Cli::try_parse_from(["xerg", "pattern", "--decorations", "never"]).unwrap();
+        assert_eq!(cli.decorations.as_deref(), Some("never"));
+
+        let cli = Cli::try_parse_from(["xerg", "pattern"]).unwrap();
+        assert_eq!(cli.decorations, None);
+    }
+
+    #[test]
+    fn test_cli_threads_option() {
+        let cli = Cli::try_parse_from(vec!["xerg", "pattern", "-j", "2"]).unwrap();
+        assert_eq!(cli.threads.as_deref(), Some("2"));
+
+        let cli = Cli::try_parse_from(vec!["xerg", "pattern", "--threads", "0"]).unwrap();
+        assert_eq!(cli.threads.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_parse_threads() {
+        assert_eq!(parse_threads("4"), Ok(4));
+        assert_eq!(parse_threads("0"), Ok(0));
+        assert!(parse_threads("two").unwrap_err().contains("'two'"));
+        assert!(parse_threads("-1").is_err());
+    }
+
+    #[test]
+    fn test_cli_progress_flag() {
+        let cli = Cli::try_parse_from(vec!["xerg", "--progress", "foo"]).unwrap();
+        assert!(cli.progress);
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_timeout("0.5"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_timeout("0"), Ok(Duration::ZERO));
+        assert!(parse_timeout("-1").is_err());
+        assert!(parse_timeout("soon").unwrap_err().contains("'soon'"));
+    }
+
+    #[test]
+    fn test_pattern_follows_double_dash() {
+        let args = |list: &[&str]| list.iter().map(OsString::from).collect::<Vec<_>>();
+        assert!(pattern_follows_double_dash(
+            &args(&["xerg", "--", "src"]),
+            "src"
+        ));
+        assert!(pattern_follows_double_dash(
+            &args(&["xerg", "--stats", "--", "src"]),
+            "src"
+        ));
+        assert!(!pattern_follows_double_dash(&args(&["xerg", "src"]), "src"));
+        assert!(!pattern_follows_double_dash(
+            &args(&["xerg", "src", "--"]),
+            "src"
+        ));
+    }
+
+    #[test]
+    fn test_parse_max_columns() {
+        assert_eq!(parse_max_columns("200"), Ok(Some(200)));
+        assert_eq!(parse_max_columns("0"), Ok(None));
+        assert!(parse_max_columns("wide").unwrap_err().contains("'wide'"));
+    }
+
+    #[test]
+    fn test_parse_fuzzy() {
+        assert_eq!(parse_fuzzy("1"), Ok(1));
+        assert_eq!(parse_fuzzy(" 0 "), Ok(0));
+        assert!(parse_fuzzy("one").unwrap_err().contains("'one'"));
+        assert!(Cli::try_parse_from(["xerg", "--fuzzy", "1", "-w", "foo"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_path_separator() {
+        assert_eq!(parse_path_separator("/"), Ok('/'));
+        assert_eq!(parse_path_separator("\\"), Ok('\\'));
+        assert!(parse_path_separator("").is_err());
+        assert!(parse_path_separator("::").unwrap_err().contains("'::'"));
+    }
+
+    #[test]
+    fn test_parse_size_suffixes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("500K"), Ok(500 * 1024));
+        assert_eq!(parse_size("10m"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_parse_size_invalid() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("K").is_err());
+        assert!(parse_size("10T").is_err());
+        assert!(parse_size("-5M").is_err());
+        assert!(parse_size("99999999999G").is_err());
+    }
+
+    #[test]
+    fn test_reader_thresholds() {
+        let no_env = |_: &str| None;
+        assert_eq!(
+            reader_thresholds(None, None, no_env),
+            Ok((BULK_READ_SIZE_THRESHOLD, MEMORY_MAP_SIZE_THRESHOLD))
+        );
+        assert_eq!(
+            reader_thresholds(Some("1M"), Some("2M"), no_env),
+            Ok((1 << 20, 2 << 20))
+        );
+
+        let env = |name: &str| (name == BULK_THRESHOLD_ENV).then(|| "64K".to_string());
+        assert_eq!(
+            reader_thresholds(None, None, env),
+            Ok((64 << 10, MEMORY_MAP_SIZE_THRESHOLD))
+        );
+        assert_eq!(
+            reader_thresholds(Some("1K"), None, env),
+            Ok((1 << 10, MEMORY_MAP_SIZE_THRESHOLD))
+        );
+    }
+
+    #[test]
+    fn test_reader_thresholds_invalid() {
+        let no_env = |_: &str| None;
+        assert!(reader_thresholds(Some("2M"), Some("1M"), no_env).is_err());
+        assert!(reader_thresholds(Some("1M"), Some("1M"), no_env).is_err());
+        assert!(reader_thresholds(Some("lots"), None, no_env).is_err());
+    }
+
+    #[test]
+    fn test_thread_count() {
+        assert_eq!(thread_count(None, 8), 7);
+        assert_eq!(thread_count(None, 1), 1);
+        assert_eq!(thread_count(Some(0), 8), 8);
+        assert_eq!(thread_count(Some(2), 8), 2);
+    }
+
+    #[test]
+    fn test_cli_replace_option() {
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "--replace", "bar$1"]).unwrap();
+        assert_eq!(cli.replace.as_deref(), Some("bar$1"));
+    }
+
+    #[test]
+    fn test_cli_in_place_requires_replace() {
+        let cli = Cli::try_parse_from(vec![
+            "xerg",
+            "foo",
+            "-r",
+            "bar",
+            "--in-place",
+            "--backup",
+            "~",
+        ])
+        .unwrap();
+        assert!(cli.in_place);
+        assert_eq!(cli.backup.as_deref(), Some("~"));
+
+        assert!(Cli::try_parse_from(vec!["xerg", "foo", "--in-place"]).is_err());
+        assert!(Cli::try_parse_from(vec!["xerg", "foo", "-r", "x", "--backup", "~"]).is_err());
+
+        // Whole files are rewritten, so options limiting what is searched are refused
+        for option in [
+            &["--lines", "1:2"][..],
+            &["-m", "1"],
+            &["--encoding", "latin1"],
+        ] {
+            let args = [&["xerg", "foo", "-r", "bar", "--in-place"][..], option].concat();
+            assert!(Cli::try_parse_from(args).is_err(), "{:?}", option);
+        }
+    }
+
+    #[test]
+    fn test_cli_dry_run_requires_replace() {
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "-r", "bar", "--dry-run"]).unwrap();
+        assert!(cli.dry_run);
+
+        assert!(Cli::try_parse_from(vec!["xerg", "foo", "--dry-run"]).is_err());
+        assert!(
+            Cli::try_parse_from(vec!["xerg", "foo", "-r", "x", "--dry-run", "--in-place"]).is_err()
+        );
+
+        // A dry run previews the real rewrite, so it refuses the same options
+        for option in [
+            &["--lines", "1:2"][..],
+            &["-m", "1"],
+            &["--encoding", "latin1"],
+        ] {
+            let args = [&["xerg", "foo", "-r", "bar", "--dry-run"][..], option].concat();
+            assert!(Cli::try_parse_from(args).is_err(), "{:?}", option);
+        }
+    }
+
+    #[test]
+    fn test_cli_line_number_flags_override_each_other() {
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "-N"]).unwrap();
+        assert!(cli.no_line_number);
+
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "-N", "-n"]).unwrap();
+        assert!(!cli.no_line_number);
+    }
+
+    #[test]
+    fn test_cli_context_options() {
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "-C", "2", "-A", "1"]).unwrap();
+        assert_eq!(cli.context, Some(2));
+        assert_eq!(cli.after_context, Some(1));
+        assert_eq!(cli.before_context, None);
+        assert_eq!(cli.context_separator, "--");
+
+        let result = Cli::try_parse_from(vec![
+            "xerg",
+            "foo",
+            "--context-separator",
+            "::",
+            "--no-context-separator",
+        ]);
+        assert!(result.is_err());
+
+        let result = Cli::try_parse_from(vec!["xerg", "foo", "-C", "1", "--json"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_repeated_glob() {
+        let args = vec!["xerg", "foo", "-g", "*.rs", "--glob", "!tests/*"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.glob, vec!["*.rs", "!tests/*"]);
+    }
+
+    #[test]
+    fn test_cli_type_options() {
+        let args = vec!["xerg", "foo", "-t", "rust", "--type", "py", "-T", "md"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.file_type, vec!["rust", "py"]);
+        assert_eq!(cli.type_not, vec!["md"]);
+    }
+
+    #[test]
+    fn test_cli_type_list_needs_no_pattern() {
+        let cli = Cli::try_parse_from(vec!["xerg", "--type-list"]).unwrap();
+        assert!(cli.type_list);
+    }
+
+    #[test]
+    fn test_cli_files_takes_path_instead_of_pattern() {
+        let cli = Cli::try_parse_from(vec!["xerg", "--files", "src"]).unwrap();
+        assert!(cli.list_files);
+        assert_eq!(cli.pattern.as_deref(), Some("src"));
+
+        assert!(Cli::try_parse_from(vec!["xerg", "--files"]).is_ok());
+        assert!(Cli::try_parse_from(vec!["xerg", "--files", "-e", "foo"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_relative_and_absolute_override_each_other() {
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "--relative"]).unwrap();
+        assert!(cli.relative);
+
+        let cli = Cli::try_parse_from(vec!["xerg", "foo", "--relative", "--absolute"]).unwrap();
+        assert!(!cli.relative && cli.absolute);
+    }
+
+    #[test]
+    fn test_cli_word_regexp_flag() {
+        let args = vec!["xerg", "-w", "log"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.pattern.as_deref(), Some("log"));
+        assert!(cli.word_regexp);
+    }
+
+    #[test]
+    fn test_cli_filename_flags_override_each_other() {
+        let cli = Cli::try_parse_from(vec!["xerg", "-h", "foo"]).unwrap();
+        assert!(cli.no_filename);
+
+        let cli = Cli::try_parse_from(vec!["xerg", "-h", "-H", "foo"]).unwrap();
+        assert!(cli.with_filename);
+        assert!(!cli.no_filename);
+    }
+
+    #[test]
+    fn test_cli_line_regexp_is_long_only() {
+        let cli = Cli::try_parse_from(vec!["xerg", "--line-regexp", "done"]).unwrap();
+        assert!(cli.line_regexp);
+        assert!(!cli.xtreme);
+
+        let cli = Cli::try_parse_from(vec!["xerg", "-x", "done"]).unwrap();
+        assert!(!cli.line_regexp);
+        assert!(cli.xtreme);
+    }
+
+    #[test]
+    fn test_cli_pattern_file_makes_pattern_optional() {
+        let args = vec!["xerg", "-f", "patterns.txt"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.file, Some(PathBuf::from("patterns.txt")));
+        assert_eq!(cli.pattern, None);
+    }
+
+    #[test]
+    fn test_cli_repeated_regexp() {
+        let args = vec!["xerg", "-e", "foo", "-e", "bar", "src"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.regexp, vec!["foo", "bar"]);
+        assert_eq!(cli.pattern.as_deref(), Some("src"));
+    }
+
+    #[test]
+    fn test_cli_requires_pattern_without_file() {
+        let args = vec!["xerg"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_read_pattern_file_skips_empty_lines() {
+        let temp_dir = TempDir::new("pattern_file_test").unwrap();
+        let pattern_file = temp_dir.path().join("patterns.txt");
+        std::fs::write(&pattern_file, "foo\n\nbar\n").unwrap();
+
+        let patterns = read_pattern_file(&pattern_file).unwrap();
+        assert_eq!(patterns, vec!["foo", "bar"]);
+    }
+
     #[test]
     fn test_cli_pattern_only() {
         // Test CLI with just pattern (no path)
         let args = vec!["xerg", "pattern"];
         let cli = Cli::try_parse_from(args).unwrap();
 
-        assert_eq!(cli.pattern, "pattern");
-        assert_eq!(cli.path, None);
-        assert_eq!(cli.color, "red");
+        assert_eq!(cli.pattern.as_deref(), Some("pattern"));
+        assert!(cli.paths.is_empty());
+        assert_eq!(cli.color, "auto");
     }
 }
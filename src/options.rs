@@ -0,0 +1,515 @@
+//! # Search Options
+//!
+//! This module groups the pattern, its highlight color, the stats switch and
+//! every optional switch that tunes how a search is performed, so new flags can
+//! be added without growing every function signature. [`SearchOptions::new`]
+//! starts from a pattern; everything else is set with struct update syntax.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use xerg::options::SearchOptions;
+//!
+//! let options = SearchOptions {
+//!     word_regexp: true,
+//!     max_count: Some(10),
+//!     show_stats: true,
+//!     ..SearchOptions::new("use")
+//! }
+//! .on_progress(Box::new(|done, total| {
+//!     eprintln!("searched {} of {} files", done, total);
+//! }));
+//! ```
+
+use crate::output::colors::{Color, TermCaps};
+use crate::search::crawler::CrawlOptions;
+use crate::search::deadline::Deadline;
+use crate::search::progress::ScanProgress;
+use crate::search::reader::{BULK_READ_SIZE_THRESHOLD, MEMORY_MAP_SIZE_THRESHOLD};
+use encoding_rs::Encoding;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Batches of at most this many files are searched sequentially on the calling thread
+///
+/// Spawning Rayon tasks costs more than searching a few small files, so tiny
+/// batches skip the thread pool entirely. The `small_batch` group in
+/// `benches/small_file_overhead.rs` shows the sequential path ahead for up to
+/// eight tiny files; the default stays lower so a few large files still get
+/// searched in parallel.
+pub const DEFAULT_SEQUENTIAL_THRESHOLD: usize = 4;
+
+/// Gray of the 256-color palette used for decorations unless `--ui-color` is given
+pub const DEFAULT_UI_COLOR: Color = Color::Indexed(245);
+
+/// Key used to order results when `--sort` is given
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    /// Alphabetical by file path
+    Path,
+    /// Last modification time, oldest first
+    Modified,
+}
+
+impl SortBy {
+    /// Parses a `--sort` value (case-insensitive)
+    pub fn from_string(sort_str: &str) -> Option<SortBy> {
+        match sort_str.to_lowercase().as_str() {
+            "path" => Some(SortBy::Path),
+            "modified" => Some(SortBy::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// Inclusive range of 1-based line numbers searched in each file (`--lines`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    /// First line searched
+    pub start: usize,
+    /// Last line searched, or `None` to search to the end of the file
+    pub end: Option<usize>,
+}
+
+impl LineRange {
+    /// Parses a `--lines` value, `START:END` where either side may be left out as in `1000:` or `:500`
+    pub fn parse(range: &str) -> Result<LineRange, String> {
+        let invalid = || {
+            format!(
+                "invalid line range '{}'; expected START:END with line numbers from 1, such as 1000:2000, 1000: or :500",
+                range
+            )
+        };
+        let number = |side: &str| match side.trim() {
+            "" => Ok(None),
+            side => match side.parse::<usize>() {
+                Ok(number) if number > 0 => Ok(Some(number)),
+                _ => Err(invalid()),
+            },
+        };
+
+        let (start, end) = range.split_once(':').ok_or_else(invalid)?;
+        let start = number(start)?.unwrap_or(1);
+        let end = number(end)?;
+        if end.is_some_and(|end| end < start) {
+            return Err(format!(
+                "invalid line range '{}'; END is before START",
+                range
+            ));
+        }
+
+        Ok(LineRange { start, end })
+    }
+
+    /// Whether the 1-based line `number` is inside the range
+    pub fn contains(&self, number: usize) -> bool {
+        number >= self.start && self.end.is_none_or(|end| number <= end)
+    }
+
+    /// Whether the 1-based line `number` is past the end, so no later line is inside either
+    pub fn is_past(&self, number: usize) -> bool {
+        self.end.is_some_and(|end| number > end)
+    }
+}
+
+/// Layout of the `--stats` summary line
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StatsFormat {
+    /// `result: files:N; lines:N; ...` text, colored unless colors are off
+    #[default]
+    Text,
+    /// A single `{"summary":{...}}` JSON object without escape codes
+    Json,
+}
+
+impl StatsFormat {
+    /// Parses a `--stats-format` value (case-insensitive)
+    pub fn from_string(format_str: &str) -> Option<StatsFormat> {
+        match format_str.to_lowercase().as_str() {
+            "text" => Some(StatsFormat::Text),
+            "json" => Some(StatsFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Matcher used for single-line searches (`--engine`)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Engine {
+    /// Substring search when the pattern has no regex syntax, the regex otherwise
+    #[default]
+    Auto,
+    /// Always compile the pattern as a regex
+    Regex,
+    /// Match the pattern as plain text, like `-F`, with substring search
+    Literal,
+}
+
+impl Engine {
+    /// Parses an `--engine` value (case-insensitive)
+    pub fn from_string(engine_str: &str) -> Option<Engine> {
+        match engine_str.to_lowercase().as_str() {
+            "auto" => Some(Engine::Auto),
+            "regex" => Some(Engine::Regex),
+            "literal" => Some(Engine::Literal),
+            _ => None,
+        }
+    }
+}
+
+/// Callback receiving `(files_done, files_total)` as a search advances
+pub type ProgressCallback = Box<dyn Fn(usize, usize) + Send + Sync>;
+
+/// What to search for and how, shared by the default and xtreme search modes
+pub struct SearchOptions {
+    /// Regex to search for; newline-separated patterns match any of them
+    pub pattern: String,
+    /// Color of the match highlights (`--color <COLOR>`)
+    pub color: Color,
+    /// Report per-file and overall statistics (`--stats`)
+    pub show_stats: bool,
+    /// Only match the pattern at word boundaries (`-w`, `--word-regexp`)
+    pub word_regexp: bool,
+    /// Only match the pattern against whole lines (`--line-regexp`)
+    pub line_regexp: bool,
+    /// Treat the pattern as a literal string rather than a regex (`-F`, `--fixed-strings`)
+    pub fixed_strings: bool,
+    /// Group identical matching lines across files (`--group-by-content`)
+    pub group_by_content: bool,
+    /// Emit one JSON object per matching line instead of formatted text (`--json`)
+    pub json: bool,
+    /// Highlight matches with ANSI escape codes (`--color <when>`)
+    pub use_color: bool,
+    /// Color headers, line numbers and stats lines (`--decorations <when>`, follows `--color`)
+    pub decorate: bool,
+    /// Color of the decorations: headers, line numbers and stats lines (`--ui-color`)
+    pub ui_color: Color,
+    /// Print matching lines with each match rewritten by this template (`--replace`)
+    pub replace: Option<String>,
+    /// Copy files rewritten in place to their path plus this suffix first (`--backup`)
+    pub backup_suffix: Option<String>,
+    /// Include the 1-based byte column of the first match on each line (`--column`)
+    pub column: bool,
+    /// Show how many matches each printed line holds, as `(3x)` (`--show-line-match-count`)
+    pub line_match_count: bool,
+    /// Prefix matching lines with their line number (`-N`, `--no-line-number` turns it off)
+    pub line_number: bool,
+    /// Print a `--- path ---` header above each matching file (`--no-headers` turns it off)
+    pub headers: bool,
+    /// Report the byte offset of the first match on each line (`-b`, `--byte-offset`)
+    pub byte_offset: bool,
+    /// Follow every printed file path with a NUL byte instead of `:` (`-Z`, `--null`)
+    pub null: bool,
+    /// Strip leading whitespace from printed lines after matching (`--trim`)
+    pub trim: bool,
+    /// Fixed width line numbers are padded to, instead of fitting each file (`--line-number-width`)
+    pub line_number_width: Option<usize>,
+    /// What the terminal can display; `rgb:` colors are downgraded without truecolor
+    pub term_caps: TermCaps,
+    /// Prefix xtreme-mode lines with the file path (`-H`, `--with-filename`)
+    ///
+    /// The CLI turns this off when a single file is searched, unless overridden.
+    pub with_filename: bool,
+    /// Character shown between path components instead of the platform's (`--path-separator`)
+    pub path_separator: Option<char>,
+    /// Show text output paths relative to this directory when under it (`--relative`)
+    pub relative_to: Option<PathBuf>,
+    /// Print `path:occurrences` per matching file instead of the lines (`--count-matches`)
+    pub count_matches: bool,
+    /// Print only the number of matches across all files (`--total`)
+    pub total: bool,
+    /// Print one JSON report with per-file counts and totals instead of the matches (`--summary-json`)
+    pub summary_json: bool,
+    /// Cut printed matching lines to this many characters around the first match (`--max-columns`)
+    pub max_columns: Option<usize>,
+    /// Run the pattern over whole files so matches can span lines (`-U`, `--multiline`)
+    pub multiline: bool,
+    /// Let `.` match newlines in multiline mode (`--multiline-dotall`)
+    pub multiline_dotall: bool,
+    /// Match the literal pattern within this many edits instead of as a regex (`--fuzzy`)
+    pub fuzzy: Option<usize>,
+    /// Whether lines are matched by substring search or by the regex (`--engine`)
+    pub engine: Engine,
+    /// List skipped and failed files with reasons once the search ends (`--verbose`)
+    pub verbose: bool,
+    /// Print xtreme matches grouped under a per-file path heading (`--heading`)
+    pub heading: bool,
+    /// Stop showing a file's matches after this many matching lines (`-m`, `--max-count`)
+    ///
+    /// With per-file stats the rest of the file is still read so the counts
+    /// cover every match, unless `max_count_stops_scan` is set.
+    pub max_count: Option<usize>,
+    /// Stop reading a file at `max_count` even when its stats are shown, trading exact counts for speed (`--max-count-stops-scan`)
+    pub max_count_stops_scan: bool,
+    /// Non-matching lines to show before each match (`-B`, `--before-context`)
+    pub before_context: usize,
+    /// Non-matching lines to show after each match (`-A`, `--after-context`)
+    pub after_context: usize,
+    /// Printed between context groups that are not adjacent, `None` to print nothing (`--context-separator`)
+    pub context_separator: Option<String>,
+    /// Only search these lines of each file, stopping once past the end (`--lines`)
+    ///
+    /// Lines outside the range are not counted in the `lines` statistics.
+    pub lines: Option<LineRange>,
+    /// Stop searching once this many lines matched across all files (`--max-matches-total`)
+    ///
+    /// Files are searched in parallel, so this is a best-effort cap that may be
+    /// slightly overshot.
+    pub max_matches_total: Option<usize>,
+    /// Transcode files from this encoding to UTF-8 before searching (`--encoding`)
+    pub encoding: Option<&'static Encoding>,
+    /// Search lines that are not valid UTF-8 as raw bytes instead of skipping them (`--byte-regexp`)
+    ///
+    /// Such lines are printed with invalid bytes shown as U+FFFD. Ignored with
+    /// `encoding` or `multiline`, and by the xtreme mode.
+    pub byte_regexp: bool,
+    /// Decompress `.gz` files before searching them (`-z`, `--search-zip`)
+    pub search_zip: bool,
+    /// Skip files larger than this many bytes, counting them as skipped (`--max-filesize`)
+    pub max_filesize: Option<u64>,
+    /// Buffer results and print them in this order instead of as they complete (`--sort`)
+    pub sort: Option<SortBy>,
+    /// Reverse the `sort` order, as `--sortr` does
+    pub sort_reverse: bool,
+    /// Layout of the `--stats` summary line (`--stats-format`)
+    pub stats_format: StatsFormat,
+    /// Follow the stats summary with matches per file extension (`--stats-by-type`)
+    pub stats_by_type: bool,
+    /// List each file's search time, slowest first, after the results (`--timing`)
+    pub timing: bool,
+    /// Directory traversal settings such as `--max-depth`
+    pub crawl: CrawlOptions,
+    /// Largest file size, in bytes, read into memory in one go (`--bulk-threshold`)
+    pub bulk_read_threshold: u64,
+    /// Largest file size, in bytes, memory mapped rather than streamed (`--mmap-threshold`)
+    pub memory_map_threshold: u64,
+    /// Largest file count processed sequentially instead of on the thread pool
+    pub sequential_threshold: usize,
+    /// Always (`--mmap`) or never (`--no-mmap`) memory map files, instead of deciding by size
+    ///
+    /// Memory mapping can fail or misbehave on network filesystems such as NFS
+    /// and CIFS, where `Some(false)` is the safer choice.
+    pub mmap: Option<bool>,
+    /// Stop opening new files once this deadline expires (`--timeout`)
+    pub deadline: Option<Deadline>,
+    /// Log the reader picked for each file and why to stderr (`--debug`)
+    pub debug: bool,
+    /// Throttled progress hook, see [`SearchOptions::on_progress`]
+    pub progress: Option<ProgressCallback>,
+    /// Counter behind the `--progress` status line, bumped as each file is scanned
+    pub scan_progress: Option<Arc<ScanProgress>>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            pattern: String::new(),
+            color: Color::Red,
+            show_stats: false,
+            word_regexp: false,
+            line_regexp: false,
+            fixed_strings: false,
+            group_by_content: false,
+            json: false,
+            use_color: true,
+            decorate: true,
+            ui_color: DEFAULT_UI_COLOR,
+            replace: None,
+            backup_suffix: None,
+            column: false,
+            line_match_count: false,
+            line_number: true,
+            headers: true,
+            byte_offset: false,
+            null: false,
+            path_separator: None,
+            relative_to: None,
+            with_filename: true,
+            term_caps: TermCaps::default(),
+            line_number_width: None,
+            trim: false,
+            count_matches: false,
+            total: false,
+            summary_json: false,
+            max_columns: None,
+            multiline: false,
+            multiline_dotall: false,
+            fuzzy: None,
+            engine: Engine::Auto,
+            verbose: false,
+            heading: false,
+            max_count: None,
+            max_count_stops_scan: false,
+            before_context: 0,
+            after_context: 0,
+            context_separator: Some("--".to_string()),
+            lines: None,
+            max_matches_total: None,
+            encoding: None,
+            byte_regexp: false,
+            search_zip: false,
+            max_filesize: None,
+            sort: None,
+            sort_reverse: false,
+            stats_format: StatsFormat::Text,
+            stats_by_type: false,
+            timing: false,
+            crawl: CrawlOptions::default(),
+            bulk_read_threshold: BULK_READ_SIZE_THRESHOLD,
+            memory_map_threshold: MEMORY_MAP_SIZE_THRESHOLD,
+            sequential_threshold: DEFAULT_SEQUENTIAL_THRESHOLD,
+            mmap: None,
+            deadline: None,
+            debug: false,
+            progress: None,
+            scan_progress: None,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Options searching for `pattern`, with everything else at its default
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Register a callback invoked with `(files_done, files_total)` during a search
+    ///
+    /// Updates are throttled and serialized, so the callback never runs concurrently
+    /// with itself and always sees increasing `files_done` values. A final
+    /// `(total, total)` update is delivered once the search completes.
+    pub fn on_progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+}
+
+impl fmt::Debug for SearchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchOptions")
+            .field("pattern", &self.pattern)
+            .field("color", &self.color)
+            .field("show_stats", &self.show_stats)
+            .field("word_regexp", &self.word_regexp)
+            .field("line_regexp", &self.line_regexp)
+            .field("fixed_strings", &self.fixed_strings)
+            .field("group_by_content", &self.group_by_content)
+            .field("json", &self.json)
+            .field("use_color", &self.use_color)
+            .field("decorate", &self.decorate)
+            .field("ui_color", &self.ui_color)
+            .field("replace", &self.replace)
+            .field("backup_suffix", &self.backup_suffix)
+            .field("column", &self.column)
+            .field("line_match_count", &self.line_match_count)
+            .field("line_number", &self.line_number)
+            .field("headers", &self.headers)
+            .field("byte_offset", &self.byte_offset)
+            .field("null", &self.null)
+            .field("path_separator", &self.path_separator)
+            .field("relative_to", &self.relative_to)
+            .field("with_filename", &self.with_filename)
+            .field("term_caps", &self.term_caps)
+            .field("line_number_width", &self.line_number_width)
+            .field("trim", &self.trim)
+            .field("count_matches", &self.count_matches)
+            .field("total", &self.total)
+            .field("summary_json", &self.summary_json)
+            .field("max_columns", &self.max_columns)
+            .field("multiline", &self.multiline)
+            .field("multiline_dotall", &self.multiline_dotall)
+            .field("fuzzy", &self.fuzzy)
+            .field("engine", &self.engine)
+            .field("verbose", &self.verbose)
+            .field("heading", &self.heading)
+            .field("max_count", &self.max_count)
+            .field("max_count_stops_scan", &self.max_count_stops_scan)
+            .field("before_context", &self.before_context)
+            .field("after_context", &self.after_context)
+            .field("context_separator", &self.context_separator)
+            .field("lines", &self.lines)
+            .field("max_matches_total", &self.max_matches_total)
+            .field("encoding", &self.encoding.map(|e| e.name()))
+            .field("byte_regexp", &self.byte_regexp)
+            .field("search_zip", &self.search_zip)
+            .field("max_filesize", &self.max_filesize)
+            .field("sort", &self.sort)
+            .field("sort_reverse", &self.sort_reverse)
+            .field("stats_format", &self.stats_format)
+            .field("stats_by_type", &self.stats_by_type)
+            .field("timing", &self.timing)
+            .field("crawl", &self.crawl)
+            .field("bulk_read_threshold", &self.bulk_read_threshold)
+            .field("memory_map_threshold", &self.memory_map_threshold)
+            .field("sequential_threshold", &self.sequential_threshold)
+            .field("mmap", &self.mmap)
+            .field("deadline", &self.deadline)
+            .field("debug", &self.debug)
+            .field("progress", &self.progress.as_ref().map(|_| "<callback>"))
+            .field(
+                "scan_progress",
+                &self
+                    .scan_progress
+                    .as_ref()
+                    .map(|progress| progress.scanned()),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_by_from_string() {
+        assert_eq!(SortBy::from_string("path"), Some(SortBy::Path));
+        assert_eq!(SortBy::from_string("PATH"), Some(SortBy::Path));
+        assert_eq!(SortBy::from_string("modified"), Some(SortBy::Modified));
+        assert_eq!(SortBy::from_string("size"), None);
+    }
+
+    #[test]
+    fn test_stats_format_from_string() {
+        assert_eq!(StatsFormat::from_string("text"), Some(StatsFormat::Text));
+        assert_eq!(StatsFormat::from_string("JSON"), Some(StatsFormat::Json));
+        assert_eq!(StatsFormat::from_string("yaml"), None);
+    }
+
+    #[test]
+    fn test_engine_from_string() {
+        assert_eq!(Engine::from_string("auto"), Some(Engine::Auto));
+        assert_eq!(Engine::from_string("Literal"), Some(Engine::Literal));
+        assert_eq!(Engine::from_string("regex"), Some(Engine::Regex));
+        assert_eq!(Engine::from_string("pcre"), None);
+    }
+
+    #[test]
+    fn test_line_range_parse() {
+        let range = |start, end| Ok(LineRange { start, end });
+        assert_eq!(LineRange::parse("1000:2000"), range(1000, Some(2000)));
+        assert_eq!(LineRange::parse("1000:"), range(1000, None));
+        assert_eq!(LineRange::parse(":500"), range(1, Some(500)));
+        assert_eq!(LineRange::parse("5:5"), range(5, Some(5)));
+        assert!(
+            LineRange::parse("7:5")
+                .unwrap_err()
+                .contains("END is before START")
+        );
+        for invalid in ["100", "0:5", "a:b", "1:2:3"] {
+            assert!(LineRange::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_line_range_contains() {
+        let range = LineRange::parse("3:4").unwrap();
+        assert!(!range.contains(2) && range.contains(3) && range.contains(4));
+        assert!(!range.contains(5) && range.is_past(5) && !range.is_past(4));
+        assert!(!LineRange::parse("3:").unwrap().is_past(usize::MAX));
+    }
+}
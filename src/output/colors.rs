@@ -8,17 +8,51 @@
 //! - **Red**: Standard red text highlighting
 //! - **Green**: Standard green text highlighting  
 //! - **Blue**: Standard blue text highlighting
+//! - **Yellow**, **Cyan**, **Magenta**, **White**: Additional standard text colors
 //! - **Bold**: Bold text formatting
+//! - **Rgb**: 24-bit truecolor highlighting, written as `rgb:255,128,0`
+//! - **Indexed**: An entry of the 256-color palette, written as its number such as `245`
+//!
+//! Unknown names are rejected by [`Color::parse`] with the valid names and, for
+//! near-misses, the closest one as a suggestion.
+//!
+//! ## Color Choice
+//!
+//! [`ColorChoice`] decides whether any escape codes are emitted at all. In
+//! `auto` mode colors are only used when stdout is a terminal and `NO_COLOR`
+//! is unset, so redirected output stays free of ANSI sequences. An explicit
+//! `always` still wins over `NO_COLOR`, as the convention asks.
+//!
+//! ## Terminal Capabilities
+//!
+//! [`TermCaps`] records whether the terminal advertises truecolor through
+//! `COLORTERM`. Without it, [`Color::to_code_for`] downgrades `rgb:` colors to
+//! the nearest of the 256 standard terminal colors.
 //!
 //! ## Example
 //!
 //! ```no_run
-//! use xerg::output::colors::Color;
+//! use xerg::output::colors::{Color, ColorChoice};
 //!
 //! let red = Color::Red;
 //! let code = red.to_code(); // Returns "31"
+//! let blue: Color = "blue".parse().unwrap();
+//! println!("{}", blue); // Prints "blue"
+//! let use_color = ColorChoice::Auto.enabled(); // true only on a terminal
 //! ```
 
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+/// Named colors accepted by [`Color::parse`], besides `rgb:R,G,B`
+pub const COLOR_NAMES: [&str; 8] = [
+    "red", "green", "blue", "yellow", "cyan", "magenta", "white", "bold",
+];
+
+/// Largest edit distance at which an unknown name still gets a suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
 /// Represents available color options for text highlighting
 
 #[derive(Debug, PartialEq)]
@@ -29,8 +63,20 @@ pub enum Color {
     Green,
     /// Blue text color (ANSI code 34)
     Blue,
+    /// Yellow text color (ANSI code 33)
+    Yellow,
+    /// Cyan text color (ANSI code 36)
+    Cyan,
+    /// Magenta text color (ANSI code 35)
+    Magenta,
+    /// White text color (ANSI code 37)
+    White,
     /// Bold text formatting (ANSI code 1)
     Bold,
+    /// Truecolor text (ANSI code 38;2;r;g;b)
+    Rgb(u8, u8, u8),
+    /// 256-color palette entry (ANSI code 38;5;n)
+    Indexed(u8),
 }
 
 impl Color {
@@ -44,27 +90,57 @@ impl Color {
     /// let code = Color::Red.to_code();    // Returns "31"
     /// let code = Color::Blue.to_code();   // Returns "34"
     /// let code = Color::Bold.to_code();   // Returns "1"
+    /// let code = Color::Rgb(255, 128, 0).to_code(); // Returns "38;2;255;128;0"
     /// ```
-    pub fn to_code(&self) -> &str {
+    pub fn to_code(&self) -> String {
+        self.to_code_for(TermCaps::default())
+    }
+
+    /// Like [`Color::to_code`], but limited to what the terminal can display
+    ///
+    /// Truecolor is downgraded to the nearest 256-color code when `caps` lacks it.
+    ///
+    /// ```
+    /// use xerg::output::colors::{Color, TermCaps};
+    ///
+    /// let caps = TermCaps { truecolor: false };
+    /// assert_eq!(Color::Rgb(255, 128, 0).to_code_for(caps), "38;5;208");
+    /// assert_eq!(Color::Red.to_code_for(caps), "31");
+    /// ```
+    pub fn to_code_for(&self, caps: TermCaps) -> String {
         match self {
-            Color::Red => "31",
-            Color::Green => "32",
-            Color::Blue => "34",
-            Color::Bold => "1",
+            Color::Red => "31".to_string(),
+            Color::Green => "32".to_string(),
+            Color::Blue => "34".to_string(),
+            Color::Yellow => "33".to_string(),
+            Color::Cyan => "36".to_string(),
+            Color::Magenta => "35".to_string(),
+            Color::White => "37".to_string(),
+            Color::Bold => "1".to_string(),
+            Color::Rgb(r, g, b) if caps.truecolor => format!("38;2;{};{};{}", r, g, b),
+            Color::Rgb(r, g, b) => format!("38;5;{}", _nearest_ansi256(*r, *g, *b)),
+            Color::Indexed(n) => format!("38;5;{}", n),
         }
     }
 
     /// Parses a color from a string representation
     ///
     /// Returns `Some(Color)` if the string matches a valid color name (case-insensitive),
-    /// or `None` if the string doesn't match any known color.
+    /// or `None` if the string doesn't match any known color. Kept for backward
+    /// compatibility; `"red".parse::<Color>()` reports why a value was rejected.
     ///
     /// # Supported Values
     ///
     /// - `"red"` → `Color::Red`
     /// - `"green"` → `Color::Green`
     /// - `"blue"` → `Color::Blue`
+    /// - `"yellow"` → `Color::Yellow`
+    /// - `"cyan"` → `Color::Cyan`
+    /// - `"magenta"` → `Color::Magenta`
+    /// - `"white"` → `Color::White`
     /// - `"bold"` → `Color::Bold`
+    /// - `"rgb:R,G,B"` → `Color::Rgb(R, G, B)` with each component in `0..=255`
+    /// - `"N"` → `Color::Indexed(N)` for a 256-color palette entry in `0..=255`
     ///
     /// # Examples
     ///
@@ -73,19 +149,231 @@ impl Color {
     ///
     /// let color = Color::from_string("red");     // Returns Some(Color::Red)
     /// let color = Color::from_string("BLUE");    // Returns Some(Color::Blue)
+    /// let color = Color::from_string("rgb:255,128,0"); // Returns Some(Color::Rgb(255, 128, 0))
     /// let color = Color::from_string("invalid"); // Returns None
     /// ```
     pub fn from_string(color_str: &str) -> Option<Color> {
-        match color_str.to_lowercase().as_str() {
-            "red" => Some(Color::Red),
-            "green" => Some(Color::Green),
-            "blue" => Some(Color::Blue),
-            "bold" => Some(Color::Bold),
+        Self::parse(color_str).ok()
+    }
+
+    /// Like [`Color::from_string`], but explains why a value was rejected
+    ///
+    /// The error lists the valid color names and, for a near-miss such as
+    /// `read`, suggests the closest one.
+    ///
+    /// ```
+    /// use xerg::output::colors::Color;
+    ///
+    /// assert_eq!(Color::parse("Blue"), Ok(Color::Blue));
+    /// assert!(Color::parse("read").unwrap_err().contains("did you mean 'red'?"));
+    /// ```
+    pub fn parse(color_str: &str) -> Result<Color, String> {
+        let lowercase = color_str.to_lowercase();
+
+        if let Some(components) = lowercase.strip_prefix("rgb:") {
+            return Self::_parse_rgb(components).ok_or_else(|| {
+                format!(
+                    "invalid color '{}'; expected rgb:R,G,B with each component from 0 to 255",
+                    color_str
+                )
+            });
+        }
+
+        if let Ok(index) = lowercase.parse::<u8>() {
+            return Ok(Color::Indexed(index));
+        }
+
+        match lowercase.as_str() {
+            "red" => Ok(Color::Red),
+            "green" => Ok(Color::Green),
+            "blue" => Ok(Color::Blue),
+            "yellow" => Ok(Color::Yellow),
+            "cyan" => Ok(Color::Cyan),
+            "magenta" => Ok(Color::Magenta),
+            "white" => Ok(Color::White),
+            "bold" => Ok(Color::Bold),
+            _ => {
+                let suggestion = COLOR_NAMES
+                    .iter()
+                    .map(|name| (_edit_distance(&lowercase, name), name))
+                    .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE)
+                    .min()
+                    .map(|(_, name)| format!("did you mean '{}'? ", name))
+                    .unwrap_or_default();
+                Err(format!(
+                    "invalid color '{}'; {}valid colors are {}, rgb:R,G,B and 0 to 255",
+                    color_str,
+                    suggestion,
+                    COLOR_NAMES.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Parses the `R,G,B` part of an `rgb:R,G,B` color
+    fn _parse_rgb(components: &str) -> Option<Color> {
+        let mut values = components.split(',').map(|v| v.trim().parse::<u8>());
+
+        match (values.next(), values.next(), values.next(), values.next()) {
+            (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Some(Color::Rgb(r, g, b)),
             _ => None,
         }
     }
 }
 
+impl FromStr for Color {
+    type Err = String;
+
+    /// Same as [`Color::parse`]
+    fn from_str(color_str: &str) -> Result<Self, Self::Err> {
+        Color::parse(color_str)
+    }
+}
+
+impl fmt::Display for Color {
+    /// Writes the canonical lowercase name, `rgb:R,G,B` or palette number, which parses back to the same color
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Red => write!(f, "red"),
+            Color::Green => write!(f, "green"),
+            Color::Blue => write!(f, "blue"),
+            Color::Yellow => write!(f, "yellow"),
+            Color::Cyan => write!(f, "cyan"),
+            Color::Magenta => write!(f, "magenta"),
+            Color::White => write!(f, "white"),
+            Color::Bold => write!(f, "bold"),
+            Color::Rgb(r, g, b) => write!(f, "rgb:{},{},{}", r, g, b),
+            Color::Indexed(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// What the terminal can display, as advertised by its environment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TermCaps {
+    /// 24-bit colors are shown as-is (`COLORTERM=truecolor` or `24bit`)
+    pub truecolor: bool,
+}
+
+impl Default for TermCaps {
+    /// Assume full support, so library output is exactly what was asked for
+    fn default() -> Self {
+        Self { truecolor: true }
+    }
+}
+
+impl TermCaps {
+    /// Read the capabilities from the process environment
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Read the capabilities through `env`, which looks up an environment variable
+    pub fn from_env(env: impl Fn(&str) -> Option<String>) -> Self {
+        let colorterm = env("COLORTERM").unwrap_or_default().to_lowercase();
+        Self {
+            truecolor: colorterm == "truecolor" || colorterm == "24bit",
+        }
+    }
+}
+
+/// Levels of each channel in the 6x6x6 color cube of the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Index of the 256-color palette entry closest to an RGB color
+///
+/// Both the color cube (16-231) and the grayscale ramp (232-255) are
+/// considered, keeping whichever is nearer.
+fn _nearest_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(value))
+            .unwrap_or(0)
+    };
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum::<u32>()
+    };
+
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23);
+    let gray_level = (8 + 10 * gray_step) as u8;
+    let gray_index = 232 + gray_step as usize;
+
+    if distance((gray_level, gray_level, gray_level)) < distance(cube) {
+        gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn _edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// When to emit ANSI escape codes (`--color <when>`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    /// Color only when stdout is a terminal
+    Auto,
+    /// Always color, even when output is redirected
+    Always,
+    /// Never emit escape codes
+    Never,
+}
+
+impl ColorChoice {
+    /// Parses `auto`, `always` or `never` (case-insensitive)
+    pub fn from_string(choice_str: &str) -> Option<ColorChoice> {
+        match choice_str.to_lowercase().as_str() {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    /// Resolves the choice against the current stdout and `NO_COLOR`
+    ///
+    /// `auto` turns color off whenever `NO_COLOR` is set to a non-empty value,
+    /// while an explicit `always` still turns it on.
+    pub fn enabled(&self) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.resolve(std::io::stdout().is_terminal(), no_color)
+    }
+
+    /// Resolves the choice for a given terminal state and `NO_COLOR` setting
+    ///
+    /// Precedence, highest first: `never`/`always`, then `NO_COLOR`, then
+    /// whether stdout is a terminal.
+    pub fn resolve(&self, is_terminal: bool, no_color: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal && !no_color,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,4 +449,139 @@ mod tests {
             assert!(!code.is_empty());
         }
     }
+
+    #[test]
+    fn test_extended_colors() {
+        let colors_list = vec![
+            ("yellow", Color::Yellow, "33"),
+            ("cyan", Color::Cyan, "36"),
+            ("magenta", Color::Magenta, "35"),
+            ("white", Color::White, "37"),
+        ];
+        for (color_str, expected_color, code) in colors_list {
+            assert_eq!(Color::from_string(color_str), Some(expected_color));
+            assert_eq!(Color::from_string(color_str).unwrap().to_code(), code);
+        }
+    }
+
+    #[test]
+    fn test_rgb_color() {
+        let color = Color::from_string("rgb:255,128,0");
+        assert_eq!(color, Some(Color::Rgb(255, 128, 0)));
+        assert_eq!(color.unwrap().to_code(), "38;2;255;128;0");
+        assert_eq!(Color::from_string("RGB:1, 2, 3"), Some(Color::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_indexed_color() {
+        assert_eq!(Color::parse("245"), Ok(Color::Indexed(245)));
+        assert_eq!(Color::Indexed(245).to_code(), "38;5;245");
+        assert_eq!(
+            Color::Indexed(7).to_string().parse::<Color>(),
+            Ok(Color::Indexed(7))
+        );
+        assert!(Color::parse("256").unwrap_err().contains("0 to 255"));
+    }
+
+    #[test]
+    fn test_rgb_color_invalid() {
+        assert!(Color::from_string("rgb:256,0,0").is_none());
+        assert!(Color::from_string("rgb:1,2").is_none());
+        assert!(Color::from_string("rgb:1,2,3,4").is_none());
+        assert!(Color::from_string("rgb:a,b,c").is_none());
+    }
+
+    #[test]
+    fn test_parse_suggests_near_misses() {
+        let err = Color::parse("read").unwrap_err();
+        assert!(
+            err.contains("invalid color 'read'; did you mean 'red'?"),
+            "{}",
+            err
+        );
+        assert!(err.contains("red, green, blue, yellow, cyan, magenta, white, bold"));
+
+        let err = Color::parse("purple").unwrap_err();
+        assert!(!err.contains("did you mean"), "{}", err);
+
+        assert!(
+            Color::parse("rgb:300,0,0")
+                .unwrap_err()
+                .contains("0 to 255")
+        );
+        assert_eq!(Color::parse("CYAN"), Ok(Color::Cyan));
+    }
+
+    #[test]
+    fn test_from_str_and_display_round_trip() {
+        assert_eq!("blue".parse::<Color>(), Ok(Color::Blue));
+        assert!("read".parse::<Color>().is_err());
+
+        for name in COLOR_NAMES {
+            let color: Color = name.parse().unwrap();
+            assert_eq!(color.to_string(), name);
+        }
+        let rgb = Color::Rgb(255, 128, 0);
+        assert_eq!(rgb.to_string(), "rgb:255,128,0");
+        assert_eq!(rgb.to_string().parse::<Color>(), Ok(rgb));
+    }
+
+    #[test]
+    fn test_rgb_downgrades_without_truecolor() {
+        let basic = TermCaps { truecolor: false };
+
+        assert_eq!(Color::Rgb(255, 128, 0).to_code_for(basic), "38;5;208");
+        assert_eq!(Color::Rgb(0, 0, 0).to_code_for(basic), "38;5;16");
+        assert_eq!(Color::Rgb(128, 128, 128).to_code_for(basic), "38;5;244");
+        assert_eq!(
+            Color::Rgb(1, 2, 3).to_code_for(TermCaps::default()),
+            "38;2;1;2;3"
+        );
+        assert_eq!(Color::Blue.to_code_for(basic), "34");
+    }
+
+    #[test]
+    fn test_term_caps_from_colorterm() {
+        let caps = |value: &'static str| TermCaps::from_env(move |_| Some(value.to_string()));
+
+        assert!(caps("truecolor").truecolor);
+        assert!(caps("24BIT").truecolor);
+        assert!(!caps("256color").truecolor);
+        assert!(!TermCaps::from_env(|_| None).truecolor);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(_edit_distance("read", "red"), 1);
+        assert_eq!(_edit_distance("blu", "blue"), 1);
+        assert_eq!(_edit_distance("gren", "green"), 1);
+        assert_eq!(_edit_distance("", "bold"), 4);
+        assert_eq!(_edit_distance("cyan", "cyan"), 0);
+    }
+
+    #[test]
+    fn test_color_choice_from_string() {
+        assert_eq!(ColorChoice::from_string("auto"), Some(ColorChoice::Auto));
+        assert_eq!(
+            ColorChoice::from_string("ALWAYS"),
+            Some(ColorChoice::Always)
+        );
+        assert_eq!(ColorChoice::from_string("never"), Some(ColorChoice::Never));
+        assert_eq!(ColorChoice::from_string("red"), None);
+    }
+
+    #[test]
+    fn test_color_choice_enabled() {
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+
+    #[test]
+    fn test_color_choice_resolve_precedence() {
+        assert!(ColorChoice::Auto.resolve(true, false));
+        assert!(!ColorChoice::Auto.resolve(false, false));
+        assert!(!ColorChoice::Auto.resolve(true, true));
+        assert!(ColorChoice::Always.resolve(false, true));
+        assert!(!ColorChoice::Never.resolve(true, false));
+    }
 }
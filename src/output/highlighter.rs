@@ -6,8 +6,26 @@
 //! ## Features
 //!
 //! - **Regex Pattern Matching**: Uses compiled regex for efficient pattern detection
+//! - **Match Spans**: Exposes the byte range of every match for custom rendering ([`TextHighlighter::match_spans`])
 //! - **ANSI Color Formatting**: Applies color codes around matched text
 //! - **Performance Optimized**: Compiles regex once and reuses for multiple matches
+//! - **Word Matching**: Optionally restricts matches to whole words (`-w`)
+//! - **Line Matching**: Optionally requires the pattern to match a whole line (`--line-regexp`)
+//! - **Literal Matching**: Optionally escapes regex metacharacters in the pattern (`-F`)
+//! - **Multiple Patterns**: Newline-separated patterns are combined into one alternation
+//! - **Pre-colored Input**: Keeps matches colored even when they contain ANSI resets
+//! - **Color Control**: Returns text untouched when colors are disabled (`--color never`)
+//! - **Replacement**: Rewrites matches with a `$1`-style template instead of coloring them (`--replace`)
+//! - **Columns**: Reports the 1-based byte column of the first match (`--column`)
+//! - **Byte Offsets**: Reports where the first match starts in the file (`-b`)
+//! - **Line Match Counts**: Reports how many matches each line holds (`--show-line-match-count`)
+//! - **Trimming**: Optionally drops leading whitespace from printed lines after matching (`--trim`)
+//! - **Long Lines**: Optionally cuts printed lines to a window around their first match (`--max-columns`)
+//! - **Empty Matches**: Leaves zero-width matches uncolored, so an empty pattern matches every line as-is
+//! - **Multiline**: Keeps `^`/`$` line-anchored across whole files and recolors each line of a spanning match (`-U`)
+//! - **Raw Bytes**: Also compiles the pattern for lines that are not valid UTF-8 (`--byte-regexp`)
+//! - **Fuzzy Matching**: Matches within an edit distance through a [`FuzzyMatcher`] instead of the regex (`--fuzzy`)
+//! - **Literal Engine**: Finds regex-free patterns with a [`LiteralMatcher`] substring search (`--engine`)
 //!
 //! ## Example
 //!
@@ -21,27 +39,852 @@
 //! ```
 
 use super::colors::Color;
-use regex::Regex;
+use crate::options::{Engine, SearchOptions};
+use crate::search::fuzzy::FuzzyMatcher;
+use crate::search::literal::{LiteralMatcher, is_literal};
+use regex::{Regex, bytes};
+
+/// ANSI reset sequences that would end a highlight early if left inside a match
+const RESET_SEQUENCES: [&str; 2] = ["\x1b[0m", "\x1b[m"];
 
 pub struct TextHighlighter {
     pub regex: Regex,
+    /// The same pattern over raw bytes, for lines that are not UTF-8 (`--byte-regexp`)
+    pub bytes_regex: Option<bytes::Regex>,
+    /// Approximate matcher used instead of `regex` to find matches (`--fuzzy`)
+    pub fuzzy: Option<FuzzyMatcher>,
+    /// Substring matcher used instead of `regex` for plain-text patterns (`--engine`)
+    pub literal: Option<LiteralMatcher>,
+    /// `replace_all` template coloring `$0`, for callers doing their own substitution
     pub highlighted_pattern: String,
+    color_start: String,
+    use_color: bool,
+    replacement: Option<String>,
+    show_column: bool,
+    show_line_match_count: bool,
+    show_byte_offset: bool,
+    /// Strip leading whitespace from output lines once they matched (`--trim`)
+    trim: bool,
+    /// Cut printed lines longer than this many characters around their first match (`--max-columns`)
+    max_columns: Option<usize>,
+}
+
+/// Apply the pattern transforms requested in `options` before compiling
+///
+/// Fuzzy patterns and `--engine literal` ones are literal, so they are
+/// escaped like `-F` ones.
+///
+/// Like grep, a pattern containing newlines is treated as several patterns, each
+/// escaped individually under `-F` and combined into a single alternation. The
+/// user pattern is wrapped in a non-capturing group so alternations such as
+/// `foo|bar` keep their meaning once word boundaries or line anchors are
+/// added around them.
+/// Under `--multiline` the regex runs over whole files, so `(?mR)` keeps `^`
+/// and `$` anchored at `\n` or `\r\n` line breaks as they are for single lines.
+fn build_pattern(pattern: &str, options: &SearchOptions) -> String {
+    let escape = |p: &str| {
+        if options.fixed_strings || options.fuzzy.is_some() || options.engine == Engine::Literal {
+            regex::escape(p)
+        } else {
+            p.to_string()
+        }
+    };
+
+    let pattern = if pattern.contains('\n') {
+        pattern
+            .split('\n')
+            .map(|p| format!("(?:{})", escape(p)))
+            .collect::<Vec<_>>()
+            .join("|")
+    } else {
+        escape(pattern)
+    };
+
+    let pattern = if options.word_regexp {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern
+    };
+
+    let pattern = if options.line_regexp {
+        format!("^(?:{})$", pattern)
+    } else {
+        pattern
+    };
+
+    match (options.multiline, options.multiline_dotall) {
+        (false, _) => pattern,
+        (true, false) => format!("(?mR){}", pattern),
+        (true, true) => format!("(?mRs){}", pattern),
+    }
+}
+
+/// Substring matcher standing in for the regex when `--engine` allows it
+///
+/// Only single patterns qualify, and only without `-w`, `--line-regexp`,
+/// `--multiline` or `--fuzzy`, whose matches the plain text cannot express.
+fn literal_matcher(pattern: &str, options: &SearchOptions) -> Option<LiteralMatcher> {
+    let plain = match options.engine {
+        Engine::Regex => false,
+        Engine::Literal => !pattern.is_empty(),
+        Engine::Auto => (options.fixed_strings && !pattern.is_empty()) || is_literal(pattern),
+    };
+    let constrained = options.word_regexp
+        || options.line_regexp
+        || options.multiline
+        || options.fuzzy.is_some()
+        || pattern.contains('\n');
+
+    (plain && !constrained).then(|| LiteralMatcher::new(pattern))
+}
+
+/// Byte range of the `max`-character window of `text` centered on its first match
+///
+/// Returns `None` when `text` already fits. Without a match the window starts
+/// at the beginning of the line.
+fn column_window(text: &str, spans: &[(usize, usize)], max: usize) -> Option<(usize, usize)> {
+    let starts: Vec<usize> = text.char_indices().map(|(offset, _)| offset).collect();
+    if starts.len() <= max {
+        return None;
+    }
+
+    let char_at = |offset: usize| starts.partition_point(|&start| start < offset);
+    let center = spans
+        .iter()
+        .find(|(start, end)| start < end)
+        .map_or(0, |&(start, end)| (char_at(start) + char_at(end)) / 2);
+    let first = center.saturating_sub(max / 2).min(starts.len() - max);
+    let byte_at = |index: usize| starts.get(index).copied().unwrap_or(text.len());
+    Some((byte_at(first), byte_at(first + max)))
+}
+
+/// Marker standing in for `bytes` cut from a line by `--max-columns`, empty when nothing was cut
+fn omitted_marker(bytes: usize) -> String {
+    if bytes == 0 {
+        String::new()
+    } else {
+        format!("[... omitted {} bytes ...]", bytes)
+    }
+}
+
+/// Leading whitespace dropped by `--trim`, never a line break
+fn _is_indent(c: char) -> bool {
+    c.is_whitespace() && c != '\n'
+}
+
+/// Sorted non-empty spans, with touching or overlapping ones joined into one
+fn merge_spans(spans: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut sorted: Vec<_> = spans.iter().filter(|(start, end)| start < end).collect();
+    sorted.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(sorted.len());
+    for &(start, end) in sorted {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Strip the leading whitespace of every line in `text` for `--trim`
+///
+/// Spans are moved along with the text; a span starting inside removed
+/// indentation starts at the first kept character instead.
+fn trim_lines(text: &str, spans: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    let mut trimmed = String::with_capacity(text.len());
+    // (line start, indentation length, bytes removed before the line)
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            trimmed.push('\n');
+        }
+        let kept = line.trim_start_matches(_is_indent);
+        lines.push((
+            line_start,
+            line.len() - kept.len(),
+            line_start - trimmed.len(),
+        ));
+        trimmed.push_str(kept);
+        line_start += line.len() + 1;
+    }
+
+    let map = |offset: usize| {
+        let line = lines.partition_point(|&(start, _, _)| start <= offset) - 1;
+        let (start, indent, removed) = lines[line];
+        offset - removed - (offset - start).min(indent)
+    };
+    let spans = spans
+        .iter()
+        .map(|&(start, end)| (map(start), map(end)))
+        .collect();
+
+    (trimmed, spans)
 }
 
 impl TextHighlighter {
     pub fn new(pattern: &str, color: &Color) -> Self {
-        let regex = Regex::new(pattern).unwrap();
-        let color_code = color.to_code();
+        Self::with_options(pattern, color, &SearchOptions::default())
+    }
 
-        Self {
-            regex,
+    /// Highlighter for `options.pattern` in `options.color`, as every search builds it
+    pub fn from_options(options: &SearchOptions) -> Self {
+        Self::with_options(&options.pattern, &options.color, options)
+    }
+
+    pub fn with_options(pattern: &str, color: &Color, options: &SearchOptions) -> Self {
+        Self::try_with_options(pattern, color, options).unwrap()
+    }
+
+    /// Like [`TextHighlighter::with_options`], but returns an error for an invalid pattern
+    pub fn try_with_options(
+        pattern: &str,
+        color: &Color,
+        options: &SearchOptions,
+    ) -> Result<Self, regex::Error> {
+        let fuzzy = options
+            .fuzzy
+            .map(|max_edits| FuzzyMatcher::new(pattern, max_edits));
+        let literal = literal_matcher(pattern, options);
+        let pattern = build_pattern(pattern, options);
+        let regex = Regex::new(&pattern)?;
+        let bytes_regex = if options.byte_regexp {
+            Some(bytes::Regex::new(&pattern)?)
+        } else {
+            None
+        };
+        let color_code = color.to_code_for(options.term_caps);
+
+        Ok(Self {
             highlighted_pattern: format!("\x1b[{}m$0\x1b[0m", color_code),
+            color_start: format!("\x1b[{}m", color_code),
+            use_color: options.use_color,
+            replacement: options.replace.clone(),
+            show_column: options.column,
+            show_line_match_count: options.line_match_count,
+            show_byte_offset: options.byte_offset,
+            trim: options.trim,
+            max_columns: options.max_columns,
+            regex,
+            bytes_regex,
+            fuzzy,
+            literal,
+        })
+    }
+
+    /// Byte ranges of every match in `text`, in order
+    ///
+    /// The building block for custom rendering: [`TextHighlighter::highlight`]
+    /// wraps these ranges in ANSI codes, and `--json` reports them as-is.
+    /// Zero-width matches are included as empty ranges. Under `--fuzzy` a line
+    /// has at most one span, its closest match.
+    pub fn match_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        if let Some(fuzzy) = &self.fuzzy {
+            return fuzzy.find(text).into_iter().collect();
+        }
+        if let Some(literal) = &self.literal {
+            return literal.find_iter(text).collect();
         }
+
+        self.regex
+            .find_iter(text)
+            .map(|m| (m.start(), m.end()))
+            .collect()
     }
 
+    /// Color every match in `text`, or apply the `--replace` template and `--trim`
+    ///
+    /// A convenience wrapper over [`TextHighlighter::match_spans`] and
+    /// [`TextHighlighter::highlight_spans`]. Zero-width matches (an empty
+    /// pattern matches at every position) get no codes.
     pub fn highlight(&self, text: &str) -> String {
-        self.regex
-            .replace_all(text, &self.highlighted_pattern)
-            .to_string()
+        // Replacements never look at the spans, and uncolored output only
+        // needs them to place the --max-columns window
+        if self.replacement.is_some() || !(self.use_color || self.max_columns.is_some()) {
+            return self.highlight_spans(text, &[]);
+        }
+
+        self.highlight_spans(text, &self.match_spans(text))
+    }
+
+    /// Substitute every match with `template`, expanding `$1`, `${name}` and `$0` references
+    ///
+    /// The rewritten line is returned without color codes so it can be piped
+    /// or saved as-is.
+    pub fn highlight_replace(&self, text: &str, template: &str) -> String {
+        self.regex.replace_all(text, template).to_string()
+    }
+
+    /// 1-based byte column of the first match when `--column` is enabled
+    ///
+    /// Columns count bytes rather than characters, matching the offsets
+    /// reported by `--json`.
+    pub fn match_column(&self, text: &str) -> Option<usize> {
+        if !self.show_column {
+            return None;
+        }
+
+        self._first_match(text)
+            .map(|start| self.trimmed_offset(text, start) + 1)
+    }
+
+    /// Number of matches in `text` when `--show-line-match-count` is enabled
+    pub fn line_match_count(&self, text: &str) -> Option<usize> {
+        self.show_line_match_count.then(|| self.match_count(text))
+    }
+
+    /// Absolute byte offset of the first match in `text`, if `--byte-offset` is enabled
+    ///
+    /// `line_offset` is where the line starts in the file, so the result points
+    /// at the match itself like `grep -ob`.
+    pub fn match_offset(&self, text: &str, line_offset: usize) -> Option<usize> {
+        if !self.show_byte_offset {
+            return None;
+        }
+
+        self._first_match(text).map(|start| line_offset + start)
+    }
+
+    /// Whether `text` contains a match
+    pub fn is_match(&self, text: &str) -> bool {
+        if let Some(literal) = &self.literal {
+            return literal.is_match(text);
+        }
+        match &self.fuzzy {
+            Some(fuzzy) => fuzzy.is_match(text),
+            None => self.regex.is_match(text),
+        }
+    }
+
+    /// Number of matches in `text`, which is at most one per line under `--fuzzy`
+    pub fn match_count(&self, text: &str) -> usize {
+        if let Some(literal) = &self.literal {
+            return literal.find_iter(text).count();
+        }
+        match &self.fuzzy {
+            Some(fuzzy) => usize::from(fuzzy.is_match(text)),
+            None => self.regex.find_iter(text).count(),
+        }
+    }
+
+    /// Byte offset where the first match in `text` starts
+    fn _first_match(&self, text: &str) -> Option<usize> {
+        if let Some(literal) = &self.literal {
+            return literal.find_iter(text).next().map(|(start, _)| start);
+        }
+        match &self.fuzzy {
+            Some(fuzzy) => fuzzy.find(text).map(|(start, _)| start),
+            None => self.regex.find(text).map(|m| m.start()),
+        }
+    }
+
+    /// Like [`TextHighlighter::highlight`], but colors the given byte ranges of `text`
+    ///
+    /// `--multiline` blocks are highlighted this way because a match may rely
+    /// on text past the end of the block, such as its final line break.
+    pub fn highlight_spans(&self, text: &str, spans: &[(usize, usize)]) -> String {
+        if let Some(template) = &self.replacement {
+            let replaced = self.highlight_replace(text, template);
+            let replaced = if self.trim {
+                trim_lines(&replaced, &[]).0
+            } else {
+                replaced
+            };
+            return self.fit_columns(&replaced, &[]);
+        }
+
+        if self.trim {
+            let (trimmed, spans) = trim_lines(text, spans);
+            return self.fit_columns(&trimmed, &spans);
+        }
+
+        self.fit_columns(text, spans)
+    }
+
+    /// Color `text`, cut down to the `--max-columns` window around its first match when too long
+    ///
+    /// Each cut end is replaced by an `[... omitted N bytes ...]` marker.
+    fn fit_columns(&self, text: &str, spans: &[(usize, usize)]) -> String {
+        let Some((start, end)) = self
+            .max_columns
+            .and_then(|max| column_window(text, spans, max))
+        else {
+            return self.color_spans(text, spans);
+        };
+
+        let spans: Vec<_> = spans
+            .iter()
+            .map(|&(s, e)| (s.clamp(start, end) - start, e.clamp(start, end) - start))
+            .collect();
+        format!(
+            "{}{}{}",
+            omitted_marker(start),
+            self.color_spans(&text[start..end], &spans),
+            omitted_marker(text.len() - end)
+        )
+    }
+
+    /// Wrap each non-empty span of `text` in the highlight color
+    ///
+    /// Touching or overlapping spans share one pair of codes.
+    fn color_spans(&self, text: &str, spans: &[(usize, usize)]) -> String {
+        if !self.use_color {
+            return text.to_string();
+        }
+
+        let mut highlighted = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end) in merge_spans(spans) {
+            highlighted.push_str(&text[last..start]);
+            highlighted.push_str(&self.color_start);
+            highlighted.push_str(&self.neutralize_resets(&text[start..end]));
+            highlighted.push_str("\x1b[0m");
+            last = end;
+        }
+        highlighted.push_str(&text[last..]);
+        highlighted
+    }
+
+    /// Like [`TextHighlighter::match_column`], for the first of the given spans
+    ///
+    /// `text` is the block the spans point into, needed to adjust for `--trim`.
+    pub fn span_column(&self, text: &str, spans: &[(usize, usize)]) -> Option<usize> {
+        spans
+            .first()
+            .filter(|_| self.show_column)
+            .map(|&(start, _)| self.trimmed_offset(text, start) + 1)
+    }
+
+    /// Like [`TextHighlighter::line_match_count`], counting the given spans
+    pub fn span_match_count(&self, spans: &[(usize, usize)]) -> Option<usize> {
+        self.show_line_match_count.then_some(spans.len())
+    }
+
+    /// Like [`TextHighlighter::match_offset`], for the first of the given spans
+    pub fn span_offset(&self, spans: &[(usize, usize)], line_offset: usize) -> Option<usize> {
+        spans
+            .first()
+            .filter(|_| self.show_byte_offset)
+            .map(|&(start, _)| line_offset + start)
+    }
+
+    /// Where `offset` in the first line of `text` ends up once `--trim` removes its indentation
+    fn trimmed_offset(&self, text: &str, offset: usize) -> usize {
+        if !self.trim {
+            return offset;
+        }
+
+        let indent = text.len() - text.trim_start_matches(_is_indent).len();
+        offset.saturating_sub(indent)
+    }
+
+    /// Re-apply the highlight color after every reset found inside a matched span
+    ///
+    /// A `--multiline` span is also closed before each line break and reopened
+    /// after it, so line number prefixes printed in between stay uncolored.
+    fn neutralize_resets(&self, matched: &str) -> String {
+        RESET_SEQUENCES
+            .iter()
+            .fold(matched.to_string(), |span, reset| {
+                span.replace(reset, &format!("\x1b[0m{}", self.color_start))
+            })
+            .replace('\n', &format!("\x1b[0m\n{}", self.color_start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_options() -> SearchOptions {
+        SearchOptions {
+            word_regexp: true,
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_matches_and_highlights_closest_span() {
+        let options = SearchOptions {
+            fuzzy: Some(1),
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("databse(", &Color::Red, &options);
+
+        assert!(highlighter.is_match("a database( call"));
+        assert!(!highlighter.is_match("a datum"));
+        assert_eq!(highlighter.match_count("database( database("), 1);
+        assert_eq!(highlighter.match_spans("a database("), vec![(2, 11)]);
+        assert_eq!(
+            highlighter.highlight("a database("),
+            "a \x1b[31mdatabase(\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_engine_picks_literal_matcher_for_plain_patterns() {
+        let literal = |pattern: &str, engine| {
+            let options = SearchOptions {
+                engine,
+                ..SearchOptions::default()
+            };
+            TextHighlighter::with_options(pattern, &Color::Red, &options)
+                .literal
+                .is_some()
+        };
+        assert!(literal("needle", Engine::Auto));
+        assert!(!literal("ne+dle", Engine::Auto));
+        assert!(!literal("needle", Engine::Regex));
+        assert!(literal("ne+dle", Engine::Literal));
+        assert!(!literal("one\ntwo", Engine::Literal));
+
+        let options = SearchOptions {
+            engine: Engine::Literal,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("a.b", &Color::Red, &options);
+        assert!(highlighter.is_match("x a.b y"));
+        assert!(!highlighter.is_match("axb"));
+        assert_eq!(highlighter.match_count("a.b a.b"), 2);
+        assert_eq!(highlighter.highlight("x a.b"), "x \x1b[31ma.b\x1b[0m");
+        // Regex-only paths such as --replace see the escaped pattern
+        assert!(!highlighter.regex.is_match("axb"));
+    }
+
+    #[test]
+    fn test_word_regexp_matches_whole_words_only() {
+        let highlighter = TextHighlighter::with_options("log", &Color::Red, &word_options());
+
+        assert!(highlighter.regex.is_match("write to log now"));
+        assert!(!highlighter.regex.is_match("catalog"));
+        assert!(!highlighter.regex.is_match("login"));
+    }
+
+    #[test]
+    fn test_word_regexp_keeps_alternation_grouped() {
+        let highlighter = TextHighlighter::with_options("foo|bar", &Color::Red, &word_options());
+
+        assert!(highlighter.regex.is_match("foo"));
+        assert!(highlighter.regex.is_match("bar"));
+        assert!(!highlighter.regex.is_match("foobar"));
+        assert!(!highlighter.regex.is_match("barn"));
+    }
+
+    #[test]
+    fn test_match_spans_return_every_match() {
+        let highlighter = TextHighlighter::new("o+", &Color::Red);
+
+        assert_eq!(
+            highlighter.match_spans("foo bar boo"),
+            vec![(1, 3), (9, 11)]
+        );
+        assert!(highlighter.match_spans("bar").is_empty());
+        assert_eq!(
+            highlighter.highlight("foo bar boo"),
+            "f\x1b[31moo\x1b[0m bar b\x1b[31moo\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_line_regexp_matches_whole_lines_only() {
+        let options = SearchOptions {
+            line_regexp: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("done", &Color::Red, &options);
+
+        assert!(highlighter.regex.is_match("done"));
+        assert!(!highlighter.regex.is_match("done now"));
+        assert!(!highlighter.regex.is_match("not done"));
+        assert_eq!(highlighter.highlight("done"), "\x1b[31mdone\x1b[0m");
+    }
+
+    #[test]
+    fn test_line_regexp_composes_with_fixed_strings_and_case() {
+        let options = SearchOptions {
+            line_regexp: true,
+            fixed_strings: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("a.b|c", &Color::Red, &options);
+        assert!(highlighter.regex.is_match("a.b|c"));
+        assert!(!highlighter.regex.is_match("axb|c"));
+        assert!(!highlighter.regex.is_match("c"));
+
+        let options = SearchOptions {
+            line_regexp: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("(?i)done|todo", &Color::Red, &options);
+        assert!(highlighter.regex.is_match("DONE"));
+        assert!(!highlighter.regex.is_match("todo later"));
+    }
+
+    #[test]
+    fn test_fixed_strings_matches_literally() {
+        let options = SearchOptions {
+            fixed_strings: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("a.b", &Color::Red, &options);
+
+        assert!(highlighter.regex.is_match("a.b"));
+        assert!(!highlighter.regex.is_match("axb"));
+    }
+
+    #[test]
+    fn test_fixed_strings_with_word_regexp() {
+        let options = SearchOptions {
+            word_regexp: true,
+            fixed_strings: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("a.b", &Color::Red, &options);
+
+        assert!(highlighter.regex.is_match("x a.b y"));
+        assert!(!highlighter.regex.is_match("xa.b"));
+        assert!(!highlighter.regex.is_match("x axb y"));
+    }
+
+    #[test]
+    fn test_highlight_keeps_color_across_embedded_reset() {
+        let highlighter = TextHighlighter::new("foo.*bar", &Color::Red);
+
+        assert_eq!(
+            highlighter.highlight("say foo\x1b[0m and bar!"),
+            "say \x1b[31mfoo\x1b[0m\x1b[31m and bar\x1b[0m!"
+        );
+    }
+
+    #[test]
+    fn test_highlight_leaves_resets_outside_match_untouched() {
+        let highlighter = TextHighlighter::new("bar", &Color::Blue);
+
+        assert_eq!(
+            highlighter.highlight("\x1b[32mfoo\x1b[0m bar"),
+            "\x1b[32mfoo\x1b[0m \x1b[34mbar\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_newline_separated_patterns_form_alternation() {
+        let highlighter = TextHighlighter::new("foo\nbar", &Color::Red);
+
+        assert!(highlighter.regex.is_match("some foo"));
+        assert!(highlighter.regex.is_match("bar here"));
+        assert!(!highlighter.regex.is_match("baz"));
+    }
+
+    #[test]
+    fn test_newline_separated_patterns_escaped_individually() {
+        let options = SearchOptions {
+            fixed_strings: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("a.b\nc|d", &Color::Red, &options);
+
+        assert!(highlighter.regex.is_match("a.b"));
+        assert!(highlighter.regex.is_match("c|d"));
+        assert!(!highlighter.regex.is_match("axb"));
+        assert!(!highlighter.regex.is_match("c"));
+    }
+
+    #[test]
+    fn test_word_regexp_highlights_full_match() {
+        let highlighter = TextHighlighter::with_options("log", &Color::Red, &word_options());
+
+        assert_eq!(
+            highlighter.highlight("log catalog"),
+            "\x1b[31mlog\x1b[0m catalog"
+        );
+    }
+
+    #[test]
+    fn test_highlight_without_color_returns_plain_text() {
+        let options = SearchOptions {
+            use_color: false,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("log", &Color::Red, &options);
+
+        assert_eq!(highlighter.highlight("log it"), "log it");
+    }
+
+    #[test]
+    fn test_highlight_with_replacement() {
+        let options = SearchOptions {
+            replace: Some("bar$1".to_string()),
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options(r"foo(\d+)", &Color::Red, &options);
+
+        assert_eq!(highlighter.highlight("x foo12 foo3"), "x bar12 bar3");
+        assert_eq!(highlighter.highlight_replace("foo7", "${1}!"), "7!");
+    }
+
+    #[test]
+    fn test_try_with_options_invalid_pattern() {
+        let result =
+            TextHighlighter::try_with_options("foo(", &Color::Red, &SearchOptions::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_match_column_is_byte_based() {
+        let options = SearchOptions {
+            column: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("foo", &Color::Red, &options);
+
+        assert_eq!(highlighter.match_column("a foo foo"), Some(3));
+        assert_eq!(highlighter.match_column("\u{e9} foo"), Some(4));
+        assert_eq!(highlighter.match_column("bar"), None);
+        assert_eq!(
+            TextHighlighter::new("foo", &Color::Red).match_column("foo"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_match_offset_adds_line_offset() {
+        let options = SearchOptions {
+            byte_offset: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("foo", &Color::Red, &options);
+
+        assert_eq!(highlighter.match_offset("a foo foo", 10), Some(12));
+        assert_eq!(highlighter.match_offset("bar", 10), None);
+        assert_eq!(
+            TextHighlighter::new("foo", &Color::Red).match_offset("foo", 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_multiline_anchors_lines_and_recolors_each_line() {
+        let options = SearchOptions {
+            multiline: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options(r"b$\r?\n^c", &Color::Red, &options);
+
+        assert!(highlighter.regex.is_match("a b\r\nc d"));
+        assert!(!highlighter.regex.is_match("a b x\nc d"));
+        assert_eq!(
+            highlighter.highlight("a b\nc d"),
+            "a \x1b[31mb\x1b[0m\n\x1b[31mc\x1b[0m d"
+        );
+
+        let dotall = SearchOptions {
+            multiline_dotall: true,
+            ..options
+        };
+        let highlighter = TextHighlighter::with_options("a.b", &Color::Red, &dotall);
+        assert!(highlighter.regex.is_match("a\nb"));
+    }
+
+    #[test]
+    fn test_highlight_spans_colors_given_ranges() {
+        let options = SearchOptions {
+            column: true,
+            byte_offset: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("unused", &Color::Red, &options);
+        let spans = [(2, 6), (7, 8)];
+
+        assert_eq!(
+            highlighter.highlight_spans("a bc\nd e", &spans),
+            "a \x1b[31mbc\x1b[0m\n\x1b[31md\x1b[0m \x1b[31me\x1b[0m"
+        );
+        assert_eq!(highlighter.span_column("a bc\nd e", &spans), Some(3));
+        assert_eq!(highlighter.span_offset(&spans, 10), Some(12));
+        assert_eq!(highlighter.span_column("", &[]), None);
+    }
+
+    #[test]
+    fn test_zero_width_matches_are_not_highlighted() {
+        let highlighter = TextHighlighter::new("", &Color::Red);
+        assert_eq!(highlighter.highlight("abc"), "abc");
+
+        let highlighter = TextHighlighter::new("x*", &Color::Red);
+        assert_eq!(highlighter.highlight("axxb"), "a\x1b[31mxx\x1b[0mb");
+        assert_eq!(highlighter.highlight_spans("ab", &[(1, 1)]), "ab");
+
+        let highlighter = TextHighlighter::new("a*", &Color::Red);
+        assert_eq!(highlighter.highlight("aaa"), "\x1b[31maaa\x1b[0m");
+        assert_eq!(highlighter.highlight("baaab"), "b\x1b[31maaa\x1b[0mb");
+    }
+
+    #[test]
+    fn test_adjacent_and_overlapping_spans_share_one_highlight() {
+        let highlighter = TextHighlighter::new("a", &Color::Red);
+        assert_eq!(
+            highlighter.highlight("aaa b a"),
+            "\x1b[31maaa\x1b[0m b \x1b[31ma\x1b[0m"
+        );
+        assert_eq!(
+            highlighter.highlight_spans("abcdef", &[(3, 5), (0, 2), (1, 3)]),
+            "\x1b[31mabcde\x1b[0mf"
+        );
+    }
+
+    #[test]
+    fn test_max_columns_centers_window_on_first_match() {
+        let options = SearchOptions {
+            max_columns: Some(6),
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("needle", &Color::Red, &options);
+        let line = format!("{}needle{}", "a".repeat(100), "b".repeat(50));
+
+        assert_eq!(
+            highlighter.highlight(&line),
+            "[... omitted 100 bytes ...]\x1b[31mneedle\x1b[0m[... omitted 50 bytes ...]"
+        );
+        assert_eq!(highlighter.highlight("needle"), "\x1b[31mneedle\x1b[0m");
+
+        let plain = TextHighlighter::with_options(
+            "é",
+            &Color::Red,
+            &SearchOptions {
+                max_columns: Some(3),
+                use_color: false,
+                ..SearchOptions::default()
+            },
+        );
+        // Windows count characters and never split one
+        assert_eq!(plain.highlight("éééxyz"), "ééé[... omitted 3 bytes ...]");
+        assert_eq!(plain.highlight("xyzwvé"), "[... omitted 3 bytes ...]wvé");
+    }
+
+    #[test]
+    fn test_trim_strips_indent_after_matching() {
+        let options = SearchOptions {
+            trim: true,
+            column: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options(r"^\s+fn", &Color::Red, &options);
+
+        assert_eq!(
+            highlighter.highlight("    fn main()"),
+            "\x1b[31mfn\x1b[0m main()"
+        );
+        assert_eq!(highlighter.match_column("    fn main()"), Some(1));
+
+        let highlighter = TextHighlighter::with_options("main", &Color::Red, &options);
+        assert_eq!(highlighter.match_column("\t fn main()"), Some(4));
+    }
+
+    #[test]
+    fn test_trim_lines_moves_spans_on_each_line() {
+        let (text, spans) = trim_lines("  ab\n\t cd", &[(3, 4), (5, 9)]);
+
+        assert_eq!(text, "ab\ncd");
+        assert_eq!(spans, vec![(1, 2), (3, 5)]);
     }
 }
@@ -0,0 +1,318 @@
+//! # JSON Encoding
+//!
+//! This module builds the JSON Lines objects emitted by `--json` and the single
+//! report object emitted by `--summary-json`, serialized with `serde_json`.
+//! Output is ASCII-safe: every character outside printable ASCII, including
+//! controls, DEL and U+2028, is written as a `\uXXXX` escape (using surrogate
+//! pairs where needed), so consumers never have to deal with the terminal's
+//! encoding.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use xerg::output::json::match_object;
+//! use std::path::Path;
+//!
+//! let object = match_object(Path::new("src/main.rs"), 11, "use std::fs;", &[(0, 3)], None);
+//! // Returns: {"path":"src/main.rs","line":12,"text":"use std::fs;","submatches":[{"start":0,"end":3}]}
+//! ```
+
+use serde::Serialize;
+use serde_json::ser::{Formatter, Serializer};
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Compact JSON formatting that escapes everything outside printable ASCII
+struct AsciiFormatter;
+
+impl Formatter for AsciiFormatter {
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        let mut start = 0;
+        for (index, c) in fragment.char_indices() {
+            if (' '..='~').contains(&c) {
+                continue;
+            }
+
+            writer.write_all(&fragment.as_bytes()[start..index])?;
+            let mut units = [0u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                write!(writer, "\\u{:04x}", unit)?;
+            }
+            start = index + c.len_utf8();
+        }
+
+        writer.write_all(&fragment.as_bytes()[start..])
+    }
+}
+
+/// Serialize `value` as one line of ASCII-only JSON
+fn _to_json<T: Serialize>(value: &T) -> String {
+    let mut out = Vec::new();
+    value
+        .serialize(&mut Serializer::with_formatter(&mut out, AsciiFormatter))
+        .expect("JSON objects serialize into memory");
+
+    String::from_utf8(out).expect("the formatter only writes ASCII")
+}
+
+/// `value` rounded to milliseconds, as the reported times are
+fn _round_millis(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[derive(Serialize)]
+struct Submatch {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Serialize)]
+struct MatchLine<'a> {
+    path: Cow<'a, str>,
+    line: usize,
+    text: &'a str,
+    submatches: Vec<Submatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_offset: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    summary: SummaryTotals,
+}
+
+#[derive(Serialize)]
+struct SummaryTotals {
+    files: usize,
+    lines: usize,
+    matches: usize,
+    skipped: usize,
+    errors: usize,
+    time: f64,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    files: Vec<ReportFile<'a>>,
+    totals: ReportTotals,
+    elapsed_ms: f64,
+}
+
+#[derive(Serialize)]
+struct ReportFile<'a> {
+    path: &'a str,
+    matches: usize,
+    lines: usize,
+    skipped: usize,
+}
+
+#[derive(Serialize)]
+struct ReportTotals {
+    files: usize,
+    lines: usize,
+    matches: usize,
+    skipped: usize,
+    errors: usize,
+}
+
+#[derive(Serialize)]
+struct Types<'a> {
+    types: Vec<TypeMatches<'a>>,
+}
+
+#[derive(Serialize)]
+struct TypeMatches<'a> {
+    #[serde(rename = "type")]
+    extension: &'a str,
+    matches: usize,
+}
+
+/// Build the object describing one matching line
+///
+/// `index` is the 0-based line index; the emitted `line` is 1-based. Submatch
+/// offsets are byte offsets into `text`; `byte_offset` is the absolute offset
+/// of the first match in the file and is only emitted with `--byte-offset`.
+pub fn match_object(
+    path: &Path,
+    index: usize,
+    text: &str,
+    submatches: &[(usize, usize)],
+    byte_offset: Option<usize>,
+) -> String {
+    _to_json(&MatchLine {
+        path: path.to_string_lossy(),
+        line: index + 1,
+        text,
+        submatches: submatches
+            .iter()
+            .map(|&(start, end)| Submatch { start, end })
+            .collect(),
+        byte_offset,
+    })
+}
+
+/// Build the trailing summary object emitted with `--stats`
+pub fn summary_object(
+    files: usize,
+    lines: usize,
+    matched: usize,
+    skipped: usize,
+    errors: usize,
+    elapsed_secs: f64,
+) -> String {
+    _to_json(&Summary {
+        summary: SummaryTotals {
+            files,
+            lines,
+            matches: matched,
+            skipped,
+            errors,
+            time: _round_millis(elapsed_secs),
+        },
+    })
+}
+
+/// Build the single `--summary-json` report object
+///
+/// `files` holds a `(path, matches, lines, skipped)` entry per searched file;
+/// the remaining arguments are the totals across the whole search.
+pub fn report_object(
+    files: &[(String, usize, usize, usize)],
+    searched: usize,
+    lines: usize,
+    matched: usize,
+    skipped: usize,
+    errors: usize,
+    elapsed_ms: f64,
+) -> String {
+    _to_json(&Report {
+        files: files
+            .iter()
+            .map(|(path, matches, lines, skipped)| ReportFile {
+                path,
+                matches: *matches,
+                lines: *lines,
+                skipped: *skipped,
+            })
+            .collect(),
+        totals: ReportTotals {
+            files: searched,
+            lines,
+            matches: matched,
+            skipped,
+            errors,
+        },
+        elapsed_ms: _round_millis(elapsed_ms),
+    })
+}
+
+/// Build the `--stats-by-type` object, keeping the breakdown's order in an array
+pub fn types_object(breakdown: &[(&str, usize)]) -> String {
+    _to_json(&Types {
+        types: breakdown
+            .iter()
+            .map(|&(extension, matches)| TypeMatches { extension, matches })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `text` field of a match object holding `text`
+    fn encoded_text(text: &str) -> String {
+        let object = match_object(Path::new("a"), 0, text, &[], None);
+        let start = object.find(r#""text":"#).unwrap() + 7;
+        let end = object.find(r#","submatches""#).unwrap();
+        object[start..end].to_string()
+    }
+
+    #[test]
+    fn test_escape_quotes_and_controls() {
+        assert_eq!(encoded_text("say \"hi\"\\"), r#""say \"hi\"\\""#);
+        assert_eq!(encoded_text("a\tb\x1b[31m"), r#""a\tb\u001b[31m""#);
+        assert_eq!(encoded_text("\0\x7f"), r#""\u0000\u007f""#);
+    }
+
+    #[test]
+    fn test_escape_non_ascii_is_ascii_safe() {
+        assert_eq!(encoded_text("caf\u{e9}"), r#""caf\u00e9""#);
+        assert_eq!(encoded_text("\u{1f980}"), r#""\ud83e\udd80""#);
+        // Line separators would break JavaScript consumers if left raw
+        assert_eq!(encoded_text("a\u{2028}b\u{2029}"), r#""a\u2028b\u2029""#);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invalid_utf8_path_is_replaced() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"bad\xff.txt"));
+        let object = match_object(path, 0, "x", &[], None);
+        assert!(
+            object.starts_with(r#"{"path":"bad\ufffd.txt","#),
+            "{}",
+            object
+        );
+    }
+
+    #[test]
+    fn test_match_object_format() {
+        let object = match_object(Path::new("src/main.rs"), 11, "use foo", &[(0, 3)], None);
+
+        assert_eq!(
+            object,
+            r#"{"path":"src/main.rs","line":12,"text":"use foo","submatches":[{"start":0,"end":3}]}"#
+        );
+    }
+
+    #[test]
+    fn test_match_object_with_byte_offset() {
+        let object = match_object(Path::new("a.txt"), 0, "x foo", &[(2, 5)], Some(42));
+
+        assert_eq!(
+            object,
+            r#"{"path":"a.txt","line":1,"text":"x foo","submatches":[{"start":2,"end":5}],"byte_offset":42}"#
+        );
+    }
+
+    #[test]
+    fn test_summary_object_format() {
+        assert_eq!(
+            summary_object(2, 10, 3, 0, 1, 0.5),
+            r#"{"summary":{"files":2,"lines":10,"matches":3,"skipped":0,"errors":1,"time":0.5}}"#
+        );
+    }
+
+    #[test]
+    fn test_report_object_format() {
+        let files = [
+            ("a.rs".to_string(), 2, 10, 0),
+            ("b\u{e9}.rs".to_string(), 0, 4, 1),
+        ];
+        assert_eq!(
+            report_object(&files, 2, 14, 2, 1, 0, 12.5),
+            r#"{"files":[{"path":"a.rs","matches":2,"lines":10,"skipped":0},{"path":"b\u00e9.rs","matches":0,"lines":4,"skipped":1}],"totals":{"files":2,"lines":14,"matches":2,"skipped":1,"errors":0},"elapsed_ms":12.5}"#
+        );
+        assert_eq!(
+            report_object(&[], 0, 0, 0, 0, 0, 0.0),
+            r#"{"files":[],"totals":{"files":0,"lines":0,"matches":0,"skipped":0,"errors":0},"elapsed_ms":0.0}"#
+        );
+    }
+
+    #[test]
+    fn test_types_object_format() {
+        assert_eq!(
+            types_object(&[(".rs", 12), ("(none)", 1)]),
+            r#"{"types":[{"type":".rs","matches":12},{"type":"(none)","matches":1}]}"#
+        );
+        assert_eq!(types_object(&[]), r#"{"types":[]}"#);
+    }
+}
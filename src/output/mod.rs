@@ -4,10 +4,12 @@
 //! - ANSI color management and terminal formatting
 //! - Text highlighting with pattern matching
 //! - Result formatting, statistics, and structured output
+//! - ASCII-safe JSON encoding for machine-readable results
 //!
 //! The output module provides consistent formatting across both
 //! default and xtreme search modes while maintaining performance.
 
 pub mod colors;
 pub mod highlighter;
+pub mod json;
 pub mod result;
@@ -10,6 +10,16 @@
 //! - **Parallel Communication**: Handles messages from multiple worker threads
 //! - **Structured Results**: Provides machine-readable result format
 //! - **Real-time Display**: Streams results as they become available
+//! - **Content Grouping**: Optionally groups identical matching lines across files
+//! - **Color Control**: Drops ANSI decorations when disabled (`--decorations never`,
+//!   which follows `--color` by default), independently of the match highlights
+//! - **JSON Lines**: Optionally emits one JSON object per match for tooling (`--json`)
+//! - **Sorting**: Optionally buffers results to print them in path order (`--sort path`)
+//! - **Line Number Alignment**: Pads line numbers to the widest one in each file, or
+//!   to a fixed width (`--line-number-width`)
+//! - **Path Separators**: Optionally shows text output paths with a fixed separator (`--path-separator`)
+//! - **Relative Paths**: Optionally shows text output paths relative to the search root (`--relative`)
+//! - **Any Writer**: Every printer has a `_to` variant writing to any `Write`, such as a `Vec<u8>`
 //!
 //! ## Result Format
 //!
@@ -19,13 +29,27 @@
 //! result: files:8; lines:1699; matches:85; skipped:0; errors:0; time:0.002s;
 //! ```
 //!
+//! With `--stats-by-type`, a breakdown of matches per file extension follows it:
+//!
+//! ```text
+//! types: .rs: 80 matches, .md: 5 matches
+//! ```
+//!
+//! With `--timing`, every file's search time follows, slowest first:
+//!
+//! ```text
+//! timing:
+//!     41.207ms  src/big.rs
+//!      0.118ms  src/lib.rs
+//! ```
+//!
 //! ## Search Statistics
 //!
 //! The module tracks comprehensive metrics:
 //! - **Files**: Total number of files processed
 //! - **Lines**: Total lines read across all files
 //! - **Matches**: Total pattern occurrences found
-//! - **Skipped**: Lines that couldn't be read due to errors
+//! - **Skipped**: Lines that couldn't be read due to errors, plus files over `--max-filesize`
 //! - **Errors**: File-level access failures
 //! - **Time**: Total execution time with millisecond precision (3 decimal places)
 //!
@@ -33,23 +57,53 @@
 //!
 //! ```no_run
 //! use xerg::output::result::{print_result, ResultMessage};
+//! use xerg::options::SearchOptions;
 //! use std::sync::mpsc;
 //!
 //! let (tx, rx) = mpsc::channel();
 //! let start_time = std::time::Instant::now();
 //! // Send messages from worker threads...
-//! print_result(rx, true, start_time); // Print with statistics
+//! print_result(rx, true, start_time, &SearchOptions::default()); // Print with statistics
 //! ```
 
+use super::json;
+use crate::options::{SearchOptions, SortBy, StatsFormat};
+use crate::search::crawler::modified_time;
+use crate::search::xtreme::XtremeStats;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::Instant;
 
 pub type FileMatchResult = Vec<ResultMessage>;
 
+/// Matching line contents mapped to every `(path, line_index)` where they occur
+pub type ContentGroups = BTreeMap<String, Vec<(PathBuf, usize)>>;
+
 pub enum ResultMessage {
     Header(PathBuf),
     Line {
+        index: usize,
+        /// 1-based byte column of the first match, present with `--column`
+        column: Option<usize>,
+        /// Byte offset of the first match in the file, present with `--byte-offset`
+        byte_offset: Option<usize>,
+        /// Number of matches on the line, present with `--show-line-match-count`
+        matches: Option<usize>,
+        content: String,
+    },
+    /// Unhighlighted matching line with the byte range of each match
+    Match {
+        index: usize,
+        text: String,
+        submatches: Vec<(usize, usize)>,
+        /// Byte offset of the first match in the file, present with `--byte-offset`
+        byte_offset: Option<usize>,
+    },
+    /// Non-matching line shown around a match with `-A`, `-B` or `-C`
+    Context {
         index: usize,
         content: String,
     },
@@ -59,137 +113,883 @@ pub enum ResultMessage {
         skipped: usize,
     },
     Error(String),
+    /// File left unsearched, such as one exceeding `--max-filesize`, and why
+    SkippedFile {
+        path: PathBuf,
+        reason: String,
+    },
+    /// Time spent searching a file, present with `--timing`
+    Timing {
+        path: PathBuf,
+        millis: f64,
+    },
     Done,
 }
 
-fn _print_line(index: usize, content: &str) {
-    println!("  \x1b[1;38;5;245m{:>3}:\x1b[0m  {}", index + 1, content);
+/// Wrap `text` in the given SGR weight and the `--ui-color` code in `decorate`
+///
+/// `decorate` is `None` when decorations are disabled, leaving `text` plain.
+fn _paint(weight: &str, text: &str, decorate: Option<&str>) -> String {
+    match decorate {
+        Some(color) => format!("\x1b[{};{}m{}\x1b[0m", weight, color, text),
+        None => text.to_string(),
+    }
+}
+
+/// SGR code of the `--ui-color` decorations, or `None` when they are disabled
+fn _decoration(options: &SearchOptions) -> Option<String> {
+    options
+        .decorate
+        .then(|| options.ui_color.to_code_for(options.term_caps))
+}
+
+/// Print a matching line, prefixed by its line number, column and byte offset when enabled
+///
+/// Without any prefix the content is printed as-is so snippets can be copied verbatim.
+/// A `--multiline` match spans several lines; each following line gets its own
+/// line number but no column or byte offset. Line numbers are right-aligned
+/// to `width` characters.
+fn _print_line(
+    out: &mut impl Write,
+    index: Option<usize>,
+    column: Option<usize>,
+    byte_offset: Option<usize>,
+    content: &str,
+    width: usize,
+    decorate: Option<&str>,
+) -> io::Result<()> {
+    for (offset, line) in content.split('\n').enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if offset == 0 {
+            _print_numbered_line(out, index, column, byte_offset, line, width, decorate)?;
+        } else {
+            let index = index.map(|i| i + offset);
+            _print_numbered_line(out, index, None, None, line, width, decorate)?;
+        }
+    }
+    Ok(())
+}
+
+/// `content` led by its `(3x)` match count under `--show-line-match-count`
+///
+/// Shared with xtreme mode so both print the count the same way.
+pub fn with_match_count<'a>(
+    matches: Option<usize>,
+    content: &'a str,
+    decorate: Option<&str>,
+) -> Cow<'a, str> {
+    match matches {
+        Some(matches) => {
+            let count = format!("({}x)", matches);
+            Cow::Owned(format!("{}  {}", _paint("2", &count, decorate), content))
+        }
+        None => Cow::Borrowed(content),
+    }
+}
+
+fn _print_numbered_line(
+    out: &mut impl Write,
+    index: Option<usize>,
+    column: Option<usize>,
+    byte_offset: Option<usize>,
+    content: &str,
+    width: usize,
+    decorate: Option<&str>,
+) -> io::Result<()> {
+    let mut fields = [index.map(|index| index + 1), column, byte_offset]
+        .into_iter()
+        .flatten();
+    let Some(first) = fields.next() else {
+        return writeln!(out, "{}", content);
+    };
+    let number = fields.fold(format!("{:>width$}:", first), |number, field| {
+        format!("{}{}:", number, field)
+    });
+    writeln!(out, "  {}  {}", _paint("1", &number, decorate), content)
+}
+
+/// Print a context line, its line number followed by `-` rather than a match's `:`
+fn _print_context_line(
+    out: &mut impl Write,
+    index: Option<usize>,
+    content: &str,
+    width: usize,
+    decorate: Option<&str>,
+) -> io::Result<()> {
+    match index {
+        Some(index) => {
+            let number = format!("{:>width$}-", index + 1);
+            writeln!(out, "  {}  {}", _paint("2", &number, decorate), content)
+        }
+        None => writeln!(out, "{}", content),
+    }
+}
+
+/// Narrowest line number width, kept so short files line up as they always have
+const MIN_LINE_NUMBER_WIDTH: usize = 3;
+
+/// Width fitting the largest line number among one file's messages
+///
+/// Each file's messages arrive together, so this needs no extra buffering.
+/// Lines following a `--multiline` match count towards the largest number.
+fn _line_number_width(messages: &[ResultMessage]) -> usize {
+    let last_number = messages
+        .iter()
+        .filter_map(|msg| match msg {
+            ResultMessage::Line { index, content, .. } => {
+                Some(index + 1 + content.matches('\n').count())
+            }
+            ResultMessage::Match { index, text, .. } => {
+                Some(index + 1 + text.matches('\n').count())
+            }
+            ResultMessage::Context { index, .. } => Some(index + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    last_number.to_string().len().max(MIN_LINE_NUMBER_WIDTH)
+}
+
+/// Format `path` for output, showing `separator` between its components when given
+///
+/// Only the displayed text changes; files are still opened with their real path.
+pub fn display_path(path: &Path, separator: Option<char>) -> Cow<'_, str> {
+    let text = path.to_string_lossy();
+    match separator {
+        Some(separator) if text.contains(std::path::is_separator) => Cow::Owned(
+            text.chars()
+                .map(|c| {
+                    if std::path::is_separator(c) {
+                        separator
+                    } else {
+                        c
+                    }
+                })
+                .collect(),
+        ),
+        _ => text,
+    }
 }
 
-fn _print_header(filepath: &Path) {
-    println!("\x1b[1;38;5;245m--- {}\x1b[0m ---", filepath.display());
+/// `path` without the `root` prefix, or unchanged when it lies outside `root`
+///
+/// The root itself keeps its full path rather than becoming empty.
+pub fn relative_path<'a>(path: &'a Path, root: Option<&Path>) -> &'a Path {
+    root.and_then(|root| path.strip_prefix(root).ok())
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .unwrap_or(path)
 }
 
-fn _print_line_stats(lines: usize, matched: usize, skipped: usize) {
-    println!(
-        "  \x1b[2;38;5;245mlines: {}, matches: {}, skipped: {}\x1b[0m",
+/// Format `path` for text output, applying `--relative` and `--path-separator`
+pub fn shown_path<'a>(path: &'a Path, options: &SearchOptions) -> Cow<'a, str> {
+    display_path(
+        relative_path(path, options.relative_to.as_deref()),
+        options.path_separator,
+    )
+}
+
+/// Text printed right after a file path: a NUL byte under `--null`, otherwise nothing
+fn _path_terminator(null: bool) -> &'static str {
+    if null { "\0" } else { "" }
+}
+
+/// Match count from the `SearchStats` among one file's messages, if it is there
+///
+/// Stats close each file's batch, so the count is known before its header prints.
+fn _batch_matches(messages: &[ResultMessage]) -> Option<usize> {
+    messages.iter().find_map(|msg| match msg {
+        ResultMessage::SearchStats { matched, .. } => Some(*matched),
+        _ => None,
+    })
+}
+
+/// Print `--- path ---`, or `--- path (N matches) ---` when the count is given
+fn _print_header(
+    out: &mut impl Write,
+    filepath: &str,
+    matches: Option<usize>,
+    decorate: Option<&str>,
+    null: bool,
+) -> io::Result<()> {
+    let path = format!("--- {}{}", filepath, _path_terminator(null));
+    let count = match matches {
+        Some(1) => " (1 match)".to_string(),
+        Some(n) => format!(" ({} matches)", n),
+        None => String::new(),
+    };
+    writeln!(out, "{}{} ---", _paint("1", &path, decorate), count)
+}
+
+fn _print_content_group(
+    out: &mut impl Write,
+    content: &str,
+    locations: &[(PathBuf, usize)],
+    options: &SearchOptions,
+) -> io::Result<()> {
+    writeln!(out, "{}", content)?;
+    for (path, index) in locations {
+        let separator = if options.null { "\0" } else { ":" };
+        let path = shown_path(path, options);
+        let location = format!("{}{}{}", path, separator, index + 1);
+        writeln!(
+            out,
+            "  {}",
+            _paint("1", &location, _decoration(options).as_deref())
+        )?;
+    }
+    Ok(())
+}
+
+/// Bucket used by `--stats-by-type`: the path's extension with a leading dot, or `(none)`
+fn _type_key(path: &Path) -> String {
+    path.extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_else(|| "(none)".to_string())
+}
+
+/// Extensions with at least one match, most matches first and ties by name
+fn _type_breakdown(type_matches: &BTreeMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut breakdown: Vec<(&str, usize)> = type_matches
+        .iter()
+        .filter(|&(_, &matched)| matched > 0)
+        .map(|(ext, &matched)| (ext.as_str(), matched))
+        .collect();
+    breakdown.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    breakdown
+}
+
+/// Print the `--stats-by-type` breakdown in the `--stats-format` chosen in `options`
+fn _print_type_stats(
+    out: &mut impl Write,
+    type_matches: &BTreeMap<String, usize>,
+    options: &SearchOptions,
+) -> io::Result<()> {
+    let breakdown = _type_breakdown(type_matches);
+    if options.stats_format == StatsFormat::Json {
+        return writeln!(out, "{}", json::types_object(&breakdown));
+    }
+
+    let types = breakdown
+        .iter()
+        .map(|(ext, matched)| format!("{}: {} matches", ext, matched))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let types = if types.is_empty() {
+        "types: (no matches)".to_string()
+    } else {
+        format!("types: {}", types)
+    };
+    writeln!(
+        out,
+        "{}",
+        _paint("1", &types, _decoration(options).as_deref())
+    )
+}
+
+/// Print a `--count-matches` line, `path:occurrences`
+fn _print_count(
+    out: &mut impl Write,
+    path: &str,
+    occurrences: usize,
+    null: bool,
+) -> io::Result<()> {
+    let separator = if null { "\0" } else { ":" };
+    writeln!(out, "{}{}{}", path, separator, occurrences)
+}
+
+fn _print_line_stats(
+    out: &mut impl Write,
+    lines: usize,
+    matched: usize,
+    skipped: usize,
+    decorate: Option<&str>,
+) -> io::Result<()> {
+    let stats = format!(
+        "lines: {}, matches: {}, skipped: {}",
         lines, matched, skipped
     );
+    writeln!(out, "  {}", _paint("2", &stats, decorate))
 }
 
-fn _print_result_stats(
+/// Counts summed over every file for the `--stats` totals line
+#[derive(Debug, Default, Clone, Copy)]
+struct ResultTotals {
     files: usize,
     lines: usize,
     matched: usize,
     skipped: usize,
     errors: usize,
+}
+
+/// Print the totals line in the `--stats-format` chosen in `options`
+fn _print_result_stats(
+    out: &mut impl Write,
+    totals: &ResultTotals,
     elapsed_secs: f64,
-) {
-    println!(
-        "\x1b[1;38;5;245mresult: files:{}; lines:{}; matches:{}; skipped:{}; errors:{}; time:{:.3}s;\x1b[0m",
+    options: &SearchOptions,
+) -> io::Result<()> {
+    let ResultTotals {
+        files,
+        lines,
+        matched,
+        skipped,
+        errors,
+    } = *totals;
+    if options.stats_format == StatsFormat::Json {
+        let summary = json::summary_object(files, lines, matched, skipped, errors, elapsed_secs);
+        return writeln!(out, "{}", summary);
+    }
+
+    let summary = format!(
+        "result: files:{}; lines:{}; matches:{}; skipped:{}; errors:{}; time:{:.3}s;",
         files, lines, matched, skipped, errors, elapsed_secs
     );
+    writeln!(
+        out,
+        "{}",
+        _paint("1", &summary, _decoration(options).as_deref())
+    )
+}
+
+/// Print the `--timing` list of files with their search time, slowest first
+fn _print_timings(
+    out: &mut impl Write,
+    timings: &mut [(PathBuf, f64)],
+    options: &SearchOptions,
+) -> io::Result<()> {
+    timings.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let width = timings
+        .first()
+        .map_or(0, |(_, millis)| format!("{:.3}", millis).len());
+
+    writeln!(
+        out,
+        "{}",
+        _paint("1", "timing:", _decoration(options).as_deref())
+    )?;
+    for (path, millis) in timings.iter() {
+        let path = shown_path(path, options);
+        let millis = format!("{:>width$.3}ms", millis, width = width);
+        writeln!(
+            out,
+            "  {}  {}",
+            _paint("2", &millis, _decoration(options).as_deref()),
+            path
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Print the `--verbose` report of files left unsearched and files that failed
+///
+/// Prints nothing when every file was searched cleanly.
+fn _print_verbose_report(
+    out: &mut impl Write,
+    skipped: &[(PathBuf, String)],
+    errors: &[String],
+    options: &SearchOptions,
+) -> io::Result<()> {
+    if !skipped.is_empty() {
+        writeln!(out, "skipped:")?;
+        for (path, reason) in skipped {
+            writeln!(out, "  {}: {}", shown_path(path, options), reason)?;
+        }
+    }
+    if !errors.is_empty() {
+        writeln!(out, "errors:")?;
+        for err in errors {
+            writeln!(out, "  {}", err)?;
+        }
+    }
+    Ok(())
 }
 
-pub fn print_result(rx: mpsc::Receiver<FileMatchResult>, show_stats: bool, start_time: Instant) {
-    print_result_formatted(rx, show_stats, start_time, false);
+/// Unwrap the result of printing to stdout, panicking on errors like `println!` does
+pub(crate) fn expect_stdout<T>(result: io::Result<T>) -> T {
+    result.unwrap_or_else(|e| panic!("failed printing to stdout: {}", e))
 }
 
-/// Print results for xtreme mode (raw string output)
-pub fn print_xtreme_results(
-    rx: mpsc::Receiver<Vec<String>>,
+/// Print results with file headers, returning `true` if any line matched
+///
+/// A file's `Header` message is held back until its first matching line, so
+/// files without matches print nothing. This also holds for standard input,
+/// whose header is sent before any line has been read.
+pub fn print_result(
+    rx: mpsc::Receiver<FileMatchResult>,
+    show_stats: bool,
+    start_time: Instant,
+    options: &SearchOptions,
+) -> bool {
+    expect_stdout(print_result_to(
+        &mut std::io::stdout(),
+        rx,
+        show_stats,
+        start_time,
+        options,
+    ))
+}
+
+/// Like [`print_result`], but writes to `out` instead of stdout
+///
+/// ```no_run
+/// use xerg::options::SearchOptions;
+/// use xerg::output::result::print_result_to;
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// drop(tx);
+/// let mut out = Vec::new();
+/// print_result_to(&mut out, rx, false, std::time::Instant::now(), &SearchOptions::default()).unwrap();
+/// ```
+pub fn print_result_to<W: Write>(
+    out: &mut W,
+    rx: mpsc::Receiver<FileMatchResult>,
+    show_stats: bool,
+    start_time: Instant,
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    print_result_formatted(out, rx, show_stats, start_time, false, options)
+}
+
+/// Print structured matches as JSON Lines, one object per matching line
+///
+/// Each object has the form
+/// `{"path":"src/main.rs","line":12,"text":"...","submatches":[{"start":4,"end":7}]}`
+/// where `line` is 1-based and submatch offsets are byte offsets into `text`.
+/// When `show_stats` is set a final `{"summary":{...}}` object carries the totals.
+/// Errors are still reported on stderr so stdout stays valid JSON Lines.
+/// Returns `true` if any line matched.
+pub fn print_result_json(
+    rx: mpsc::Receiver<FileMatchResult>,
     show_stats: bool,
     start_time: Instant,
-) {
-    let mut total_files = 0;
+) -> bool {
+    expect_stdout(print_result_json_to(
+        &mut std::io::stdout(),
+        rx,
+        show_stats,
+        start_time,
+    ))
+}
+
+/// Like [`print_result_json`], but writes to `out` instead of stdout
+pub fn print_result_json_to<W: Write>(
+    out: &mut W,
+    rx: mpsc::Receiver<FileMatchResult>,
+    show_stats: bool,
+    start_time: Instant,
+) -> io::Result<bool> {
+    let mut any_match = false;
     let mut total_lines = 0;
-    let mut total_matches = 0;
+    let mut total_matched = 0;
     let mut total_skipped = 0;
-    let total_errors = 0;
-
-    while let Ok(results) = rx.recv() {
-        for line in results {
-            if line.starts_with("#") {
-                // Parse stats from comment line if present
-                if show_stats {
-                    total_files += 1;
-                    // Parse: # filepath: lines:X, matches:Y, skipped:Z
-                    if let Some(stats_part) = line.split(": ").nth(1) {
-                        for stat in stats_part.split(", ") {
-                            if let Some(value) = stat.split(":").nth(1) {
-                                match stat.split(":").next() {
-                                    Some("lines") => {
-                                        total_lines += value.parse::<u64>().unwrap_or(0)
-                                    }
-                                    Some("matches") => {
-                                        total_matches += value.parse::<u64>().unwrap_or(0)
-                                    }
-                                    Some("skipped") => {
-                                        total_skipped += value.parse::<u64>().unwrap_or(0)
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
+    let mut total_errors = 0;
+    let mut files_processed = 0;
+
+    // Standard input streams its matches in separate batches after a single header
+    let mut current_path = None;
+
+    for messages in rx {
+        for msg in messages {
+            match msg {
+                ResultMessage::Header(path) => current_path = Some(path),
+                ResultMessage::Match {
+                    index,
+                    text,
+                    submatches,
+                    byte_offset,
+                } => {
+                    if let Some(path) = &current_path {
+                        writeln!(
+                            out,
+                            "{}",
+                            json::match_object(path, index, &text, &submatches, byte_offset)
+                        )?;
+                        any_match = true;
                     }
                 }
-            } else {
-                // Direct output for raw results
-                println!("{}", line);
+                ResultMessage::SearchStats {
+                    lines,
+                    matched,
+                    skipped,
+                } => {
+                    total_lines += lines;
+                    total_matched += matched;
+                    total_skipped += skipped;
+                    files_processed += 1;
+                }
+                ResultMessage::Error(err) => {
+                    eprintln!("Error: {}", err);
+                    total_errors += 1;
+                }
+                ResultMessage::SkippedFile { .. } => total_skipped += 1,
+                ResultMessage::Line { .. }
+                | ResultMessage::Context { .. }
+                | ResultMessage::Timing { .. } => {}
+                ResultMessage::Done => break,
             }
         }
     }
 
-    // Print summary stats if requested
     if show_stats {
-        let elapsed = start_time.elapsed();
-        println!(
-            "result: files:{}; lines:{}; matches:{}; skipped:{}; errors:{}; time:{:.3}s;",
-            total_files,
-            total_lines,
-            total_matches,
-            total_skipped,
-            total_errors,
-            elapsed.as_secs_f64()
-        );
+        writeln!(
+            out,
+            "{}",
+            json::summary_object(
+                files_processed,
+                total_lines,
+                total_matched,
+                total_skipped,
+                total_errors,
+                start_time.elapsed().as_secs_f64(),
+            )
+        )?;
+    }
+
+    Ok(any_match)
+}
+
+/// Print only the number of matches across all results, for `--total`
+///
+/// The per-file counts come from the stats messages, so the search must have
+/// run with stats on. Errors are still reported on stderr. Returns `true` if
+/// anything matched.
+pub fn print_result_total_to<W: Write>(
+    out: &mut W,
+    rx: mpsc::Receiver<FileMatchResult>,
+) -> io::Result<bool> {
+    let mut total_matched = 0;
+
+    for messages in rx {
+        for msg in messages {
+            match msg {
+                ResultMessage::SearchStats { matched, .. } => total_matched += matched,
+                ResultMessage::Error(err) => eprintln!("Error: {}", err),
+                _ => {}
+            }
+        }
+    }
+
+    writeln!(out, "{}", total_matched)?;
+    Ok(total_matched > 0)
+}
+
+/// Print one `--summary-json` report object covering the whole search
+///
+/// Like [`print_result_total_to`], this relies on the per-file stats messages,
+/// so the search must have run with stats on. Files are listed by path, with
+/// their match, line and skipped-line counts. Returns `true` if anything matched.
+pub fn print_result_summary_json_to<W: Write>(
+    out: &mut W,
+    rx: mpsc::Receiver<FileMatchResult>,
+    start_time: Instant,
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let mut totals = ResultTotals::default();
+    let mut files = Vec::new();
+    let mut current_path = None;
+
+    for messages in rx {
+        for msg in messages {
+            match msg {
+                ResultMessage::Header(path) => current_path = Some(path),
+                ResultMessage::SearchStats {
+                    lines,
+                    matched,
+                    skipped,
+                } => {
+                    if let Some(path) = current_path.as_deref() {
+                        let path = shown_path(path, options).into_owned();
+                        files.push((path, matched, lines, skipped));
+                    }
+                    totals.lines += lines;
+                    totals.matched += matched;
+                    totals.skipped += skipped;
+                    totals.files += 1;
+                }
+                ResultMessage::Error(err) => {
+                    eprintln!("Error: {}", err);
+                    totals.errors += 1;
+                }
+                ResultMessage::SkippedFile { .. } => totals.skipped += 1,
+                _ => {}
+            }
+        }
+    }
+
+    files.sort();
+    writeln!(
+        out,
+        "{}",
+        json::report_object(
+            &files,
+            totals.files,
+            totals.lines,
+            totals.matched,
+            totals.skipped,
+            totals.errors,
+            start_time.elapsed().as_secs_f64() * 1000.0,
+        )
+    )?;
+    Ok(totals.matched > 0)
+}
+
+/// Path a batch of messages belongs to, taken from its header or skip notice
+fn _result_path(messages: &FileMatchResult) -> Option<&Path> {
+    messages.iter().find_map(|msg| match msg {
+        ResultMessage::Header(path) | ResultMessage::SkippedFile { path, .. } => {
+            Some(path.as_path())
+        }
+        _ => None,
+    })
+}
+
+/// Buffer every result and replay them in `sort` order, reversed for `--sortr`
+///
+/// This waits for the whole search to finish, so output is no longer streamed.
+/// Batches without a path, such as errors raised before a file was opened, come first.
+pub fn sort_results(
+    rx: mpsc::Receiver<FileMatchResult>,
+    sort: SortBy,
+    reverse: bool,
+) -> mpsc::Receiver<FileMatchResult> {
+    let mut results: Vec<FileMatchResult> = rx.into_iter().collect();
+
+    match sort {
+        SortBy::Path => results.sort_by(|a, b| _result_path(a).cmp(&_result_path(b))),
+        SortBy::Modified => results.sort_by_cached_key(|messages| {
+            let path = _result_path(messages).map(Path::to_path_buf);
+            (path.as_deref().and_then(modified_time), path)
+        }),
+    }
+    if reverse {
+        results.reverse();
+    }
+
+    let (tx, sorted_rx) = mpsc::channel();
+    for messages in results {
+        tx.send(messages).ok();
+    }
+
+    sorted_rx
+}
+
+/// Group matching lines with identical content across all files
+///
+/// Groups are keyed by the line content and therefore sorted by it. Locations
+/// within a group are sorted by path and line so the output is deterministic.
+pub fn group_by_content(results: &[FileMatchResult]) -> ContentGroups {
+    let mut groups = ContentGroups::new();
+
+    let mut current_path = None;
+
+    for messages in results {
+        for msg in messages {
+            match msg {
+                ResultMessage::Header(path) => current_path = Some(path),
+                ResultMessage::Line { index, content, .. } => {
+                    if let Some(path) = current_path {
+                        groups
+                            .entry(content.clone())
+                            .or_default()
+                            .push((path.clone(), *index));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for locations in groups.values_mut() {
+        locations.sort();
+    }
+
+    groups
+}
+
+/// Print matching lines grouped by content, listing each `path:line` beneath them
+///
+/// All results are buffered before printing, so output is not streamed.
+/// Returns `true` if any line matched.
+pub fn print_result_grouped(
+    rx: mpsc::Receiver<FileMatchResult>,
+    show_stats: bool,
+    start_time: Instant,
+    options: &SearchOptions,
+) -> bool {
+    expect_stdout(print_result_grouped_to(
+        &mut std::io::stdout(),
+        rx,
+        show_stats,
+        start_time,
+        options,
+    ))
+}
+
+/// Like [`print_result_grouped`], but writes to `out` instead of stdout
+pub fn print_result_grouped_to<W: Write>(
+    out: &mut W,
+    rx: mpsc::Receiver<FileMatchResult>,
+    show_stats: bool,
+    start_time: Instant,
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let results: Vec<FileMatchResult> = rx.into_iter().collect();
+    let groups = group_by_content(&results);
+    let any_match = !groups.is_empty();
+
+    for (content, locations) in groups {
+        _print_content_group(out, &content, &locations, options)?;
+    }
+
+    let mut totals = ResultTotals::default();
+
+    for msg in results.iter().flatten() {
+        match msg {
+            ResultMessage::SearchStats {
+                lines,
+                matched,
+                skipped,
+            } => {
+                totals.lines += lines;
+                totals.matched += matched;
+                totals.skipped += skipped;
+                totals.files += 1;
+            }
+            ResultMessage::Error(err) => {
+                eprintln!("Error: {}", err);
+                totals.errors += 1;
+            }
+            ResultMessage::SkippedFile { .. } => totals.skipped += 1,
+            _ => {}
+        }
     }
+
+    if show_stats {
+        _print_result_stats(out, &totals, start_time.elapsed().as_secs_f64(), options)?;
+    }
+
+    Ok(any_match)
 }
 
 pub fn print_result_xtreme(
     rx: mpsc::Receiver<FileMatchResult>,
     show_stats: bool,
     start_time: Instant,
-) {
-    print_result_formatted(rx, show_stats, start_time, true);
+    options: &SearchOptions,
+) -> bool {
+    expect_stdout(print_result_formatted(
+        &mut std::io::stdout(),
+        rx,
+        show_stats,
+        start_time,
+        true,
+        options,
+    ))
 }
 
 fn print_result_formatted(
+    out: &mut impl Write,
     rx: mpsc::Receiver<FileMatchResult>,
     show_stats: bool,
     start_time: Instant,
     xtreme_mode: bool,
-) {
-    let mut total_lines = 0;
-    let mut total_matched = 0;
-    let mut total_skipped = 0;
-    let mut total_errors = 0;
-    let mut files_processed = 0;
+    options: &SearchOptions,
+) -> io::Result<bool> {
+    let decoration = _decoration(options);
+    let decorate = decoration.as_deref();
+    let line_index = |index: usize| options.line_number.then_some(index);
+    let mut any_match = false;
+    let mut totals = ResultTotals::default();
+    let mut current_path = None;
+    let mut type_matches = BTreeMap::new();
+    let mut timings = Vec::new();
+    let mut skipped_files = Vec::new();
+    let mut errors = Vec::new();
+    // Headers wait for the file's first match, so files without one stay silent
+    let mut header_pending = false;
+    let mut file_matched = false;
+    // Groups of context lines that are not adjacent get a separator between them
+    let context_separator = (options.before_context > 0 || options.after_context > 0)
+        .then_some(options.context_separator.as_deref())
+        .flatten();
+    let mut last_shown: Option<usize> = None;
 
     for message in rx {
+        let width = options
+            .line_number_width
+            .unwrap_or_else(|| _line_number_width(&message));
+        let header_matches = show_stats.then(|| _batch_matches(&message)).flatten();
         for msg in message {
+            if matches!(
+                msg,
+                ResultMessage::Line { .. } | ResultMessage::Match { .. }
+            ) {
+                file_matched = true;
+            }
+            // Context before a file's first match also brings its header
+            if let ResultMessage::Line { index, .. }
+            | ResultMessage::Match { index, .. }
+            | ResultMessage::Context { index, .. } = msg
+            {
+                if let Some(path) = current_path.as_deref().filter(|_| header_pending) {
+                    let path = shown_path(path, options);
+                    _print_header(out, &path, header_matches, decorate, options.null)?;
+                    header_pending = false;
+                }
+                if let Some(separator) = context_separator
+                    && last_shown.is_some_and(|last| index > last + 1)
+                {
+                    writeln!(out, "{}", _paint("2", separator, decorate))?;
+                }
+                last_shown = Some(index);
+            }
+
             match msg {
                 ResultMessage::Header(_path) => {
-                    if !xtreme_mode {
-                        _print_header(&_path);
-                    }
                     // In xtreme mode, skip headers for raw output
+                    header_pending = !xtreme_mode && !options.count_matches && options.headers;
+                    file_matched = false;
+                    last_shown = None;
+                    current_path = Some(_path);
                 }
-                ResultMessage::Line { index, content } => {
+                ResultMessage::Match {
+                    index,
+                    text,
+                    byte_offset,
+                    ..
+                } => {
+                    any_match = true;
+                    if xtreme_mode {
+                        writeln!(out, "{}", text)?;
+                    } else {
+                        let index = line_index(index);
+                        _print_line(out, index, None, byte_offset, &text, width, decorate)?;
+                    }
+                }
+                ResultMessage::Line {
+                    index,
+                    column,
+                    byte_offset,
+                    matches,
+                    content,
+                } => {
+                    any_match = true;
                     if xtreme_mode {
                         // In xtreme mode, content already contains raw format
-                        println!("{}", content);
+                        writeln!(out, "{}", content)?;
                     } else {
-                        _print_line(index, &content);
+                        let index = line_index(index);
+                        let content = with_match_count(matches, &content, decorate);
+                        _print_line(out, index, column, byte_offset, &content, width, decorate)?;
+                    }
+                }
+                ResultMessage::Context { index, content } => {
+                    if xtreme_mode {
+                        writeln!(out, "{}", content)?;
+                    } else {
+                        _print_context_line(out, line_index(index), &content, width, decorate)?;
                     }
                 }
                 ResultMessage::SearchStats {
@@ -197,39 +997,113 @@ fn print_result_formatted(
                     matched,
                     skipped,
                 } => {
-                    if show_stats && !xtreme_mode {
-                        _print_line_stats(lines, matched, skipped);
+                    if options.count_matches {
+                        if let Some(path) = current_path.as_deref().filter(|_| matched > 0) {
+                            let path = shown_path(path, options);
+                            _print_count(out, &path, matched, options.null)?;
+                            any_match = true;
+                        }
+                    } else if show_stats && !xtreme_mode && file_matched {
+                        _print_line_stats(out, lines, matched, skipped, decorate)?;
                     }
-                    total_lines += lines;
-                    total_matched += matched;
-                    total_skipped += skipped;
-                    files_processed += 1;
+                    if let Some(path) = current_path.as_deref().filter(|_| options.stats_by_type) {
+                        *type_matches.entry(_type_key(path)).or_insert(0) += matched;
+                    }
+                    if let Some(path) = current_path.as_ref().filter(|_| skipped > 0) {
+                        let noun = if skipped == 1 { "line" } else { "lines" };
+                        let reason = format!("{} {} skipped as invalid UTF-8", skipped, noun);
+                        skipped_files.push((path.clone(), reason));
+                    }
+                    totals.lines += lines;
+                    totals.matched += matched;
+                    totals.skipped += skipped;
+                    totals.files += 1;
                 }
                 ResultMessage::Error(err) => {
                     if xtreme_mode {
-                        println!("# Error: {}", err);
+                        writeln!(out, "# Error: {}", err)?;
                     } else {
                         eprintln!("Error: {}", err);
                     }
-                    total_errors += 1;
+                    totals.errors += 1;
+                    errors.push(err);
+                }
+                ResultMessage::SkippedFile { path, reason } => {
+                    totals.skipped += 1;
+                    skipped_files.push((path, reason));
                 }
+                ResultMessage::Timing { path, millis } => timings.push((path, millis)),
                 ResultMessage::Done => break,
             }
         }
     }
 
-    // Print total summary if we processed any files and stats are enabled
-    if show_stats && files_processed > 0 {
+    // Print the total summary whenever stats are enabled, even with no files,
+    // so tooling can always rely on a `result:` line
+    if show_stats {
         let elapsed_secs = start_time.elapsed().as_secs_f64();
-        _print_result_stats(
-            files_processed,
-            total_lines,
-            total_matched,
-            total_skipped,
-            total_errors,
-            elapsed_secs,
+        _print_result_stats(out, &totals, elapsed_secs, options)?;
+        if options.stats_by_type {
+            _print_type_stats(out, &type_matches, options)?;
+        }
+    }
+    if options.timing {
+        _print_timings(out, &mut timings, options)?;
+    }
+    // Diagnostics stay off stdout, like the per-file errors above
+    if options.verbose {
+        skipped_files.sort();
+        _print_verbose_report(&mut io::stderr(), &skipped_files, &errors, options)?;
+    }
+
+    Ok(any_match)
+}
+
+/// Print the xtreme totals, as a `# Summary:` line or a JSON object per `format`
+///
+/// The text summary carries the same counts as default mode plus the
+/// throughput in MB/s, computed from the bytes searched.
+pub fn print_xtreme_stats(stats: &XtremeStats, start_time: Instant, format: StatsFormat) {
+    expect_stdout(print_xtreme_stats_to(
+        &mut std::io::stdout(),
+        stats,
+        start_time,
+        format,
+    ))
+}
+
+/// Like [`print_xtreme_stats`], but writes to `out` instead of stdout
+pub fn print_xtreme_stats_to<W: Write>(
+    out: &mut W,
+    stats: &XtremeStats,
+    start_time: Instant,
+    format: StatsFormat,
+) -> io::Result<()> {
+    let duration = start_time.elapsed();
+    if format == StatsFormat::Json {
+        let summary = json::summary_object(
+            stats.files,
+            stats.lines,
+            stats.matches,
+            stats.skipped,
+            stats.errors,
+            duration.as_secs_f64(),
         );
+        return writeln!(out, "{}", summary);
     }
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "# Summary: files:{}, lines:{}, matches:{}, skipped:{}, errors:{}, time:{}ms, throughput:{:.2}MB/s",
+        stats.files,
+        stats.lines,
+        stats.matches,
+        stats.skipped,
+        stats.errors,
+        duration.as_millis(),
+        stats.megabytes_per_sec(duration.as_secs_f64())
+    )
 }
 
 #[cfg(test)]
@@ -244,6 +1118,9 @@ mod tests {
         let header = ResultMessage::Header(PathBuf::from("test.txt"));
         let line = ResultMessage::Line {
             index: 0,
+            column: None,
+            byte_offset: None,
+            matches: None,
             content: "test content".to_string(),
         };
         let stats = ResultMessage::SearchStats {
@@ -277,6 +1154,17 @@ mod tests {
         }
     }
 
+    /// Run [`print_result_to`] without decorations, returning its output and result
+    fn plain_output(rx: mpsc::Receiver<FileMatchResult>, show_stats: bool) -> (String, bool) {
+        let options = SearchOptions {
+            decorate: false,
+            ..SearchOptions::default()
+        };
+        let mut out = Vec::new();
+        let matched = print_result_to(&mut out, rx, show_stats, Instant::now(), &options).unwrap();
+        (String::from_utf8(out).unwrap(), matched)
+    }
+
     #[test]
     fn test_print_result_with_stats() {
         let (tx, rx) = mpsc::channel();
@@ -286,6 +1174,9 @@ mod tests {
             ResultMessage::Header(PathBuf::from("test.txt")),
             ResultMessage::Line {
                 index: 0,
+                column: None,
+                byte_offset: None,
+                matches: None,
                 content: "found match".to_string(),
             },
             ResultMessage::SearchStats {
@@ -299,9 +1190,16 @@ mod tests {
         tx.send(messages).unwrap();
         drop(tx);
 
-        // This test mainly ensures the function doesn't panic
-        // Results go to stdout so we can't easily capture it in tests
-        print_result(rx, true, Instant::now());
+        let (output, matched) = plain_output(rx, true);
+        assert!(matched);
+        assert!(
+            output.starts_with(
+                "--- test.txt (1 match) ---\n    1:  found match\n  lines: 5, matches: 1, skipped: 0\n\
+                 result: files:1; lines:5; matches:1; skipped:0; errors:0; time:"
+            ),
+            "{}",
+            output
+        );
     }
 
     #[test]
@@ -313,6 +1211,9 @@ mod tests {
             ResultMessage::Header(PathBuf::from("test.txt")),
             ResultMessage::Line {
                 index: 0,
+                column: None,
+                byte_offset: None,
+                matches: None,
                 content: "found match".to_string(),
             },
             ResultMessage::SearchStats {
@@ -327,7 +1228,9 @@ mod tests {
         drop(tx);
 
         // This should not display stats
-        print_result(rx, false, Instant::now());
+        let (output, matched) = plain_output(rx, false);
+        assert!(matched);
+        assert_eq!(output, "--- test.txt ---\n    1:  found match\n");
     }
 
     #[test]
@@ -349,8 +1252,14 @@ mod tests {
         tx.send(messages).unwrap();
         drop(tx);
 
-        // This test ensures error handling works
-        print_result(rx, true, Instant::now());
+        // Errors go to stderr; the file has no matches so only the totals print
+        let (output, matched) = plain_output(rx, true);
+        assert!(!matched);
+        assert!(
+            output.starts_with("result: files:1; lines:0; matches:0; skipped:5; errors:1; time:"),
+            "{}",
+            output
+        );
     }
 
     #[test]
@@ -362,6 +1271,9 @@ mod tests {
             ResultMessage::Header(PathBuf::from("file1.txt")),
             ResultMessage::Line {
                 index: 0,
+                column: None,
+                byte_offset: None,
+                matches: None,
                 content: "match in file 1".to_string(),
             },
             ResultMessage::SearchStats {
@@ -377,6 +1289,9 @@ mod tests {
             ResultMessage::Header(PathBuf::from("file2.txt")),
             ResultMessage::Line {
                 index: 5,
+                column: None,
+                byte_offset: None,
+                matches: None,
                 content: "match in file 2".to_string(),
             },
             ResultMessage::SearchStats {
@@ -392,7 +1307,12 @@ mod tests {
         drop(tx);
 
         // Test multiple files with summary
-        print_result(rx, true, Instant::now());
+        let (output, _) = plain_output(rx, true);
+        assert!(output.starts_with(
+            "--- file1.txt (2 matches) ---\n    1:  match in file 1\n  lines: 10, matches: 2, skipped: 0\n\
+             --- file2.txt (1 match) ---\n    6:  match in file 2\n  lines: 8, matches: 1, skipped: 1\n\
+             result: files:2; lines:18; matches:3; skipped:1; errors:0;"
+        ));
     }
 
     #[test]
@@ -400,8 +1320,42 @@ mod tests {
         let (tx, rx) = mpsc::channel();
         drop(tx); // No messages sent
 
-        // Should handle empty results gracefully
-        print_result(rx, true, Instant::now());
+        // The summary is still printed, with zero counts
+        let (output, matched) = plain_output(rx, true);
+        assert!(!matched);
+        assert!(
+            output.starts_with("result: files:0; lines:0; matches:0; skipped:0; errors:0; time:"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_print_result_total_sums_every_file() {
+        let (tx, rx) = mpsc::channel();
+        for matched in [3, 0, 4] {
+            tx.send(vec![
+                ResultMessage::Header(PathBuf::from("file.txt")),
+                ResultMessage::SearchStats {
+                    lines: 10,
+                    matched,
+                    skipped: 0,
+                },
+                ResultMessage::Done,
+            ])
+            .unwrap();
+        }
+        drop(tx);
+
+        let mut out = Vec::new();
+        assert!(print_result_total_to(&mut out, rx).unwrap());
+        assert_eq!(String::from_utf8(out).unwrap(), "7\n");
+
+        let (tx, rx) = mpsc::channel::<FileMatchResult>();
+        drop(tx);
+        let mut out = Vec::new();
+        assert!(!print_result_total_to(&mut out, rx).unwrap());
+        assert_eq!(String::from_utf8(out).unwrap(), "0\n");
     }
 
     #[test]
@@ -421,6 +1375,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_group_by_content_merges_identical_lines() {
+        let results = vec![
+            vec![
+                ResultMessage::Header(PathBuf::from("b.rs")),
+                ResultMessage::Line {
+                    index: 4,
+                    column: None,
+                    byte_offset: None,
+                    matches: None,
+                    content: "let x = 1;".to_string(),
+                },
+                ResultMessage::Done,
+            ],
+            vec![
+                ResultMessage::Header(PathBuf::from("a.rs")),
+                ResultMessage::Line {
+                    index: 0,
+                    column: None,
+                    byte_offset: None,
+                    matches: None,
+                    content: "let x = 1;".to_string(),
+                },
+                ResultMessage::Line {
+                    index: 2,
+                    column: None,
+                    byte_offset: None,
+                    matches: None,
+                    content: "let y = 2;".to_string(),
+                },
+                ResultMessage::Done,
+            ],
+        ];
+
+        let groups = group_by_content(&results);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups["let x = 1;"],
+            vec![(PathBuf::from("a.rs"), 0), (PathBuf::from("b.rs"), 4)]
+        );
+        assert_eq!(groups["let y = 2;"], vec![(PathBuf::from("a.rs"), 2)]);
+    }
+
     #[test]
     fn test_search_stats_fields() {
         // Test SearchStats field access
@@ -443,23 +1441,220 @@ mod tests {
             panic!("Expected SearchStats variant");
         }
     }
-}
 
-pub fn print_xtreme_stats(
-    files_processed: usize,
-    lines: usize,
-    matches: usize,
-    skipped: usize,
-    start_time: Instant,
-) {
-    let duration = start_time.elapsed();
-    println!();
-    println!(
-        "# Summary: files:{}, lines:{}, matches:{}, skipped:{}, time:{:.2}ms",
-        files_processed,
-        lines,
-        matches,
-        skipped,
-        duration.as_millis()
-    );
+    #[test]
+    fn test_verbose_report_lists_skipped_and_failed_files() {
+        let skipped = vec![(PathBuf::from("big.log"), "too large".to_string())];
+        let errors = vec!["Failed to read file gone.txt: not found".to_string()];
+        let options = SearchOptions::default();
+
+        let mut out = Vec::new();
+        _print_verbose_report(&mut out, &skipped, &errors, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "skipped:\n  big.log: too large\nerrors:\n  Failed to read file gone.txt: not found\n"
+        );
+
+        let mut out = Vec::new();
+        _print_verbose_report(&mut out, &[], &[], &options).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_with_match_count_prefixes_content() {
+        assert_eq!(with_match_count(Some(3), "a a a", None), "(3x)  a a a");
+        assert_eq!(
+            with_match_count(Some(1), "a", Some("38;5;245")),
+            "\x1b[2;38;5;245m(1x)\x1b[0m  a"
+        );
+        assert_eq!(with_match_count(None, "a", None), "a");
+    }
+
+    #[test]
+    fn test_sort_results_by_path() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(vec![
+            ResultMessage::Header(PathBuf::from("c.rs")),
+            ResultMessage::Done,
+        ])
+        .unwrap();
+        tx.send(vec![ResultMessage::SkippedFile {
+            path: PathBuf::from("b.rs"),
+            reason: "too large".to_string(),
+        }])
+        .unwrap();
+        tx.send(vec![ResultMessage::Header(PathBuf::from("a.rs"))])
+            .unwrap();
+        drop(tx);
+
+        let order: Vec<PathBuf> = sort_results(rx, SortBy::Path, false)
+            .into_iter()
+            .map(|messages| _result_path(&messages).unwrap().to_path_buf())
+            .collect();
+
+        assert_eq!(
+            order,
+            vec![
+                PathBuf::from("a.rs"),
+                PathBuf::from("b.rs"),
+                PathBuf::from("c.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_breakdown_sorts_by_matches() {
+        assert_eq!(_type_key(Path::new("src/lib.rs")), ".rs");
+        assert_eq!(_type_key(Path::new("Makefile")), "(none)");
+
+        let type_matches = BTreeMap::from([
+            (".md".to_string(), 3),
+            (".rs".to_string(), 12),
+            (".txt".to_string(), 0),
+            ("(none)".to_string(), 3),
+        ]);
+        assert_eq!(
+            _type_breakdown(&type_matches),
+            vec![(".rs", 12), ("(none)", 3), (".md", 3)]
+        );
+    }
+
+    #[test]
+    fn test_print_result_json_to_writer() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(vec![
+            ResultMessage::Header(PathBuf::from("a.txt")),
+            ResultMessage::Match {
+                index: 1,
+                text: "x foo".to_string(),
+                submatches: vec![(2, 5)],
+                byte_offset: None,
+            },
+            ResultMessage::Done,
+        ])
+        .unwrap();
+        drop(tx);
+
+        let mut out = Vec::new();
+        assert!(print_result_json_to(&mut out, rx, false, Instant::now()).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"path\":\"a.txt\",\"line\":2,\"text\":\"x foo\",\"submatches\":[{\"start\":2,\"end\":5}]}\n"
+        );
+    }
+
+    #[test]
+    fn test_print_xtreme_stats_to_writer() {
+        let stats = XtremeStats {
+            files: 2,
+            lines: 10,
+            matches: 3,
+            ..XtremeStats::default()
+        };
+        let mut out = Vec::new();
+        print_xtreme_stats_to(&mut out, &stats, Instant::now(), StatsFormat::Text).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(
+            output.starts_with(
+                "\n# Summary: files:2, lines:10, matches:3, skipped:0, errors:0, time:"
+            )
+        );
+    }
+
+    #[test]
+    fn test_display_path_rewrites_separators() {
+        let path = Path::new("src").join("output").join("result.rs");
+        assert_eq!(display_path(&path, Some('|')), "src|output|result.rs");
+        assert_eq!(display_path(&path, None), path.to_string_lossy());
+        assert!(matches!(
+            display_path(Path::new("main.rs"), Some('|')),
+            Cow::Borrowed("main.rs")
+        ));
+    }
+
+    #[test]
+    fn test_relative_path_strips_root_only_inside_it() {
+        let root = Path::new("/work/project");
+        let inside = root.join("src").join("lib.rs");
+        assert_eq!(
+            relative_path(&inside, Some(root)),
+            Path::new("src").join("lib.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/elsewhere/a.rs"), Some(root)),
+            Path::new("/elsewhere/a.rs")
+        );
+        assert_eq!(relative_path(root, Some(root)), root);
+        assert_eq!(relative_path(&inside, None), inside);
+    }
+
+    #[test]
+    fn test_line_numbers_align_to_widest_in_file() {
+        let line = |index: usize| ResultMessage::Line {
+            index,
+            column: None,
+            byte_offset: None,
+            matches: None,
+            content: "x".to_string(),
+        };
+        let file = || {
+            vec![
+                ResultMessage::Header(PathBuf::from("big.txt")),
+                line(8),
+                line(1233),
+                ResultMessage::Done,
+            ]
+        };
+
+        let (tx, rx) = mpsc::channel();
+        tx.send(file()).unwrap();
+        drop(tx);
+        let (output, _) = plain_output(rx, false);
+        assert_eq!(output, "--- big.txt ---\n     9:  x\n  1234:  x\n");
+
+        let (tx, rx) = mpsc::channel();
+        tx.send(file()).unwrap();
+        drop(tx);
+        let options = SearchOptions {
+            decorate: false,
+            line_number_width: Some(0),
+            ..SearchOptions::default()
+        };
+        let mut out = Vec::new();
+        print_result_to(&mut out, rx, false, Instant::now(), &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "--- big.txt ---\n  9:  x\n  1234:  x\n"
+        );
+    }
+
+    #[test]
+    fn test_timings_print_slowest_first() {
+        let (tx, rx) = mpsc::channel();
+        for (path, millis) in [("fast.txt", 0.5), ("slow.txt", 12.25)] {
+            tx.send(vec![
+                ResultMessage::Header(PathBuf::from(path)),
+                ResultMessage::Timing {
+                    path: PathBuf::from(path),
+                    millis,
+                },
+                ResultMessage::Done,
+            ])
+            .unwrap();
+        }
+        drop(tx);
+        let options = SearchOptions {
+            timing: true,
+            decorate: false,
+            ..SearchOptions::default()
+        };
+
+        let mut out = Vec::new();
+        print_result_to(&mut out, rx, false, Instant::now(), &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timing:\n  12.250ms  slow.txt\n   0.500ms  fast.txt\n"
+        );
+    }
 }
@@ -0,0 +1,89 @@
+//! # Context Lines
+//!
+//! Tracks the lines shown around each match for `-A`, `-B` and `-C`.
+//!
+//! ## Features
+//!
+//! - **Before Context**: Keeps the last few non-matching lines until a match calls for them
+//! - **After Context**: Shows the lines following a match, starting over at every new match
+//! - **No Repeats**: A line is shown at most once, even where two matches' context overlaps
+//!
+//! The tracker only decides which lines to show; groups that are not
+//! adjacent get the `--context-separator` between them when printed.
+
+use std::collections::VecDeque;
+
+pub struct ContextLines {
+    before: usize,
+    after: usize,
+    /// Non-matching lines since the last shown one, at most `before` of them
+    recent: VecDeque<(usize, String)>,
+    /// Lines still to show after the last match
+    after_left: usize,
+}
+
+impl ContextLines {
+    pub fn new(before: usize, after: usize) -> Self {
+        Self {
+            before,
+            after,
+            recent: VecDeque::with_capacity(before),
+            after_left: 0,
+        }
+    }
+
+    /// The held back lines to show before a match, oldest first
+    ///
+    /// Also starts the match's after context.
+    pub fn take_before(&mut self) -> Vec<(usize, String)> {
+        self.after_left = self.after;
+        self.recent.drain(..).collect()
+    }
+
+    /// Record the non-matching line `index`, returning it if it is shown as after context
+    ///
+    /// Other lines are held back in case a match follows within `before` lines.
+    pub fn other(&mut self, index: usize, line: &str) -> Option<(usize, String)> {
+        if self.after_left > 0 {
+            self.after_left -= 1;
+            return Some((index, line.to_string()));
+        }
+        if self.before > 0 {
+            if self.recent.len() == self.before {
+                self.recent.pop_front();
+            }
+            self.recent.push_back((index, line.to_string()));
+        }
+        None
+    }
+
+    /// Whether the last match still has after context to show
+    pub fn wants_more(&self) -> bool {
+        self.after_left > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_lines_around_matches_are_shown_once() {
+        let mut context = ContextLines::new(2, 1);
+        let lines = ["a", "b", "c", "MATCH", "d", "e", "MATCH", "f", "g"];
+        let mut shown = Vec::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            if *line == "MATCH" {
+                shown.extend(context.take_before());
+                shown.push((index, line.to_string()));
+            } else {
+                shown.extend(context.other(index, line));
+            }
+        }
+
+        let indexes: Vec<usize> = shown.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, vec![1, 2, 3, 4, 5, 6, 7]);
+        assert!(!context.wants_more());
+    }
+}
@@ -6,46 +6,276 @@
 //! ## Features
 //!
 //! - **Recursive Scanning**: Traverses directories recursively to find all files
-//! - **Hidden File Filtering**: Automatically skips hidden files and directories (starting with '.')
-//! - **Symlink Support**: Safely handles symbolic links during traversal
+//! - **Hidden File Filtering**: Skips hidden files and directories (starting with '.') unless `--hidden` is set
+//! - **Symlink Support**: Optionally follows symbolic links (`--follow-symlinks`) with loop detection
 //! - **Error Resilience**: Gracefully handles permission errors and inaccessible files
+//! - **Depth Limiting**: Optionally stops descending past a maximum depth (`--max-depth`)
+//! - **Ignore Files**: Skips paths matched by `.gitignore` and `.xgrepignore` rules unless disabled (`--no-ignore`)
+//! - **Glob Filters**: Keeps or drops files by glob relative to the search root (`--glob`)
+//! - **Include/Exclude**: Keeps or drops files whose name or path matches a glob (`--include`, `--exclude`)
+//! - **Type Filters**: Keeps or drops files by language extension (`--type`, `--type-not`)
+//! - **Stable Order**: Files come back sorted by path, so repeated runs print in the same order
+//! - **Multiple Paths**: [`get_files_from_paths`] crawls several paths, keeping each file once
+//! - **Lazy Discovery**: [`get_files_iter`] yields files while the walk is still running
+//! - **Sorting**: [`sort_files`] orders files by path or modification time for `--sort`/`--sortr`
+//!
+//! ## Ignore Precedence
+//!
+//! [`IGNORE_FILENAME`] files use gitignore syntax and are read from the search
+//! root, its subdirectories and its parent directories. Their rules take
+//! precedence over `.ignore` and `.gitignore`, so `!pattern` can bring back a
+//! file git ignores. CLI globs and types are applied afterwards and can only
+//! narrow the result further.
 //!
 //! ## Example
 //!
 //! ```no_run
-//! use xerg::search::crawler::get_files;
+//! use xerg::search::crawler::{get_files, get_files_iter, get_files_with_options, CrawlOptions};
 //! use std::path::PathBuf;
 //!
 //! let dir = PathBuf::from("src/");
 //! let files = get_files(&dir);
 //! println!("Found {} files", files.len());
+//!
+//! let options = CrawlOptions { max_depth: Some(1), ..CrawlOptions::default() };
+//! let top_level = get_files_with_options(&dir, &options);
+//!
+//! // Or handle each file as soon as it is found
+//! for file in get_files_iter(&dir, &options) {
+//!     println!("{}", file.display());
+//! }
 //! ```
 
-use std::path::PathBuf;
-use walkdir::{DirEntry, WalkDir};
+use super::file_types::TypeFilter;
+use crate::options::SortBy;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Name of xerg's own ignore file, read alongside `.gitignore`
+pub const IGNORE_FILENAME: &str = ".xgrepignore";
+
+/// Include/exclude glob patterns applied to paths relative to the search root
+///
+/// Patterns starting with `!` exclude matching paths. When at least one include
+/// pattern is given, a path must match one of them to be kept.
+#[derive(Debug, Clone, Default)]
+pub struct GlobFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
 
-fn is_hidden(entry: &DirEntry) -> bool {
-    entry
-        .file_name()
-        .to_str()
-        .map(|s| s.starts_with('.'))
-        .unwrap_or(false)
+impl GlobFilter {
+    /// Compile `--glob` patterns, failing on the first invalid one
+    pub fn new(patterns: &[String]) -> Result<Self, globset::Error> {
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(negated) => exclude.push(negated),
+                None => include.push(pattern.as_str()),
+            }
+        }
+
+        Ok(Self {
+            include: _build_set(&include)?,
+            exclude: _build_set(&exclude)?,
+        })
+    }
+
+    /// Compile separate include and exclude lists, as given to `--include` and `--exclude`
+    pub fn from_lists(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        let include: Vec<&str> = include.iter().map(String::as_str).collect();
+        let exclude: Vec<&str> = exclude.iter().map(String::as_str).collect();
+
+        Ok(Self {
+            include: _build_set(&include)?,
+            exclude: _build_set(&exclude)?,
+        })
+    }
+
+    /// Whether a path relative to the search root passes the filter
+    pub fn is_match(&self, relative: &Path) -> bool {
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|set| set.is_match(relative));
+        let excluded = self
+            .exclude
+            .as_ref()
+            .is_some_and(|set| set.is_match(relative));
+        included && !excluded
+    }
+
+    /// Like [`GlobFilter::is_match`], but a glob may match either the file name or the path
+    ///
+    /// Used for `--include` and `--exclude`, so `Cargo.lock` excludes the file
+    /// in any directory while `src/*.rs` still works on the relative path.
+    pub fn is_match_name_or_path(&self, relative: &Path) -> bool {
+        let matches = |set: &GlobSet| {
+            set.is_match(relative) || relative.file_name().is_some_and(|name| set.is_match(name))
+        };
+        self.include.as_ref().is_none_or(matches) && !self.exclude.as_ref().is_some_and(matches)
+    }
+}
+
+/// Build a glob set from `patterns`, or `None` when there are none
+fn _build_set(patterns: &[&str]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build().map(Some)
+}
+
+/// Settings controlling which files directory traversal returns
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    /// Maximum depth to descend, where `1` only lists the directory's own files
+    pub max_depth: Option<usize>,
+    /// Skip paths matched by `.gitignore`, `.ignore`, `.xgrepignore` and global git excludes
+    pub respect_gitignore: bool,
+    /// Include hidden files and directories (names starting with '.')
+    pub include_hidden: bool,
+    /// Descend into symlinked directories and read symlinked files found while walking
+    pub follow_symlinks: bool,
+    /// Glob filters from `--glob`, matched against paths relative to the search root
+    pub globs: GlobFilter,
+    /// Filters from `--include` and `--exclude`, matched against file names or relative paths
+    ///
+    /// A file must pass both these and `globs` to be searched.
+    pub name_globs: GlobFilter,
+    /// Extension filters from `--type` and `--type-not`
+    pub types: TypeFilter,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            respect_gitignore: true,
+            include_hidden: false,
+            follow_symlinks: false,
+            globs: GlobFilter::default(),
+            name_globs: GlobFilter::default(),
+            types: TypeFilter::default(),
+        }
+    }
 }
 
 /// Recursively discover files to search
 pub fn get_files(dir: &PathBuf) -> Vec<PathBuf> {
-    if dir.is_file() {
-        return vec![dir.clone()];
+    get_files_with_options(dir, &CrawlOptions::default())
+}
+
+/// Discover files to search, honoring the given crawl options
+///
+/// Files are returned sorted lexicographically by path. A single file is always returned as-is, regardless of the options (globs and
+/// types included), even when it is a symlink. Ignore files are honored even outside a git repository.
+pub fn get_files_with_options(dir: &PathBuf, options: &CrawlOptions) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = get_files_iter(dir, options).collect();
+    files.sort();
+    files
+}
+
+/// Discover files under several paths, in the order the paths are given
+///
+/// Each path is crawled like [`get_files_with_options`]. A file reached from
+/// more than one path, such as `src/` and `src/main.rs`, is only kept the
+/// first time.
+pub fn get_files_from_paths(paths: &[PathBuf], options: &CrawlOptions) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .iter()
+        .flat_map(|path| get_files_with_options(path, options))
+        .filter(|file| seen.insert(file.clone()))
+        .collect()
+}
+
+/// Number of files under `dir` that the crawl options leave out
+///
+/// Counts what ignore rules, hidden-file skipping, depth limits and the glob
+/// and type filters removed, so an empty search can say why it was empty.
+pub fn count_filtered_files(dir: &PathBuf, options: &CrawlOptions) -> usize {
+    let unfiltered = CrawlOptions {
+        respect_gitignore: false,
+        include_hidden: true,
+        follow_symlinks: options.follow_symlinks,
+        ..CrawlOptions::default()
+    };
+    let all = get_files_iter(dir, &unfiltered).count();
+    all.saturating_sub(get_files_iter(dir, options).count())
+}
+
+/// Last modification time of `path`, or `None` where the platform or file system lacks one
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Order `files` by `sort`, reversed when `reverse` is set
+///
+/// Files sharing a modification time, or lacking one, keep path order, so
+/// `modified` falls back to sorting by path where times are unavailable.
+pub fn sort_files(files: &mut [PathBuf], sort: SortBy, reverse: bool) {
+    match sort {
+        SortBy::Path => files.sort(),
+        SortBy::Modified => {
+            files.sort_by_cached_key(|path| (modified_time(path), path.clone()));
+        }
     }
+    if reverse {
+        files.reverse();
+    }
+}
 
-    WalkDir::new(dir)
-        .follow_links(true)
+/// Discover files lazily, yielding each one as soon as the walk reaches it
+///
+/// Filtering is the same as [`get_files_with_options`], which collects this
+/// iterator; consuming it directly lets searching start before the walk ends.
+/// Each directory's entries are visited in file name order, so files come out
+/// in the same order on every run.
+pub fn get_files_iter<'a>(
+    dir: &'a PathBuf,
+    options: &'a CrawlOptions,
+) -> impl Iterator<Item = PathBuf> + Send + 'a {
+    let single_file = dir.is_file().then(|| dir.clone());
+    let walk = single_file.is_none().then(|| {
+        let mut builder = WalkBuilder::new(dir);
+        builder
+            .standard_filters(options.respect_gitignore)
+            .require_git(false)
+            .hidden(!options.include_hidden)
+            .follow_links(options.follow_symlinks)
+            .max_depth(options.max_depth)
+            .sort_by_file_name(|a, b| a.cmp(b));
+        if options.respect_gitignore {
+            builder.add_custom_ignore_filename(IGNORE_FILENAME);
+        }
+        builder.build()
+    });
+
+    let walked = walk
         .into_iter()
-        .filter_entry(|e| !is_hidden(e))
+        .flatten()
         .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
         .map(|e| e.path().to_path_buf())
-        .collect()
+        .filter(move |path| {
+            let relative = path.strip_prefix(dir).unwrap_or(path);
+            options.globs.is_match(relative)
+                && options.name_globs.is_match_name_or_path(relative)
+                && options.types.is_match(path)
+        });
+
+    single_file.into_iter().chain(walked)
 }
 
 #[cfg(test)]
@@ -54,6 +284,13 @@ mod tests {
     use std::fs::{self, File};
     use tempdir::TempDir;
 
+    fn follow_options() -> CrawlOptions {
+        CrawlOptions {
+            follow_symlinks: true,
+            ..CrawlOptions::default()
+        }
+    }
+
     #[test]
     fn test_get_files_single_file() {
         // Create a temporary file and test get_files on it
@@ -77,7 +314,29 @@ mod tests {
         File::create(&file2).unwrap();
 
         let files = get_files(&temp_dir.into_path());
-        assert_eq!(files, vec![file2, file1]);
+        assert_eq!(files, vec![file1, file2]);
+    }
+
+    #[test]
+    fn test_get_files_iter_yields_same_files() {
+        let temp_dir = TempDir::new("test_").unwrap();
+        let sub_dir = temp_dir.path().join("subdir");
+        fs::create_dir(&sub_dir).unwrap();
+        File::create(temp_dir.path().join("file1.txt")).unwrap();
+        File::create(sub_dir.join("file2.rs")).unwrap();
+        let dir = temp_dir.path().to_path_buf();
+
+        let options = CrawlOptions::default();
+        let lazy: Vec<PathBuf> = get_files_iter(&dir, &options).collect();
+        let eager = get_files_with_options(&dir, &options);
+        assert_eq!(lazy.len(), 2);
+        assert_eq!(lazy, eager);
+
+        let file = sub_dir.join("file2.rs");
+        assert_eq!(
+            get_files_iter(&file, &options).collect::<Vec<_>>(),
+            vec![file.clone()]
+        );
     }
 
     #[test]
@@ -154,6 +413,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::len_zero)]
     fn test_get_files_follows_file_symlinks() {
         use std::os::unix::fs::symlink;
 
@@ -167,7 +427,7 @@ mod tests {
         let file_symlink = temp_dir.path().join("link_to_file.txt");
         symlink(&regular_file, &file_symlink).unwrap();
 
-        let files = get_files(&temp_dir.path().to_path_buf());
+        let files = get_files_with_options(&temp_dir.path().to_path_buf(), &follow_options());
 
         // Should include both the original file and the symlink target
         // Note: with follow_links(true), symlinks are resolved to their targets
@@ -181,6 +441,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::len_zero)]
     fn test_get_files_follows_directory_symlinks() {
         use std::os::unix::fs::symlink;
 
@@ -196,7 +457,7 @@ mod tests {
         let dir_symlink = temp_dir.path().join("link_to_dir");
         symlink(&sub_dir, &dir_symlink).unwrap();
 
-        let files = get_files(&temp_dir.path().to_path_buf());
+        let files = get_files_with_options(&temp_dir.path().to_path_buf(), &follow_options());
 
         // include files from both the original directory and via the symlink
         let mut sorted_files = files;
@@ -223,7 +484,7 @@ mod tests {
         let broken_symlink = temp_dir.path().join("broken_link.txt");
         symlink("nonexistent_file.txt", &broken_symlink).unwrap();
 
-        let files = get_files(&temp_dir.path().to_path_buf());
+        let files = get_files_with_options(&temp_dir.path().to_path_buf(), &follow_options());
 
         // Should include regular file but gracefully skip broken symlink
         assert_eq!(files, vec![regular_file]);
@@ -255,7 +516,7 @@ mod tests {
         let broken_symlink = temp_dir.path().join("broken_link.txt");
         symlink("nonexistent.txt", &broken_symlink).unwrap();
 
-        let files = get_files(&temp_dir.path().to_path_buf());
+        let files = get_files_with_options(&temp_dir.path().to_path_buf(), &follow_options());
 
         // With follow_links(true), should include regular files and handle symlinks appropriately
         assert!(files.contains(&regular_file));
@@ -269,4 +530,347 @@ mod tests {
                 .any(|path| path.to_string_lossy().contains("nonexistent"))
         );
     }
+
+    #[test]
+    fn test_count_filtered_files() {
+        let temp_dir = TempDir::new("test_filtered").unwrap();
+        let sub_dir = temp_dir.path().join("subdir");
+        fs::create_dir(&sub_dir).unwrap();
+        File::create(temp_dir.path().join("top.txt")).unwrap();
+        File::create(temp_dir.path().join(".hidden.txt")).unwrap();
+        File::create(sub_dir.join("nested.rs")).unwrap();
+        let dir = temp_dir.path().to_path_buf();
+
+        assert_eq!(count_filtered_files(&dir, &CrawlOptions::default()), 1);
+
+        let options = CrawlOptions {
+            max_depth: Some(1),
+            types: TypeFilter::new(&["rust".to_string()], &[]).unwrap(),
+            ..CrawlOptions::default()
+        };
+        assert!(get_files_with_options(&dir, &options).is_empty());
+        assert_eq!(count_filtered_files(&dir, &options), 3);
+    }
+
+    #[test]
+    fn test_sort_files_by_path_and_modified_time() {
+        let temp_dir = TempDir::new("test_sort").unwrap();
+        let old = temp_dir.path().join("b_old.txt");
+        let new = temp_dir.path().join("a_new.txt");
+        File::create(&old)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH)
+            .unwrap();
+        File::create(&new).unwrap();
+
+        let mut files = vec![old.clone(), new.clone()];
+        sort_files(&mut files, SortBy::Path, false);
+        assert_eq!(files, vec![new.clone(), old.clone()]);
+
+        sort_files(&mut files, SortBy::Modified, false);
+        assert_eq!(files, vec![old.clone(), new.clone()]);
+
+        sort_files(&mut files, SortBy::Modified, true);
+        assert_eq!(files, vec![new.clone(), old.clone()]);
+
+        // Without modification times, path order remains
+        let mut missing = vec![temp_dir.path().join("z"), temp_dir.path().join("y")];
+        sort_files(&mut missing, SortBy::Modified, false);
+        assert_eq!(missing[0], temp_dir.path().join("y"));
+    }
+
+    #[test]
+    fn test_get_files_max_depth() {
+        let temp_dir = TempDir::new("test_max_depth").unwrap();
+
+        let top_file = temp_dir.path().join("top.txt");
+        File::create(&top_file).unwrap();
+        let sub_dir = temp_dir.path().join("subdir");
+        fs::create_dir(&sub_dir).unwrap();
+        let sub_file = sub_dir.join("nested.txt");
+        File::create(&sub_file).unwrap();
+        let deep_dir = sub_dir.join("deeper");
+        fs::create_dir(&deep_dir).unwrap();
+        File::create(deep_dir.join("deep.txt")).unwrap();
+
+        let dir = temp_dir.path().to_path_buf();
+
+        let options = CrawlOptions {
+            max_depth: Some(1),
+            ..CrawlOptions::default()
+        };
+        assert_eq!(
+            get_files_with_options(&dir, &options),
+            vec![top_file.clone()]
+        );
+
+        let options = CrawlOptions {
+            max_depth: Some(2),
+            ..CrawlOptions::default()
+        };
+        let files = get_files_with_options(&dir, &options);
+        assert_eq!(files, vec![sub_file, top_file]);
+
+        assert_eq!(
+            get_files_with_options(&dir, &CrawlOptions::default()).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_get_files_respects_gitignore() {
+        let temp_dir = TempDir::new("test_gitignore").unwrap();
+
+        fs::write(temp_dir.path().join(".gitignore"), "target/\n*.log\n").unwrap();
+        let target_dir = temp_dir.path().join("target");
+        fs::create_dir(&target_dir).unwrap();
+        File::create(target_dir.join("build.txt")).unwrap();
+        File::create(temp_dir.path().join("debug.log")).unwrap();
+        let kept_file = temp_dir.path().join("main.rs");
+        File::create(&kept_file).unwrap();
+
+        let dir = temp_dir.path().to_path_buf();
+        assert_eq!(get_files(&dir), vec![kept_file.clone()]);
+
+        let options = CrawlOptions {
+            respect_gitignore: false,
+            ..CrawlOptions::default()
+        };
+        let files = get_files_with_options(&dir, &options);
+        assert_eq!(
+            files,
+            vec![
+                temp_dir.path().join("debug.log"),
+                kept_file,
+                target_dir.join("build.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_files_respects_xgrepignore() {
+        let temp_dir = TempDir::new("test_xgrepignore").unwrap();
+        let sub_dir = temp_dir.path().join("sub");
+        fs::create_dir(&sub_dir).unwrap();
+
+        // Found in a parent of the search root, and overriding .gitignore
+        fs::write(temp_dir.path().join(".xgrepignore"), "*.snap\n!keep.log\n").unwrap();
+        fs::write(sub_dir.join(".gitignore"), "*.log\n").unwrap();
+        for name in ["main.rs", "main.snap", "keep.log", "drop.log"] {
+            File::create(sub_dir.join(name)).unwrap();
+        }
+
+        assert_eq!(
+            get_files(&sub_dir),
+            vec![sub_dir.join("keep.log"), sub_dir.join("main.rs")]
+        );
+
+        let options = CrawlOptions {
+            respect_gitignore: false,
+            ..CrawlOptions::default()
+        };
+        assert_eq!(get_files_with_options(&sub_dir, &options).len(), 4);
+    }
+
+    #[test]
+    fn test_get_files_single_file_bypasses_gitignore() {
+        let temp_dir = TempDir::new("test_gitignore_single").unwrap();
+
+        fs::write(temp_dir.path().join(".gitignore"), "*.log\n").unwrap();
+        let log_file = temp_dir.path().join("debug.log");
+        File::create(&log_file).unwrap();
+
+        assert_eq!(get_files(&log_file), vec![log_file]);
+    }
+
+    #[test]
+    fn test_get_files_includes_hidden_when_enabled() {
+        let temp_dir = TempDir::new("test_").unwrap();
+
+        let hidden_file = temp_dir.path().join(".hidden_file");
+        let regular_file = temp_dir.path().join("regular_file.txt");
+        File::create(&hidden_file).unwrap();
+        File::create(&regular_file).unwrap();
+
+        let options = CrawlOptions {
+            include_hidden: true,
+            ..CrawlOptions::default()
+        };
+        let files = get_files_with_options(&temp_dir.path().to_path_buf(), &options);
+        assert_eq!(files, vec![hidden_file, regular_file]);
+    }
+
+    #[test]
+    fn test_get_files_skips_symlinked_directories_by_default() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new("test_no_follow").unwrap();
+
+        let sub_dir = temp_dir.path().join("subdir");
+        fs::create_dir(&sub_dir).unwrap();
+        let sub_file = sub_dir.join("file.txt");
+        File::create(&sub_file).unwrap();
+        symlink(&sub_dir, temp_dir.path().join("link_to_dir")).unwrap();
+
+        let files = get_files(&temp_dir.path().to_path_buf());
+        assert_eq!(files, vec![sub_file]);
+    }
+
+    #[test]
+    fn test_get_files_self_referential_symlink_terminates() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new("test_symlink_loop").unwrap();
+
+        let sub_dir = temp_dir.path().join("subdir");
+        fs::create_dir(&sub_dir).unwrap();
+        let sub_file = sub_dir.join("file.txt");
+        File::create(&sub_file).unwrap();
+        // subdir/loop -> .. points back up the tree
+        symlink(temp_dir.path(), sub_dir.join("loop")).unwrap();
+
+        let dir = temp_dir.path().to_path_buf();
+        assert_eq!(get_files(&dir), vec![sub_file.clone()]);
+
+        // Following links detects the cycle instead of walking it forever
+        let files = get_files_with_options(&dir, &follow_options());
+        assert_eq!(files, vec![sub_file]);
+    }
+
+    #[test]
+    fn test_get_files_symlinked_file_argument_is_read() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new("test_symlink_arg").unwrap();
+
+        let regular_file = temp_dir.path().join("regular.txt");
+        File::create(&regular_file).unwrap();
+        let file_symlink = temp_dir.path().join("link.txt");
+        symlink(&regular_file, &file_symlink).unwrap();
+
+        assert_eq!(get_files(&file_symlink), vec![file_symlink]);
+    }
+
+    fn glob_options(patterns: &[&str]) -> CrawlOptions {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        CrawlOptions {
+            globs: GlobFilter::new(&patterns).unwrap(),
+            ..CrawlOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_get_files_glob_include_and_exclude() {
+        let temp_dir = TempDir::new("test_globs").unwrap();
+
+        let tests_dir = temp_dir.path().join("tests");
+        fs::create_dir(&tests_dir).unwrap();
+        let main_rs = temp_dir.path().join("main.rs");
+        File::create(&main_rs).unwrap();
+        File::create(temp_dir.path().join("notes.md")).unwrap();
+        let test_rs = tests_dir.join("it.rs");
+        File::create(&test_rs).unwrap();
+
+        let dir = temp_dir.path().to_path_buf();
+
+        let files = get_files_with_options(&dir, &glob_options(&["*.rs"]));
+        assert_eq!(files, vec![main_rs.clone(), test_rs]);
+
+        let files = get_files_with_options(&dir, &glob_options(&["*.rs", "!tests/*"]));
+        assert_eq!(files, vec![main_rs]);
+    }
+
+    #[test]
+    fn test_get_files_include_and_exclude_match_names() {
+        let temp_dir = TempDir::new("test_include").unwrap();
+
+        let sub_dir = temp_dir.path().join("sub");
+        fs::create_dir(&sub_dir).unwrap();
+        let top_toml = temp_dir.path().join("Cargo.toml");
+        let sub_toml = sub_dir.join("Cargo.toml");
+        File::create(&top_toml).unwrap();
+        File::create(&sub_toml).unwrap();
+        File::create(sub_dir.join("Cargo.lock")).unwrap();
+        File::create(temp_dir.path().join("main.rs")).unwrap();
+
+        let include = vec!["*.toml".to_string(), "Cargo.lock".to_string()];
+        let options = CrawlOptions {
+            name_globs: GlobFilter::from_lists(&include, &["Cargo.lock".to_string()]).unwrap(),
+            ..CrawlOptions::default()
+        };
+        let dir = temp_dir.path().to_path_buf();
+        assert_eq!(
+            get_files_with_options(&dir, &options),
+            vec![top_toml.clone(), sub_toml]
+        );
+
+        let options = CrawlOptions {
+            name_globs: GlobFilter::from_lists(&[], &["sub/*".to_string()]).unwrap(),
+            globs: GlobFilter::new(&["*.toml".to_string()]).unwrap(),
+            ..CrawlOptions::default()
+        };
+        assert_eq!(get_files_with_options(&dir, &options), vec![top_toml]);
+    }
+
+    #[test]
+    fn test_get_files_glob_bypassed_for_single_file() {
+        let temp_dir = TempDir::new("test_globs_single").unwrap();
+        let notes = temp_dir.path().join("notes.md");
+        File::create(&notes).unwrap();
+
+        assert_eq!(
+            get_files_with_options(&notes, &glob_options(&["*.rs"])),
+            vec![notes]
+        );
+    }
+
+    #[test]
+    fn test_get_files_from_paths_keeps_order_and_dedups() {
+        let temp_dir = TempDir::new("test_multiple_paths").unwrap();
+        let src = temp_dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        let lib = src.join("lib.rs");
+        let main = src.join("main.rs");
+        let build = temp_dir.path().join("build.rs");
+        for file in [&lib, &main, &build] {
+            File::create(file).unwrap();
+        }
+
+        let paths = vec![build.clone(), src.clone(), main.clone()];
+        assert_eq!(
+            get_files_from_paths(&paths, &CrawlOptions::default()),
+            vec![build, lib, main]
+        );
+    }
+
+    #[test]
+    fn test_glob_filter_rejects_invalid_pattern() {
+        assert!(GlobFilter::new(&["a[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_get_files_type_filter() {
+        let temp_dir = TempDir::new("test_types").unwrap();
+
+        let main_rs = temp_dir.path().join("main.rs");
+        File::create(&main_rs).unwrap();
+        let readme = temp_dir.path().join("README.md");
+        File::create(&readme).unwrap();
+
+        let dir = temp_dir.path().to_path_buf();
+        let options = CrawlOptions {
+            types: TypeFilter::new(&["rust".to_string()], &[]).unwrap(),
+            ..CrawlOptions::default()
+        };
+        assert_eq!(
+            get_files_with_options(&dir, &options),
+            vec![main_rs.clone()]
+        );
+
+        let options = CrawlOptions {
+            types: TypeFilter::new(&[], &["rust".to_string()]).unwrap(),
+            ..CrawlOptions::default()
+        };
+        assert_eq!(get_files_with_options(&dir, &options), vec![readme]);
+    }
 }
@@ -0,0 +1,106 @@
+//! # Search Deadline
+//!
+//! Shared flag behind `--timeout`, which stops a search once it has run for
+//! too long while still reporting the results found so far.
+//!
+//! A watchdog thread sleeps until the deadline and then expires it. Workers
+//! check the flag before opening each file, so a file already being searched
+//! is finished rather than cut off halfway.
+
+use crate::options::SearchOptions;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Cloneable handle to a deadline shared by the caller and the search workers
+#[derive(Debug, Clone, Default)]
+pub struct Deadline {
+    state: Arc<DeadlineState>,
+}
+
+#[derive(Debug, Default)]
+struct DeadlineState {
+    /// Set by the watchdog once the time is up
+    expired: AtomicBool,
+    /// Set when a file was skipped because the time was up
+    interrupted: AtomicBool,
+}
+
+impl Deadline {
+    /// Start a watchdog thread that expires the deadline after `timeout`
+    ///
+    /// A zero timeout expires immediately, without a thread.
+    pub fn after(timeout: Duration) -> Self {
+        let deadline = Self::default();
+        if timeout.is_zero() {
+            deadline.expire();
+        } else {
+            let watchdog = deadline.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(timeout);
+                watchdog.expire();
+            });
+        }
+
+        deadline
+    }
+
+    /// Expire the deadline now, for example to cancel a running search
+    pub fn expire(&self) {
+        self.state.expired.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the time is up
+    pub fn expired(&self) -> bool {
+        self.state.expired.load(Ordering::Relaxed)
+    }
+
+    /// Whether the next file should be skipped, recording that the search was cut short
+    pub fn skip_file(&self) -> bool {
+        let expired = self.expired();
+        if expired {
+            self.state.interrupted.store(true, Ordering::Relaxed);
+        }
+        expired
+    }
+
+    /// Whether any file went unsearched because the deadline expired
+    pub fn interrupted(&self) -> bool {
+        self.state.interrupted.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether the next file should be skipped because `options.deadline` expired
+pub fn out_of_time(options: &SearchOptions) -> bool {
+    options.deadline.as_ref().is_some_and(Deadline::skip_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skipping_after_expiry_marks_interrupted() {
+        let deadline = Deadline::after(Duration::from_secs(60));
+        assert!(!deadline.skip_file());
+        assert!(!deadline.interrupted());
+
+        // Expiry alone does not mean the search missed anything
+        let shared = deadline.clone();
+        shared.expire();
+        assert!(deadline.expired());
+        assert!(!deadline.interrupted());
+
+        assert!(deadline.skip_file());
+        assert!(shared.interrupted());
+    }
+
+    #[test]
+    fn test_watchdog_expires_deadline() {
+        assert!(Deadline::after(Duration::ZERO).expired());
+
+        let deadline = Deadline::after(Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(deadline.expired());
+    }
+}
@@ -10,6 +10,12 @@
 //! - **Work-stealing Scheduler**: Rayon's scheduler automatically balances work across threads
 //! - **Memory Efficient**: Line-by-line processing handles files of any size
 //! - **Structured Streaming**: Streams structured matches per file with headers and statistics
+//! - **Iterator API**: [`search_iter`] lazily yields [`Match`] values for library embedders
+//! - **Other Encodings**: Optionally transcodes files such as UTF-16 to UTF-8 (`--encoding`)
+//! - **Compressed Files**: Optionally decompresses `.gz` files while searching (`-z`)
+//! - **Multiline**: Optionally matches across line breaks, reporting whole spanned lines (`-U`)
+//! - **Raw Bytes**: Optionally searches lines that are not valid UTF-8 instead of skipping them (`--byte-regexp`)
+//! - **Per-File Timing**: Optionally reports how long each file took to search (`--timing`)
 //! - **Error Resilient**: Graceful per-file error recovery without stopping other files
 //!
 //! ## Performance Characteristics
@@ -22,129 +28,581 @@
 //!
 //! ```no_run
 //! use xerg::search::default::search_files;
+//! use xerg::options::SearchOptions;
 //! use xerg::output::colors::Color;
 //! use std::path::PathBuf;
 //!
 //! let files = vec![PathBuf::from("src/main.rs")];
-//! let pattern = "use";
-//! let color = Color::Blue;
-//! let rx = search_files(&files, pattern, &color, true);
+//! let options = SearchOptions {
+//!     color: Color::Blue,
+//!     show_stats: true,
+//!     ..SearchOptions::new("use")
+//! };
+//! let rx = search_files(&files, &options);
 //!
 //! // Process results from receiver...
 //! ```
 
-use super::reader::FileReader;
+use super::context::ContextLines;
+use super::deadline::out_of_time;
+use super::limit::MatchLimit;
+use super::multiline::{MatchBlock, match_blocks};
+use super::progress::ProgressReporter;
+use super::reader::{
+    FileReader, STDIN_LABEL, content_lines, decode_lossy, decode_lossy_spans, read_decoded,
+    reader_lines, split_by_size,
+};
+use crate::options::{LineRange, SearchOptions};
+use crate::output::highlighter::TextHighlighter;
 use crate::output::result::{FileMatchResult, ResultMessage};
-use crate::output::{colors::Color, highlighter::TextHighlighter};
+use encoding_rs::Encoding;
+use flate2::read::GzDecoder;
 use memmap2::MmapOptions;
 use rayon::scope;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Result};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, ErrorKind, Result};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::time::Instant;
+
+/// Per-line settings copied out of [`SearchOptions`] so worker threads can own them
+#[derive(Clone, Copy)]
+struct LineSettings {
+    /// Keep raw text and match offsets for `--json`
+    structured: bool,
+    /// Stop showing a file's matches after this many matching lines (`--max-count`)
+    max_count: Option<usize>,
+    /// Keep reading past `max_count` so the per-file counts cover every match
+    count_past_limit: bool,
+    /// Only count matches, without building a message per line (`--count-matches`, `--total`)
+    count_only: bool,
+    /// Search whole contents so matches may span lines (`--multiline`)
+    multiline: bool,
+    /// Report how long each file took to search (`--timing`)
+    timing: bool,
+    /// Only search these lines of each file (`--lines`)
+    lines: Option<LineRange>,
+    /// Non-matching lines shown before and after each match (`-B`, `-A`)
+    before_context: usize,
+    after_context: usize,
+}
+
+impl LineSettings {
+    fn new(options: &SearchOptions) -> Self {
+        let max_count = match (options.max_count, options.max_matches_total) {
+            (Some(max), Some(total)) => Some(max.min(total)),
+            (max, total) => max.or(total),
+        };
+
+        Self {
+            structured: options.json,
+            max_count,
+            count_past_limit: false,
+            count_only: options.count_matches || options.total || options.summary_json,
+            multiline: options.multiline,
+            timing: options.timing,
+            lines: options.lines,
+            before_context: options.before_context,
+            after_context: options.after_context,
+        }
+    }
+
+    /// Tracker for the context lines of one file, showing none when only counting
+    fn context(&self) -> ContextLines {
+        if self.count_only {
+            ContextLines::new(0, 0)
+        } else {
+            ContextLines::new(self.before_context, self.after_context)
+        }
+    }
+
+    /// Count every match past `--max-count` when `show_stats` reports per-file totals
+    ///
+    /// Reading on costs the time the limit would have saved, so it is skipped
+    /// with `--max-count-stops-scan`, and under `--max-matches-total`, which
+    /// stops the whole search anyway.
+    fn with_stats(self, show_stats: bool, options: &SearchOptions) -> Self {
+        Self {
+            count_past_limit: show_stats
+                && !options.max_count_stops_scan
+                && options.max_matches_total.is_none(),
+            ..self
+        }
+    }
+
+    /// Settings for the next file under `--max-matches-total`, or `None` once it is reached
+    ///
+    /// The file may only find as many matching lines as the limit has left.
+    fn within(self, limit: &MatchLimit) -> Option<Self> {
+        match limit.remaining() {
+            Some(0) => None,
+            Some(remaining) => Some(Self {
+                max_count: Some(self.max_count.map_or(remaining, |max| max.min(remaining))),
+                ..self
+            }),
+            None => Some(self),
+        }
+    }
+
+    /// Whether the 0-based line `index` is past the `--lines` range, so reading can stop
+    fn past_range(&self, index: usize) -> bool {
+        self.lines.is_some_and(|range| range.is_past(index + 1))
+    }
+
+    /// Whether the 0-based line `index` is outside the `--lines` range
+    fn out_of_range(&self, index: usize) -> bool {
+        self.lines.is_some_and(|range| !range.contains(index + 1))
+    }
+
+    /// Whether `matched_lines` has reached the `--max-count` limit, so no more matches are shown
+    fn over_limit(&self, matched_lines: usize) -> bool {
+        self.max_count.is_some_and(|max| matched_lines >= max)
+    }
+
+    /// Whether reading can stop after `matched_lines`, as later matches are neither shown nor counted
+    fn limit_reached(&self, matched_lines: usize) -> bool {
+        !self.count_past_limit && self.over_limit(matched_lines)
+    }
+
+    /// Whether a matching line after `matched_lines` others gets a message
+    fn shows_match(&self, matched_lines: usize) -> bool {
+        !self.count_only && !self.over_limit(matched_lines)
+    }
+}
+
+/// Build the message for a matching line
+///
+/// Structured output keeps the raw text and the byte range of every match
+/// instead of embedding ANSI codes in the line.
+fn _line_message(
+    index: usize,
+    line_offset: usize,
+    line: &str,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+) -> ResultMessage {
+    if settings.structured {
+        ResultMessage::Match {
+            index,
+            text: line.to_string(),
+            submatches: highlighter.match_spans(line),
+            byte_offset: highlighter.match_offset(line, line_offset),
+        }
+    } else {
+        ResultMessage::Line {
+            index,
+            column: highlighter.match_column(line),
+            byte_offset: highlighter.match_offset(line, line_offset),
+            matches: highlighter.line_match_count(line),
+            content: highlighter.highlight(line),
+        }
+    }
+}
+
+/// Messages for lines shown as context around a match
+fn _context_messages(
+    lines: impl IntoIterator<Item = (usize, String)>,
+) -> impl Iterator<Item = ResultMessage> {
+    lines
+        .into_iter()
+        .map(|(index, content)| ResultMessage::Context { index, content })
+}
+
+/// Build the message for a `--multiline` block from its recorded match spans
+fn _block_message(
+    block: &MatchBlock,
+    text: &str,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+) -> ResultMessage {
+    let byte_offset = highlighter.span_offset(&block.spans, block.start);
+    if settings.structured {
+        ResultMessage::Match {
+            index: block.line_index,
+            text: text.to_string(),
+            submatches: block.spans.clone(),
+            byte_offset,
+        }
+    } else {
+        ResultMessage::Line {
+            index: block.line_index,
+            column: highlighter.span_column(text, &block.spans),
+            byte_offset,
+            matches: highlighter.span_match_count(&block.spans),
+            content: highlighter.highlight_spans(text, &block.spans),
+        }
+    }
+}
+
+/// Build the message for a line that is not valid UTF-8 under `--byte-regexp`
+///
+/// The line is shown decoded with U+FFFD replacements; columns and byte
+/// offsets still count the raw bytes.
+fn _bytes_message(
+    index: usize,
+    line_offset: usize,
+    line: &[u8],
+    spans: &[(usize, usize)],
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+) -> ResultMessage {
+    let (text, text_spans) = decode_lossy_spans(line, spans);
+    let byte_offset = highlighter.span_offset(spans, line_offset);
+    if settings.structured {
+        ResultMessage::Match {
+            index,
+            text,
+            submatches: text_spans,
+            byte_offset,
+        }
+    } else {
+        ResultMessage::Line {
+            index,
+            column: highlighter.span_column(&text, spans),
+            byte_offset,
+            matches: highlighter.span_match_count(spans),
+            content: highlighter.highlight_spans(&text, &text_spans),
+        }
+    }
+}
 
 /// Process content line by line and collect matches
+///
+/// When `lossy` is set the content was decoded with replacements, so lines
+/// containing U+FFFD are counted as skipped instead of searched.
 fn _process_content_lines(
     content: &str,
     highlighter: &TextHighlighter,
+    settings: LineSettings,
     messages: &mut Vec<ResultMessage>,
-) -> (usize, usize) {
+    lossy: bool,
+) -> (usize, usize, usize) {
+    if settings.multiline {
+        return _process_content_multiline(content, highlighter, settings, messages, lossy);
+    }
+
     let mut total_lines = 0;
     let mut matched_count = 0;
+    let mut skipped_count = 0;
+    let mut matched_lines = 0;
+    let mut context = settings.context();
 
-    for (index, line) in content.lines().enumerate() {
+    for (index, (offset, line)) in content_lines(content).enumerate() {
+        let limited = settings.limit_reached(matched_lines);
+        if (limited && !context.wants_more()) || settings.past_range(index) {
+            break;
+        }
+        if settings.out_of_range(index) {
+            continue;
+        }
+        if lossy && line.contains(char::REPLACEMENT_CHARACTER) {
+            skipped_count += 1;
+            continue;
+        }
+        // Past --max-count, lines are only read for the last match's after context
+        if limited {
+            messages.extend(_context_messages(context.other(index, line)));
+            continue;
+        }
         total_lines += 1;
 
-        if highlighter.regex.is_match(line) {
-            let line_msg = ResultMessage::Line {
-                index,
-                content: highlighter.highlight(line),
-            };
-            messages.push(line_msg);
-            let matches_in_line = highlighter.regex.find_iter(line).count();
+        if highlighter.is_match(line) {
+            if settings.shows_match(matched_lines) {
+                messages.extend(_context_messages(context.take_before()));
+                messages.push(_line_message(index, offset, line, highlighter, settings));
+            }
+            let matches_in_line = highlighter.match_count(line);
             matched_count += matches_in_line;
+            matched_lines += 1;
+        } else {
+            messages.extend(_context_messages(context.other(index, line)));
         }
     }
 
-    (total_lines, matched_count)
+    (total_lines, matched_count, skipped_count)
 }
 
-/// Process file using streaming line-by-line reading with BufReader
-fn _process_file_streaming(
-    filepath: &PathBuf,
+/// Process whole content for `--multiline`, reporting each block of matching lines once
+///
+/// Every line is counted up front. Blocks touching a line with U+FFFD are left
+/// out, as those lines are counted as skipped in [`_process_content_lines`].
+fn _process_content_multiline(
+    content: &str,
     highlighter: &TextHighlighter,
+    settings: LineSettings,
     messages: &mut Vec<ResultMessage>,
-) -> Result<(usize, usize, usize)> {
-    let file = File::open(filepath)?;
-    let reader = BufReader::new(file);
+    lossy: bool,
+) -> (usize, usize, usize) {
+    let is_lossy_line = |line: &str| lossy && line.contains(char::REPLACEMENT_CHARACTER);
+    let skipped_count = content_lines(content)
+        .filter(|(_, line)| is_lossy_line(line))
+        .count();
+    let total_lines = content_lines(content).count() - skipped_count;
+    let mut matched_count = 0;
+    let mut matched_blocks = 0;
+
+    for block in match_blocks(content, &highlighter.regex) {
+        if settings.limit_reached(matched_blocks) {
+            break;
+        }
+        let text = block.text(content);
+        if is_lossy_line(text) {
+            continue;
+        }
+
+        if settings.shows_match(matched_blocks) {
+            messages.push(_block_message(&block, text, highlighter, settings));
+        }
+        matched_count += block.spans.len();
+        matched_blocks += 1;
+    }
+
+    (total_lines, matched_count, skipped_count)
+}
+
+/// Process a line-oriented reader, handing each matching line to `emit`
+///
+/// Lines that are not valid UTF-8 are counted as skipped rather than aborting,
+/// the same as lines with replacements in [`_process_content_lines`], unless
+/// `--byte-regexp` searches them as bytes. Under `--multiline` the reader is
+/// drained first and searched as a whole.
+fn _process_reader<R: BufRead>(
+    mut reader: R,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+    mut emit: impl FnMut(ResultMessage),
+) -> (usize, usize, usize) {
+    if settings.multiline {
+        let mut bytes = Vec::new();
+        if reader.read_to_end(&mut bytes).is_err() {
+            return (0, 0, 1);
+        }
+        let (content, lossy) = decode_lossy(&bytes);
+        let mut messages = Vec::new();
+        let stats =
+            _process_content_multiline(&content, highlighter, settings, &mut messages, lossy);
+        messages.into_iter().for_each(emit);
+        return stats;
+    }
+
+    if let Some(bytes_regex) = &highlighter.bytes_regex {
+        return _process_reader_bytes(reader, bytes_regex, highlighter, settings, emit);
+    }
 
     let mut total_lines = 0;
     let mut matched_count = 0;
     let mut skipped_count = 0;
+    let mut matched_lines = 0;
+    let mut context = settings.context();
 
-    for (index, line) in reader.lines().enumerate() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_e) => {
+    for (index, line) in reader_lines(reader).enumerate() {
+        let limited = settings.limit_reached(matched_lines);
+        if (limited && !context.wants_more()) || settings.past_range(index) {
+            break;
+        }
+        if settings.out_of_range(index) {
+            continue;
+        }
+
+        let (offset, line) = match line {
+            Ok(line) => line,
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
                 skipped_count += 1;
                 continue;
             }
+            // Other errors (e.g. a corrupt gzip stream) repeat forever, so stop reading
+            Err(_e) => {
+                skipped_count += 1;
+                break;
+            }
         };
+        // Past --max-count, lines are only read for the last match's after context
+        if limited {
+            _context_messages(context.other(index, &line)).for_each(&mut emit);
+            continue;
+        }
         total_lines += 1;
 
-        if highlighter.regex.is_match(&line) {
-            let line_msg = ResultMessage::Line {
-                index,
-                content: highlighter.highlight(&line),
-            };
-            messages.push(line_msg);
-            let matches_in_line = highlighter.regex.find_iter(&line).count();
+        if highlighter.is_match(&line) {
+            if settings.shows_match(matched_lines) {
+                _context_messages(context.take_before()).for_each(&mut emit);
+                emit(_line_message(index, offset, &line, highlighter, settings));
+            }
+            let matches_in_line = highlighter.match_count(&line);
             matched_count += matches_in_line;
+            matched_lines += 1;
+        } else {
+            _context_messages(context.other(index, &line)).for_each(&mut emit);
+        }
+    }
+
+    (total_lines, matched_count, skipped_count)
+}
+
+/// Process a line-oriented reader for `--byte-regexp`, matching raw bytes
+///
+/// Lines that are valid UTF-8 produce the same messages as in
+/// [`_process_reader`]; the others are shown with replacements.
+fn _process_reader_bytes<R: BufRead>(
+    reader: R,
+    bytes_regex: &regex::bytes::Regex,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+    mut emit: impl FnMut(ResultMessage),
+) -> (usize, usize, usize) {
+    let mut total_lines = 0;
+    let mut matched_count = 0;
+    let mut skipped_count = 0;
+    let mut matched_lines = 0;
+    let mut context = settings.context();
+    let mut lines = reader_lines(reader);
+
+    for (index, line) in std::iter::from_fn(|| lines.next_bytes()).enumerate() {
+        let limited = settings.limit_reached(matched_lines);
+        if (limited && !context.wants_more()) || settings.past_range(index) {
+            break;
+        }
+
+        let Ok((offset, line)) = line else {
+            // Read errors repeat forever, so stop reading
+            skipped_count += 1;
+            break;
+        };
+        if settings.out_of_range(index) {
+            continue;
+        }
+        // Past --max-count, lines are only read for the last match's after context
+        if limited {
+            let text = String::from_utf8_lossy(&line);
+            _context_messages(context.other(index, &text)).for_each(&mut emit);
+            continue;
+        }
+        total_lines += 1;
+
+        let spans: Vec<_> = bytes_regex
+            .find_iter(&line)
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        if spans.is_empty() {
+            let text = String::from_utf8_lossy(&line);
+            _context_messages(context.other(index, &text)).for_each(&mut emit);
+            continue;
+        }
+
+        if settings.shows_match(matched_lines) {
+            _context_messages(context.take_before()).for_each(&mut emit);
+            emit(match std::str::from_utf8(&line) {
+                Ok(text) => _line_message(index, offset, text, highlighter, settings),
+                Err(_) => _bytes_message(index, offset, &line, &spans, highlighter, settings),
+            });
         }
+        matched_count += spans.len();
+        matched_lines += 1;
     }
 
-    Ok((total_lines, matched_count, skipped_count))
+    (total_lines, matched_count, skipped_count)
+}
+
+/// Process file using streaming line-by-line reading with BufReader
+fn _process_file_streaming(
+    filepath: &PathBuf,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+    messages: &mut Vec<ResultMessage>,
+) -> Result<(usize, usize, usize)> {
+    let file = File::open(filepath)?;
+    let reader = BufReader::new(file);
+
+    Ok(_process_reader(reader, highlighter, settings, |msg| {
+        messages.push(msg)
+    }))
+}
+
+/// Process a gzip-compressed file by streaming it through a decoder
+fn _process_file_gzip(
+    filepath: &PathBuf,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+    messages: &mut Vec<ResultMessage>,
+) -> Result<(usize, usize, usize)> {
+    let file = File::open(filepath)?;
+    let reader = BufReader::new(GzDecoder::new(file));
+
+    Ok(_process_reader(reader, highlighter, settings, |msg| {
+        messages.push(msg)
+    }))
 }
 
 /// Process file using bulk read with fs::read_to_string
 fn _process_file_bulk_read(
     filepath: &PathBuf,
     highlighter: &TextHighlighter,
+    settings: LineSettings,
     messages: &mut Vec<ResultMessage>,
 ) -> Result<(usize, usize, usize)> {
-    let content = std::fs::read_to_string(filepath)?;
-    let (total_lines, matched_count) = _process_content_lines(&content, highlighter, messages);
-    Ok((total_lines, matched_count, 0)) // No skipped lines with bulk reading
+    let bytes = std::fs::read(filepath)?;
+    let (content, lossy) = decode_lossy(&bytes);
+    Ok(_process_content_lines(
+        &content,
+        highlighter,
+        settings,
+        messages,
+        lossy,
+    ))
+}
+
+/// Process a file in another encoding by transcoding it to UTF-8 first
+fn _process_file_decoded(
+    filepath: &Path,
+    encoding: &'static Encoding,
+    highlighter: &TextHighlighter,
+    settings: LineSettings,
+    messages: &mut Vec<ResultMessage>,
+) -> Result<(usize, usize, usize)> {
+    let (content, had_errors) = read_decoded(filepath, encoding)?;
+    Ok(_process_content_lines(
+        &content,
+        highlighter,
+        settings,
+        messages,
+        had_errors,
+    ))
 }
 
 /// Process file using memory mapping
 fn _process_file_memory_map(
     filepath: &PathBuf,
     highlighter: &TextHighlighter,
+    settings: LineSettings,
     messages: &mut Vec<ResultMessage>,
 ) -> Result<(usize, usize, usize)> {
     let file = File::open(filepath)?;
     let mmap = unsafe { MmapOptions::new().map(&file)? };
-    let content = std::str::from_utf8(&mmap)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-
-    let (total_lines, matched_count) = _process_content_lines(content, highlighter, messages);
-    Ok((total_lines, matched_count, 0)) // No skipped lines with memory mapping
+    let (content, lossy) = decode_lossy(&mmap);
+
+    Ok(_process_content_lines(
+        &content,
+        highlighter,
+        settings,
+        messages,
+        lossy,
+    ))
 }
 
 fn _process_file(
     filepath: &PathBuf,
-    _pattern: &str,
     highlighter: &TextHighlighter,
     show_stats: bool,
+    settings: LineSettings,
     reader: FileReader,
 ) -> Result<FileMatchResult> {
+    let start = settings.timing.then(Instant::now);
     let mut messages = Vec::new();
     messages.push(ResultMessage::Header(filepath.to_path_buf()));
 
     let (total_lines, matched_count, skipped_count) = match reader {
         FileReader::Streaming => {
-            match _process_file_streaming(filepath, highlighter, &mut messages) {
+            match _process_file_streaming(filepath, highlighter, settings, &mut messages) {
                 Ok(stats) => stats,
                 Err(e) => {
                     let err_msg = format!("Failed to process file {}: {}", filepath.display(), e);
@@ -154,8 +612,31 @@ fn _process_file(
             }
         }
 
+        FileReader::Decode(encoding) => {
+            match _process_file_decoded(filepath, encoding, highlighter, settings, &mut messages) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    let err_msg = format!("Failed to read file {}: {}", filepath.display(), e);
+                    messages.push(ResultMessage::Error(err_msg));
+                    return Ok(messages);
+                }
+            }
+        }
+
+        FileReader::Gzip => {
+            match _process_file_gzip(filepath, highlighter, settings, &mut messages) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    let err_msg =
+                        format!("Failed to decompress file {}: {}", filepath.display(), e);
+                    messages.push(ResultMessage::Error(err_msg));
+                    return Ok(messages);
+                }
+            }
+        }
+
         FileReader::BulkRead => {
-            match _process_file_bulk_read(filepath, highlighter, &mut messages) {
+            match _process_file_bulk_read(filepath, highlighter, settings, &mut messages) {
                 Ok(stats) => stats,
                 Err(e) => {
                     let err_msg = format!("Failed to read file {}: {}", filepath.display(), e);
@@ -166,7 +647,7 @@ fn _process_file(
         }
 
         FileReader::MemoryMap => {
-            match _process_file_memory_map(filepath, highlighter, &mut messages) {
+            match _process_file_memory_map(filepath, highlighter, settings, &mut messages) {
                 Ok(stats) => stats,
                 Err(e) => {
                     let err_msg =
@@ -187,35 +668,86 @@ fn _process_file(
         });
     }
 
+    if let Some(start) = start {
+        messages.push(ResultMessage::Timing {
+            path: filepath.to_path_buf(),
+            millis: start.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+
     messages.push(ResultMessage::Done);
     Ok(messages)
 }
 
-pub fn search_files(
-    files: &[PathBuf],
-    pattern: &str,
-    color: &Color,
-    show_stats: bool,
-) -> mpsc::Receiver<FileMatchResult> {
+/// Number of matching lines reported in one file's messages
+fn _matched_lines(messages: &[ResultMessage]) -> usize {
+    messages
+        .iter()
+        .filter(|msg| {
+            matches!(
+                msg,
+                ResultMessage::Line { .. } | ResultMessage::Match { .. }
+            )
+        })
+        .count()
+}
+
+pub fn search_files(files: &[PathBuf], options: &SearchOptions) -> mpsc::Receiver<FileMatchResult> {
     let (tx, rx) = mpsc::channel();
-    let highlighter = TextHighlighter::new(pattern, color);
-    let is_single_file = files.len() == 1;
-
-    // Single-file optimization: bypass thread pool overhead for single files
-    if is_single_file {
-        let file = &files[0];
-        let reader = FileReader::select(file, true);
-
-        let messages = match _process_file(file, pattern, &highlighter, show_stats, reader) {
-            Ok(msg) => msg,
-            Err(e) => {
-                let err_msg = format!("Error processing file {}: {}", file.display(), e);
-                vec![ResultMessage::Error(err_msg)]
+    let highlighter = TextHighlighter::from_options(options);
+    let progress = ProgressReporter::new(options, files.len());
+    let limit = MatchLimit::new(options);
+    let settings = LineSettings::new(options).with_stats(
+        options.show_stats || options.summary_json || options.verbose,
+        options,
+    );
+    // Per-file counts travel in the stats message
+    let show_stats = options.show_stats
+        || options.count_matches
+        || options.total
+        || options.summary_json
+        || options.verbose;
+
+    // Files over --max-filesize are reported as skipped without being opened
+    let (files, oversized) = split_by_size(files, options.max_filesize);
+    for file in oversized {
+        let reason = format!(
+            "larger than --max-filesize ({} bytes)",
+            options.max_filesize.unwrap_or_default()
+        );
+        tx.send(vec![ResultMessage::SkippedFile { path: file, reason }])
+            .ok();
+        progress.file_done();
+    }
+    let files = &files[..];
+
+    // Small-batch optimization: bypass thread pool overhead for a handful of files
+    if files.len() <= options.sequential_threshold {
+        for file in files {
+            let Some(settings) = settings.within(&limit) else {
+                progress.file_done();
+                continue;
+            };
+            if out_of_time(options) {
+                progress.file_done();
+                continue;
             }
-        };
+            let reader = FileReader::select(file, true, options);
+
+            let messages = match _process_file(file, &highlighter, show_stats, settings, reader) {
+                Ok(msg) => msg,
+                Err(e) => {
+                    let err_msg = format!("Error processing file {}: {}", file.display(), e);
+                    vec![ResultMessage::Error(err_msg)]
+                }
+            };
+
+            limit.add(_matched_lines(&messages));
+            tx.send(messages).ok();
+            progress.file_done();
+        }
 
-        // Send result immediately for single file
-        tx.send(messages).ok();
+        progress.finish();
         return rx;
     }
 
@@ -224,13 +756,22 @@ pub fn search_files(
         for file in files {
             let _tx = tx.clone();
             let _highlighter = &highlighter;
-            let _pattern = pattern;
             let _file = file.clone();
+            let _progress = &progress;
+            let _limit = &limit;
 
             s.spawn(move |_| {
-                let reader = FileReader::select(&_file, false);
+                let Some(_settings) = settings.within(_limit) else {
+                    _progress.file_done();
+                    return;
+                };
+                if out_of_time(options) {
+                    _progress.file_done();
+                    return;
+                }
+                let reader = FileReader::select(&_file, false, options);
                 let messages =
-                    match _process_file(&_file, _pattern, _highlighter, show_stats, reader) {
+                    match _process_file(&_file, _highlighter, show_stats, _settings, reader) {
                         Ok(msg) => msg,
                         Err(e) => {
                             let err_msg =
@@ -238,10 +779,119 @@ pub fn search_files(
                             vec![ResultMessage::Error(err_msg)]
                         }
                     };
+                _limit.add(_matched_lines(&messages));
                 _tx.send(messages).ok();
+                _progress.file_done();
             });
         }
     });
+    progress.finish();
+
+    rx
+}
+
+/// A matching line yielded by [`search_iter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line_number: usize,
+    /// Unhighlighted line content
+    pub line: String,
+    /// Byte range of each match within `line`
+    pub spans: Vec<(usize, usize)>,
+}
+
+/// Lazily search files, yielding structured matches instead of messages
+///
+/// Files are searched one at a time on the calling thread, only when the
+/// iterator needs more matches, so dropping it early stops the search.
+/// Files that cannot be read or exceed `options.max_filesize` yield nothing.
+///
+/// ```no_run
+/// use xerg::options::SearchOptions;
+/// use xerg::search::default::search_iter;
+/// use std::path::PathBuf;
+///
+/// let files = vec![PathBuf::from("src/main.rs")];
+/// for m in search_iter(&files, &SearchOptions::new("use")) {
+///     println!("{}:{}: {}", m.path.display(), m.line_number, m.line);
+/// }
+/// ```
+pub fn search_iter<'a>(
+    files: &[PathBuf],
+    options: &'a SearchOptions,
+) -> impl Iterator<Item = Match> + use<'a> {
+    // Matches are yielded unhighlighted, so the color is never used
+    let highlighter = TextHighlighter::from_options(options);
+    let settings = LineSettings {
+        structured: true,
+        count_only: false,
+        ..LineSettings::new(options)
+    };
+    let (files, _oversized) = split_by_size(files, options.max_filesize);
+
+    files.into_iter().flat_map(move |file| {
+        let reader = FileReader::select(&file, true, options);
+        let messages =
+            _process_file(&file, &highlighter, false, settings, reader).unwrap_or_default();
+
+        messages.into_iter().filter_map(move |msg| match msg {
+            ResultMessage::Match {
+                index,
+                text,
+                submatches,
+                ..
+            } => Some(Match {
+                path: file.clone(),
+                line_number: index + 1,
+                line: text,
+                spans: submatches,
+            }),
+            _ => None,
+        })
+    })
+}
+
+/// Search lines read from standard input
+///
+/// Unlike file searches, each matching line is sent as soon as it is read so
+/// piped streams such as `tail -f` produce output immediately. Results are
+/// labelled with [`STDIN_LABEL`] in place of a file path.
+pub fn search_stdin(options: &SearchOptions) -> mpsc::Receiver<FileMatchResult> {
+    let (tx, rx) = mpsc::channel();
+    let highlighter = TextHighlighter::from_options(options);
+    let settings = LineSettings::new(options).with_stats(
+        options.show_stats || options.summary_json || options.verbose,
+        options,
+    );
+    let show_stats = options.show_stats
+        || options.count_matches
+        || options.total
+        || options.summary_json
+        || options.verbose;
+
+    std::thread::spawn(move || {
+        tx.send(vec![ResultMessage::Header(PathBuf::from(STDIN_LABEL))])
+            .ok();
+
+        let stdin = std::io::stdin();
+        let (lines, matched, skipped) =
+            _process_reader(stdin.lock(), &highlighter, settings, |msg| {
+                tx.send(vec![msg]).ok();
+            });
+
+        let mut summary = Vec::new();
+        if show_stats {
+            summary.push(ResultMessage::SearchStats {
+                lines,
+                matched,
+                skipped,
+            });
+        }
+        summary.push(ResultMessage::Done);
+        tx.send(summary).ok();
+    });
 
     rx
 }
@@ -249,6 +899,8 @@ pub fn search_files(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::output::colors::Color;
+    use crate::search::deadline::Deadline;
     use std::io::Write;
     use tempdir::TempDir;
 
@@ -270,7 +922,13 @@ mod tests {
 
         // Test that search_files completes without panicking
         // Results go to stdout, so we're testing the function doesn't crash
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -294,7 +952,13 @@ mod tests {
         let color = Color::Blue;
 
         // Test that function completes without panicking
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -311,7 +975,13 @@ mod tests {
         let color = Color::Green;
 
         // Should handle no matches gracefully
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -327,7 +997,13 @@ mod tests {
         let color = Color::Red;
 
         // Should handle empty files without errors
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -340,7 +1016,13 @@ mod tests {
         let color = Color::Red;
 
         // Should print error message to stderr and continue (not panic)
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -351,7 +1033,7 @@ mod tests {
         let mut file = File::create(&test_file).unwrap();
         writeln!(file, "Test pattern here").unwrap();
 
-        let files = vec![
+        let files = [
             test_file.clone(),
             test_file.clone(),
             test_file.clone(),
@@ -360,10 +1042,34 @@ mod tests {
         let pattern = "pattern";
 
         // Test all color variants
-        search_files(&vec![files[0].clone()], pattern, &Color::Red, false);
-        search_files(&vec![files[1].clone()], pattern, &Color::Green, false);
-        search_files(&vec![files[2].clone()], pattern, &Color::Blue, false);
-        search_files(&vec![files[3].clone()], pattern, &Color::Bold, false);
+        search_files(
+            &[files[0].clone()],
+            &SearchOptions {
+                color: Color::Red,
+                ..SearchOptions::new(pattern)
+            },
+        );
+        search_files(
+            &[files[1].clone()],
+            &SearchOptions {
+                color: Color::Green,
+                ..SearchOptions::new(pattern)
+            },
+        );
+        search_files(
+            &[files[2].clone()],
+            &SearchOptions {
+                color: Color::Blue,
+                ..SearchOptions::new(pattern)
+            },
+        );
+        search_files(
+            &[files[3].clone()],
+            &SearchOptions {
+                color: Color::Bold,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -381,7 +1087,13 @@ mod tests {
         let color = Color::Blue;
 
         // Should handle regex patterns (TextHighlighter uses regex internally)
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -399,7 +1111,13 @@ mod tests {
         let color = Color::Green;
 
         // Should handle Unicode and special characters
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -417,7 +1135,13 @@ mod tests {
         let color = Color::Red;
 
         // Should be case-sensitive by default
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -436,7 +1160,13 @@ mod tests {
         let color = Color::Blue;
 
         // Should handle very long lines without issues
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
     }
 
     #[test]
@@ -452,7 +1182,43 @@ mod tests {
         let color = Color::Red;
 
         // Should handle empty pattern gracefully (regex behavior)
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
+    }
+
+    #[test]
+    fn test_search_files_reports_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let temp_dir = TempDir::new("search_progress_test").unwrap();
+        let files: Vec<PathBuf> = (0..8)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file{}.txt", i));
+                let mut f = File::create(&path).unwrap();
+                writeln!(f, "progress line {}", i).unwrap();
+                path
+            })
+            .collect();
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&calls);
+        let options = SearchOptions::new("progress").on_progress(Box::new(move |done, total| {
+            sink.lock().unwrap().push((done, total));
+        }));
+
+        let rx = search_files(&files, &options);
+        assert_eq!(rx.iter().count(), files.len());
+
+        let calls = calls.lock().unwrap();
+        assert!(calls.len() >= 2);
+        assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(calls.iter().all(|&(_, total)| total == files.len()));
+        assert_eq!(calls.last(), Some(&(files.len(), files.len())));
     }
 
     #[test]
@@ -476,6 +1242,505 @@ mod tests {
         let color = Color::Green;
 
         // Should handle mixed scenarios: valid, empty, and missing files
-        search_files(&files, pattern, &color, false);
+        search_files(
+            &files,
+            &SearchOptions {
+                color,
+                ..SearchOptions::new(pattern)
+            },
+        );
+    }
+
+    #[test]
+    fn test_search_files_json_captures_submatches() {
+        let temp_dir = TempDir::new("search_json_test").unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        std::fs::write(
+            &test_file,
+            "skip
+ab cab
+",
+        )
+        .unwrap();
+
+        let options = SearchOptions {
+            json: true,
+            ..SearchOptions::new("ab")
+        };
+        let rx = search_files(&[test_file], &options);
+        let messages = rx.recv().unwrap();
+
+        let matches: Vec<_> = messages
+            .iter()
+            .filter_map(|msg| match msg {
+                ResultMessage::Match {
+                    index,
+                    text,
+                    submatches,
+                    ..
+                } => Some((*index, text.as_str(), submatches.clone())),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(matches, vec![(1, "ab cab", vec![(0, 2), (4, 6)])]);
+    }
+
+    #[test]
+    fn test_search_files_skips_oversized_files() {
+        let temp_dir = TempDir::new("search_max_filesize_test").unwrap();
+        let small = temp_dir.path().join("small.txt");
+        let large = temp_dir.path().join("large.txt");
+        std::fs::write(&small, "match\n").unwrap();
+        std::fs::write(&large, "match\n".repeat(100)).unwrap();
+
+        let options = SearchOptions {
+            max_filesize: Some(64),
+            show_stats: true,
+            ..SearchOptions::new("match")
+        };
+        let rx = search_files(&[small, large.clone()], &options);
+        let results: Vec<FileMatchResult> = rx.into_iter().collect();
+
+        let skipped: Vec<&PathBuf> = results
+            .iter()
+            .flatten()
+            .filter_map(|msg| match msg {
+                ResultMessage::SkippedFile { path, reason } => {
+                    assert_eq!(reason, "larger than --max-filesize (64 bytes)");
+                    Some(path)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(skipped, vec![&large]);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_search_files_stops_after_deadline() {
+        let temp_dir = TempDir::new("search_deadline_test").unwrap();
+        let files: Vec<PathBuf> = (0..6)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file{}.txt", i));
+                std::fs::write(&path, "match\n").unwrap();
+                path
+            })
+            .collect();
+
+        for threshold in [0, 8] {
+            let deadline = Deadline::default();
+            let options = SearchOptions {
+                deadline: Some(deadline.clone()),
+                sequential_threshold: threshold,
+                show_stats: true,
+                ..SearchOptions::new("match")
+            };
+            let rx = search_files(&files, &options);
+            assert_eq!(rx.into_iter().count(), 6);
+            assert!(!deadline.interrupted());
+
+            deadline.expire();
+            let rx = search_files(&files, &options);
+            assert_eq!(rx.into_iter().count(), 0);
+            assert!(deadline.interrupted());
+        }
+    }
+
+    #[test]
+    fn test_process_reader_emits_matches_from_any_reader() {
+        let input = std::io::Cursor::new("alpha\nbeta\nalphabet\n");
+        let highlighter = TextHighlighter::new("alpha", &Color::Red);
+        let mut messages = Vec::new();
+
+        let settings = LineSettings {
+            structured: true,
+            max_count: None,
+            count_past_limit: false,
+            count_only: false,
+            multiline: false,
+            timing: false,
+            lines: None,
+            before_context: 0,
+            after_context: 0,
+        };
+
+        let stats = _process_reader(input, &highlighter, settings, |msg| messages.push(msg));
+
+        assert_eq!(stats, (3, 2, 0));
+        let indexes: Vec<usize> = messages
+            .iter()
+            .filter_map(|msg| match msg {
+                ResultMessage::Match { index, .. } => Some(*index),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(indexes, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_process_reader_emits_context_around_matches() {
+        let input = std::io::Cursor::new("a\nb\nhit\nc\nd\ne\nhit\n");
+        let highlighter = TextHighlighter::new("hit", &Color::Red);
+        let options = SearchOptions {
+            before_context: 1,
+            after_context: 1,
+            ..SearchOptions::default()
+        };
+        let mut messages = Vec::new();
+
+        _process_reader(input, &highlighter, LineSettings::new(&options), |msg| {
+            messages.push(msg)
+        });
+        let shown: Vec<(usize, bool)> = messages
+            .iter()
+            .filter_map(|msg| match msg {
+                ResultMessage::Context { index, .. } => Some((*index, false)),
+                ResultMessage::Line { index, .. } | ResultMessage::Match { index, .. } => {
+                    Some((*index, true))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            vec![(1, false), (2, true), (3, false), (5, false), (6, true)]
+        );
+    }
+
+    #[test]
+    fn test_multiline_reports_spanned_lines_once() {
+        let input = std::io::Cursor::new("fn a(\n  b)\nfn c(\n)\nend\n");
+        let options = SearchOptions {
+            multiline: true,
+            json: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options(r"\(\n", &Color::Red, &options);
+        let mut messages = Vec::new();
+
+        let stats = _process_reader(input, &highlighter, LineSettings::new(&options), |msg| {
+            messages.push(msg)
+        });
+
+        assert_eq!(stats, (5, 2, 0));
+        let matches: Vec<_> = messages
+            .into_iter()
+            .filter_map(|msg| match msg {
+                ResultMessage::Match {
+                    index,
+                    text,
+                    submatches,
+                    ..
+                } => Some((index, text, submatches)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            matches,
+            vec![
+                (0, "fn a(".to_string(), vec![(4, 5)]),
+                (2, "fn c(".to_string(), vec![(4, 5)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_lines_are_skipped_by_every_reader() {
+        let temp_dir = TempDir::new("invalid_utf8_test").unwrap();
+        let test_file = temp_dir.path().join("mixed.txt");
+        std::fs::write(&test_file, b"good match\n\xff\xfe bad match\nmatch again\n").unwrap();
+
+        let highlighter = TextHighlighter::new("match", &Color::Red);
+        let settings = LineSettings::new(&SearchOptions::default());
+
+        for reader in [
+            FileReader::Streaming,
+            FileReader::BulkRead,
+            FileReader::MemoryMap,
+        ] {
+            let messages = _process_file(&test_file, &highlighter, true, settings, reader).unwrap();
+
+            let indexes: Vec<usize> = messages
+                .iter()
+                .filter_map(|msg| match msg {
+                    ResultMessage::Line { index, .. } => Some(*index),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(indexes, vec![0, 2], "reader {:?}", reader);
+            assert!(
+                messages.iter().any(|msg| matches!(
+                    msg,
+                    ResultMessage::SearchStats {
+                        lines: 2,
+                        matched: 2,
+                        skipped: 1
+                    }
+                )),
+                "reader {:?}",
+                reader
+            );
+        }
+    }
+
+    #[test]
+    fn test_byte_regexp_searches_invalid_utf8_lines() {
+        let input: &[u8] = b"good match\n\xff\xfe bad match\nno\n";
+        let options = SearchOptions {
+            byte_regexp: true,
+            column: true,
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("match", &Color::Red, &options);
+        let mut messages = Vec::new();
+
+        let stats = _process_reader(input, &highlighter, LineSettings::new(&options), |msg| {
+            messages.push(msg)
+        });
+
+        assert_eq!(stats, (3, 2, 0));
+        assert!(matches!(
+            &messages[1],
+            ResultMessage::Line { index: 1, column: Some(8), content, .. }
+                if content == "\u{fffd}\u{fffd} bad \x1b[31mmatch\x1b[0m"
+        ));
+
+        let settings = LineSettings {
+            structured: true,
+            ..LineSettings::new(&options)
+        };
+        let mut messages = Vec::new();
+        _process_reader(input, &highlighter, settings, |msg| messages.push(msg));
+        assert!(matches!(
+            &messages[1],
+            ResultMessage::Match { text, submatches, .. }
+                if &text[submatches[0].0..submatches[0].1] == "match"
+        ));
+    }
+
+    #[test]
+    fn test_search_iter_yields_structured_matches() {
+        let temp_dir = TempDir::new("search_iter_test").unwrap();
+        let first = temp_dir.path().join("first.txt");
+        let second = temp_dir.path().join("second.txt");
+        std::fs::write(&first, "foo bar foo\nnothing\n").unwrap();
+        std::fs::write(&second, "skip\nfood\n").unwrap();
+        let files = vec![
+            first.clone(),
+            temp_dir.path().join("missing.txt"),
+            second.clone(),
+        ];
+
+        let matches: Vec<Match> = search_iter(&files, &SearchOptions::new("foo")).collect();
+
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    path: first,
+                    line_number: 1,
+                    line: "foo bar foo".to_string(),
+                    spans: vec![(0, 3), (8, 11)],
+                },
+                Match {
+                    path: second,
+                    line_number: 2,
+                    line: "food".to_string(),
+                    spans: vec![(0, 3)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_count_stops_each_reader_early() {
+        let temp_dir = TempDir::new("max_count_test").unwrap();
+        let test_file = temp_dir.path().join("many.txt");
+        std::fs::write(&test_file, "hit\nmiss\nhit hit\nhit\nmiss\n").unwrap();
+
+        let options = SearchOptions {
+            max_count: Some(2),
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("hit", &Color::Red, &options);
+
+        for reader in [
+            FileReader::Streaming,
+            FileReader::BulkRead,
+            FileReader::MemoryMap,
+        ] {
+            let messages = _process_file(
+                &test_file,
+                &highlighter,
+                true,
+                LineSettings::new(&options),
+                reader,
+            )
+            .unwrap();
+
+            let lines = messages
+                .iter()
+                .filter(|msg| matches!(msg, ResultMessage::Line { .. }))
+                .count();
+            assert_eq!(lines, 2, "reader {:?}", reader);
+            assert!(messages.iter().any(|msg| matches!(
+                msg,
+                ResultMessage::SearchStats {
+                    lines: 3,
+                    matched: 3,
+                    skipped: 0
+                }
+            )));
+        }
+    }
+
+    #[test]
+    fn test_max_count_with_stats_counts_the_whole_file() {
+        let temp_dir = TempDir::new("max_count_test").unwrap();
+        let test_file = temp_dir.path().join("many.txt");
+        std::fs::write(&test_file, "hit\nmiss\nhit hit\nhit\nmiss\n").unwrap();
+
+        for (stops_scan, expected) in [(false, (5, 4)), (true, (3, 3))] {
+            let options = SearchOptions {
+                max_count: Some(2),
+                max_count_stops_scan: stops_scan,
+                ..SearchOptions::default()
+            };
+            let highlighter = TextHighlighter::with_options("hit", &Color::Red, &options);
+            let settings = LineSettings::new(&options).with_stats(true, &options);
+
+            for reader in [FileReader::Streaming, FileReader::BulkRead] {
+                let messages =
+                    _process_file(&test_file, &highlighter, true, settings, reader).unwrap();
+
+                assert_eq!(_matched_lines(&messages), 2, "reader {:?}", reader);
+                let stats = messages.iter().find_map(|msg| match msg {
+                    ResultMessage::SearchStats { lines, matched, .. } => Some((*lines, *matched)),
+                    _ => None,
+                });
+                assert_eq!(stats, Some(expected), "reader {:?}", reader);
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_range_limits_every_reader() {
+        let temp_dir = TempDir::new("line_range_test").unwrap();
+        let test_file = temp_dir.path().join("range.txt");
+        std::fs::write(&test_file, "hit 1\nhit 2\nhit 3\nhit 4\nhit 5\n").unwrap();
+
+        let options = SearchOptions {
+            lines: Some(LineRange::parse("2:3").unwrap()),
+            ..SearchOptions::default()
+        };
+        let highlighter = TextHighlighter::with_options("hit", &Color::Red, &options);
+
+        for reader in [
+            FileReader::Streaming,
+            FileReader::BulkRead,
+            FileReader::MemoryMap,
+        ] {
+            let messages = _process_file(
+                &test_file,
+                &highlighter,
+                true,
+                LineSettings::new(&options),
+                reader,
+            )
+            .unwrap();
+
+            let indexes: Vec<usize> = messages
+                .iter()
+                .filter_map(|msg| match msg {
+                    ResultMessage::Line { index, .. } => Some(*index),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(indexes, vec![1, 2], "reader {:?}", reader);
+            assert!(messages.iter().any(|msg| matches!(
+                msg,
+                ResultMessage::SearchStats {
+                    lines: 2,
+                    matched: 2,
+                    skipped: 0
+                }
+            )));
+        }
+    }
+
+    #[test]
+    fn test_line_counts_ignore_trailing_newline_in_every_reader() {
+        let temp_dir = TempDir::new("line_count_test").unwrap();
+        let highlighter = TextHighlighter::new("match", &Color::Red);
+        let multiline = LineSettings::new(&SearchOptions {
+            multiline: true,
+            ..SearchOptions::default()
+        });
+
+        for (name, content) in [
+            ("trailing.txt", "one match\ntwo\n\nlast match\n"),
+            ("no_trailing.txt", "one match\ntwo\n\nlast match"),
+            ("crlf.txt", "one match\r\ntwo\r\n\r\nlast match"),
+        ] {
+            let test_file = temp_dir.path().join(name);
+            std::fs::write(&test_file, content).unwrap();
+
+            for (reader, settings) in [
+                FileReader::Streaming,
+                FileReader::BulkRead,
+                FileReader::MemoryMap,
+                FileReader::Decode(encoding_rs::UTF_8),
+            ]
+            .into_iter()
+            .flat_map(|reader| {
+                [
+                    (reader, LineSettings::new(&SearchOptions::default())),
+                    (reader, multiline),
+                ]
+            }) {
+                let messages =
+                    _process_file(&test_file, &highlighter, true, settings, reader).unwrap();
+
+                assert!(
+                    messages.iter().any(|msg| matches!(
+                        msg,
+                        ResultMessage::SearchStats {
+                            lines: 4,
+                            matched: 2,
+                            skipped: 0
+                        }
+                    )),
+                    "{} with reader {:?}",
+                    name,
+                    reader
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_files_reports_timing_only_when_enabled() {
+        let temp_dir = TempDir::new("search_timing_test").unwrap();
+        let file = temp_dir.path().join("timed.txt");
+        std::fs::write(&file, "match\n").unwrap();
+        let files = vec![file.clone()];
+
+        for timing in [false, true] {
+            let options = SearchOptions {
+                timing,
+                ..SearchOptions::new("match")
+            };
+            let rx = search_files(&files, &options);
+            let timed: Vec<PathBuf> = rx
+                .into_iter()
+                .flatten()
+                .filter_map(|msg| match msg {
+                    ResultMessage::Timing { path, millis } if millis >= 0.0 => Some(path),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(timed, if timing { vec![file.clone()] } else { vec![] });
+        }
     }
 }
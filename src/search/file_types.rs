@@ -0,0 +1,149 @@
+//! # File Types
+//!
+//! This module maps short language names to file extensions so searches can be
+//! restricted with `--type rust` instead of spelling out globs.
+//!
+//! ## Features
+//!
+//! - **Built-in Types**: A fixed table of common languages and formats
+//! - **Union of Types**: Repeated `--type` flags keep files of any listed type
+//! - **Exclusion**: `--type-not` drops files of the listed types
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use xerg::search::file_types::TypeFilter;
+//! use std::path::Path;
+//!
+//! let filter = TypeFilter::new(&["rust".to_string()], &[]).unwrap();
+//! assert!(filter.is_match(Path::new("src/main.rs")));
+//! ```
+
+use std::path::Path;
+
+/// Built-in type names and their extensions, sorted by name
+pub const FILE_TYPES: &[(&str, &[&str])] = &[
+    ("c", &["c", "h"]),
+    ("cpp", &["cpp", "cc", "cxx", "hpp", "hh", "hxx"]),
+    ("css", &["css"]),
+    ("go", &["go"]),
+    ("html", &["html", "htm"]),
+    ("java", &["java"]),
+    ("js", &["js", "jsx", "mjs"]),
+    ("json", &["json"]),
+    ("md", &["md", "markdown"]),
+    ("py", &["py", "pyi"]),
+    ("rust", &["rs"]),
+    ("sh", &["sh", "bash"]),
+    ("toml", &["toml"]),
+    ("ts", &["ts", "tsx"]),
+    ("yaml", &["yaml", "yml"]),
+];
+
+/// Look up the extensions registered for a type name
+pub fn extensions(name: &str) -> Option<&'static [&'static str]> {
+    FILE_TYPES
+        .iter()
+        .find(|(type_name, _)| *type_name == name)
+        .map(|(_, exts)| *exts)
+}
+
+/// Keeps files whose extension belongs to the selected types
+#[derive(Debug, Clone, Default)]
+pub struct TypeFilter {
+    include: Vec<&'static str>,
+    exclude: Vec<&'static str>,
+}
+
+impl TypeFilter {
+    /// Build a filter from `--type` and `--type-not` names
+    ///
+    /// Returns an error naming the first unknown type.
+    pub fn new(types: &[String], types_not: &[String]) -> Result<Self, String> {
+        let collect = |names: &[String]| -> Result<Vec<&'static str>, String> {
+            let mut exts = Vec::new();
+            for name in names {
+                match extensions(name) {
+                    Some(found) => exts.extend_from_slice(found),
+                    None => {
+                        return Err(format!("unknown file type '{}' (see --type-list)", name));
+                    }
+                }
+            }
+            Ok(exts)
+        };
+
+        Ok(Self {
+            include: collect(types)?,
+            exclude: collect(types_not)?,
+        })
+    }
+
+    /// Whether a path passes the type selection
+    pub fn is_match(&self, path: &Path) -> bool {
+        let ext = path.extension().and_then(|e| e.to_str());
+        let has_ext = |list: &[&str]| ext.is_some_and(|e| list.contains(&e));
+
+        (self.include.is_empty() || has_ext(&self.include)) && !has_ext(&self.exclude)
+    }
+}
+
+/// Format the built-in types as `name: ext, ext` lines for `--type-list`
+pub fn type_list() -> String {
+    FILE_TYPES
+        .iter()
+        .map(|(name, exts)| format!("{}: {}", name, exts.join(", ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extensions_lookup() {
+        assert_eq!(extensions("py"), Some(&["py", "pyi"][..]));
+        assert_eq!(extensions("cobol"), None);
+    }
+
+    #[test]
+    fn test_type_filter_union() {
+        let filter = TypeFilter::new(&names(&["rust", "c"]), &[]).unwrap();
+
+        assert!(filter.is_match(Path::new("src/main.rs")));
+        assert!(filter.is_match(Path::new("include/lib.h")));
+        assert!(!filter.is_match(Path::new("script.py")));
+        assert!(!filter.is_match(Path::new("Makefile")));
+    }
+
+    #[test]
+    fn test_type_filter_exclude() {
+        let filter = TypeFilter::new(&[], &names(&["md"])).unwrap();
+
+        assert!(filter.is_match(Path::new("main.rs")));
+        assert!(filter.is_match(Path::new("Makefile")));
+        assert!(!filter.is_match(Path::new("README.md")));
+    }
+
+    #[test]
+    fn test_type_filter_unknown_type() {
+        let err = TypeFilter::new(&names(&["cobol"]), &[]).unwrap_err();
+        assert!(err.contains("'cobol'"));
+    }
+
+    #[test]
+    fn test_type_list_is_sorted() {
+        let list = type_list();
+        let type_names: Vec<&str> = list.lines().map(|l| l.split(':').next().unwrap()).collect();
+        let mut sorted = type_names.clone();
+        sorted.sort();
+
+        assert_eq!(type_names, sorted);
+        assert!(list.contains("rust: rs"));
+    }
+}
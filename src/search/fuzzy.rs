@@ -0,0 +1,128 @@
+//! # Fuzzy Matching
+//!
+//! Approximate matching for `--fuzzy <N>`, which finds text within `N` edits
+//! of the pattern, such as `database` for `databse` with one edit.
+//!
+//! ## Features
+//!
+//! - **Edit Distance**: Insertions, deletions and substitutions each cost one edit (Levenshtein)
+//! - **Substring Search**: The pattern may match anywhere in a line, not only whole tokens
+//! - **Best Span**: Reports the closest match in a line, preferring the earliest and longest among equals
+//! - **Multiple Patterns**: Newline-separated patterns are tried one by one, like a regex alternation
+//!
+//! Patterns are taken literally; regex syntax has no meaning here. Each line
+//! holds at most one fuzzy match, the best one, which is what gets highlighted.
+//!
+//! ## Algorithm
+//!
+//! Sellers' variant of the Levenshtein table lets a match start at any
+//! position for free. Each cell also remembers where its alignment started,
+//! so the best span comes out of a single `O(pattern × line)` pass.
+
+use std::cmp::Reverse;
+
+/// Finds the closest approximate occurrence of one or more literal patterns
+#[derive(Debug, Clone)]
+pub struct FuzzyMatcher {
+    patterns: Vec<Vec<char>>,
+    max_edits: usize,
+}
+
+impl FuzzyMatcher {
+    /// Match any of the newline-separated `pattern`s within `max_edits` edits
+    pub fn new(pattern: &str, max_edits: usize) -> Self {
+        Self {
+            patterns: pattern.split('\n').map(|p| p.chars().collect()).collect(),
+            max_edits,
+        }
+    }
+
+    /// Whether some part of `text` is within the allowed edits of a pattern
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// Byte range of the best match in `text`, if any is close enough
+    ///
+    /// The match with the fewest edits wins; among equals, the one starting
+    /// first, then the longest, so `colour` rather than `colou` matches `color`.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let byte_at = |i: usize| chars.get(i).map_or(text.len(), |&(offset, _)| offset);
+
+        self.patterns
+            .iter()
+            .filter_map(|pattern| _best_alignment(pattern, &chars))
+            .filter(|&(edits, _, _)| edits <= self.max_edits)
+            .min_by_key(|&(edits, start, end)| (edits, start, Reverse(end)))
+            .map(|(_, start, end)| (byte_at(start), byte_at(end)))
+    }
+}
+
+/// Fewest edits turning `pattern` into a substring of `text`, with that substring's char range
+///
+/// Returns `(edits, start, end)`, or `None` for an empty pattern list entry
+/// that would otherwise match everywhere.
+fn _best_alignment(pattern: &[char], text: &[(usize, char)]) -> Option<(usize, usize, usize)> {
+    if pattern.is_empty() {
+        return None;
+    }
+
+    // Each cell holds (edits, start of the text substring) for the pattern
+    // prefix so far; the first row lets a match start at any text position
+    let mut row: Vec<(usize, usize)> = (0..=text.len()).map(|j| (0, j)).collect();
+    for (i, &p) in pattern.iter().enumerate() {
+        let mut next = Vec::with_capacity(row.len());
+        next.push((i + 1, 0));
+        for (j, &(_, t)) in text.iter().enumerate() {
+            let (diagonal_edits, diagonal_start) = row[j];
+            let substitute = (diagonal_edits + usize::from(p != t), diagonal_start);
+            let delete = (row[j + 1].0 + 1, row[j + 1].1);
+            let insert = (next[j].0 + 1, next[j].1);
+            // Fewest edits first, then the later start for the tightest span
+            let best = [substitute, delete, insert]
+                .into_iter()
+                .min_by_key(|&(edits, start)| (edits, Reverse(start)))
+                .unwrap();
+            next.push(best);
+        }
+        row = next;
+    }
+
+    row.iter()
+        .enumerate()
+        .map(|(end, &(edits, start))| (edits, start, end))
+        .min_by_key(|&(edits, start, end)| (edits, start, Reverse(end)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_text_within_allowed_edits() {
+        let matcher = FuzzyMatcher::new("databse", 1);
+        assert_eq!(matcher.find("open the database now"), Some((9, 17)));
+        assert!(matcher.is_match("my_database_name"));
+        assert!(!matcher.is_match("datum"));
+
+        let exact = FuzzyMatcher::new("databse", 0);
+        assert!(!exact.is_match("database"));
+        assert!(exact.is_match("databse"));
+    }
+
+    #[test]
+    fn test_prefers_the_closest_match() {
+        let matcher = FuzzyMatcher::new("color", 2);
+        // "colour" needs one edit, "cooler" two
+        assert_eq!(matcher.find("cooler colour"), Some((7, 13)));
+    }
+
+    #[test]
+    fn test_spans_are_byte_offsets_and_patterns_alternate() {
+        let matcher = FuzzyMatcher::new("héllo\nworld", 1);
+        assert_eq!(matcher.find("¡hello!"), Some((2, 7)));
+        assert_eq!(matcher.find("word up"), Some((0, 4)));
+        assert_eq!(FuzzyMatcher::new("", 3).find("anything"), None);
+    }
+}
@@ -0,0 +1,557 @@
+//! # In-Place Replacement
+//!
+//! Rewrites files with every match substituted by the `--replace` template,
+//! like `sed -i`, for `--in-place`.
+//!
+//! ## Features
+//!
+//! - **Atomic Writes**: New contents go to a temporary file next to the original, which is then renamed over it
+//! - **Untouched Files**: Files without matches are never written, keeping their timestamps
+//! - **Binary Safety**: Files containing NUL bytes or invalid UTF-8 are skipped
+//! - **No Memory Mapping**: Files are read whole, as mapping a file that is about to be replaced is unsafe
+//! - **Backups**: Optionally copies each file to its path plus a suffix before rewriting it (`--backup`)
+//! - **Dry Runs**: [`preview_files`] reports the lines a rewrite would change without writing anything (`--dry-run`)
+//! - **One Write per File**: Paths leading to the same file, such as a symlink and its target, rewrite it once
+//!
+//! Matching works line by line as in a search, so options such as `-w` and
+//! `--line-regexp` behave the same; under `--multiline` the pattern runs over
+//! whole files instead. Symlinks are followed, rewriting the file they point to.
+
+use super::deadline::out_of_time;
+use super::reader::split_by_size;
+use crate::options::SearchOptions;
+use crate::output::highlighter::TextHighlighter;
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Totals of an in-place run
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RewriteStats {
+    /// Files read and checked for matches
+    pub files: usize,
+    /// Files written back with replacements
+    pub modified: usize,
+    /// Matches replaced across all modified files
+    pub replacements: usize,
+    /// Binary files and files over `--max-filesize`, left alone
+    pub skipped: usize,
+    /// Files that could not be read or written
+    pub errors: usize,
+}
+
+impl RewriteStats {
+    fn add(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            modified: self.modified + other.modified,
+            replacements: self.replacements + other.replacements,
+            skipped: self.skipped + other.skipped,
+            errors: self.errors + other.errors,
+        }
+    }
+}
+
+impl fmt::Display for RewriteStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "result: files:{}; modified:{}; replacements:{}; skipped:{}; errors:{};",
+            self.files, self.modified, self.replacements, self.skipped, self.errors
+        )
+    }
+}
+
+impl From<&Outcome> for RewriteStats {
+    fn from(outcome: &Outcome) -> Self {
+        match *outcome {
+            Outcome::Unchanged => RewriteStats {
+                files: 1,
+                ..RewriteStats::default()
+            },
+            Outcome::Rewritten(replacements) => RewriteStats {
+                files: 1,
+                modified: 1,
+                replacements,
+                ..RewriteStats::default()
+            },
+            Outcome::Binary => RewriteStats {
+                skipped: 1,
+                ..RewriteStats::default()
+            },
+        }
+    }
+}
+
+/// What happened to a single file
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Unchanged,
+    Rewritten(usize),
+    Binary,
+}
+
+/// One line a replacement would change
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineChange {
+    /// 1-based line number in the original file
+    pub line_number: usize,
+    /// The line as it is now, without its line ending
+    pub before: String,
+    /// The line with the template applied; may span several lines if the template adds `\n`
+    pub after: String,
+}
+
+/// The changes a rewrite would make to one file, for `--dry-run`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePreview {
+    pub path: PathBuf,
+    /// Matches that would be replaced
+    pub replacements: usize,
+    /// Changed lines in file order
+    pub changes: Vec<LineChange>,
+}
+
+/// Replace every match of `options.pattern` with `template` in `files`, writing them back
+///
+/// The pattern honors the matching options in `options`, and `template` may
+/// refer to capture groups like `--replace`.
+pub fn rewrite_files(files: &[PathBuf], template: &str, options: &SearchOptions) -> RewriteStats {
+    let highlighter = TextHighlighter::from_options(options);
+    let (files, oversized) = split_by_size(files, options.max_filesize);
+    let (targets, unresolved) = _unique_targets(&files, "rewrite");
+
+    let stats = targets
+        .par_iter()
+        .map(|(_, target)| {
+            if out_of_time(options) {
+                return RewriteStats::default();
+            }
+
+            if let Some(progress) = &options.scan_progress {
+                progress.file_scanned();
+            }
+            match _rewrite_file(target, &highlighter.regex, template, options) {
+                Ok(outcome) => RewriteStats::from(&outcome),
+                Err(e) => {
+                    eprintln!("Error: failed to rewrite {}: {}", target.display(), e);
+                    RewriteStats {
+                        errors: 1,
+                        ..RewriteStats::default()
+                    }
+                }
+            }
+        })
+        .reduce(RewriteStats::default, RewriteStats::add);
+
+    RewriteStats {
+        skipped: stats.skipped + oversized.len(),
+        errors: stats.errors + unresolved,
+        ..stats
+    }
+}
+
+/// The files `files` lead to, each once under the first path naming it, and how many could not be resolved
+///
+/// A symlink given next to its target, or a path listed twice, would otherwise
+/// be rewritten twice in parallel, racing on one temporary file and applying
+/// the template twice. `action` names what failed for unresolved paths.
+fn _unique_targets<'a>(files: &'a [PathBuf], action: &str) -> (Vec<(&'a Path, PathBuf)>, usize) {
+    let mut seen = HashSet::new();
+    let mut unresolved = 0;
+    let targets = files
+        .iter()
+        .filter_map(|file| match fs::canonicalize(file) {
+            Ok(target) => seen
+                .insert(target.clone())
+                .then_some((file.as_path(), target)),
+            Err(e) => {
+                eprintln!("Error: failed to {} {}: {}", action, file.display(), e);
+                unresolved += 1;
+                None
+            }
+        })
+        .collect();
+
+    (targets, unresolved)
+}
+
+/// Find what [`rewrite_files`] would change in `files`, without writing anything
+///
+/// Files are resolved, deduplicated and replaced as a real run would, while
+/// the changed lines are collected in the same pass. The previews of files
+/// that would be modified come back in the order of `files`, along with the
+/// totals a real run would report.
+pub fn preview_files(
+    files: &[PathBuf],
+    template: &str,
+    options: &SearchOptions,
+) -> (RewriteStats, Vec<FilePreview>) {
+    let highlighter = TextHighlighter::from_options(options);
+    let (files, oversized) = split_by_size(files, options.max_filesize);
+    let (targets, unresolved) = _unique_targets(&files, "read");
+
+    let results: Vec<_> = targets
+        .par_iter()
+        .map(|(_, target)| {
+            if out_of_time(options) {
+                return None;
+            }
+
+            if let Some(progress) = &options.scan_progress {
+                progress.file_scanned();
+            }
+            Some(_preview_file(target, &highlighter.regex, template, options))
+        })
+        .collect();
+
+    let mut stats = RewriteStats {
+        skipped: oversized.len(),
+        errors: unresolved,
+        ..RewriteStats::default()
+    };
+    let mut previews = Vec::new();
+    for ((file, _), result) in targets.iter().zip(results) {
+        match result {
+            None => {}
+            Some(Ok((outcome, changes))) => {
+                stats = stats.add(RewriteStats::from(&outcome));
+                if let Outcome::Rewritten(replacements) = outcome {
+                    previews.push(FilePreview {
+                        path: file.to_path_buf(),
+                        replacements,
+                        changes,
+                    });
+                }
+            }
+            Some(Err(e)) => {
+                eprintln!("Error: failed to read {}: {}", file.display(), e);
+                stats.errors += 1;
+            }
+        }
+    }
+
+    (stats, previews)
+}
+
+/// The lines of the resolved file `target` that [`_rewrite_file`] would change
+fn _preview_file(
+    target: &Path,
+    regex: &Regex,
+    template: &str,
+    options: &SearchOptions,
+) -> io::Result<(Outcome, Vec<LineChange>)> {
+    let mut changes = Vec::new();
+    Ok(
+        match _replace_file(target, regex, template, options, Some(&mut changes))? {
+            Ok((_, replacements)) => (Outcome::Rewritten(replacements), changes),
+            Err(outcome) => (outcome, Vec::new()),
+        },
+    )
+}
+
+/// Rewrite the resolved file `target` if anything in it matches
+fn _rewrite_file(
+    target: &Path,
+    regex: &Regex,
+    template: &str,
+    options: &SearchOptions,
+) -> io::Result<Outcome> {
+    let (replaced, replacements) = match _replace_file(target, regex, template, options, None)? {
+        Ok(rewrite) => rewrite,
+        Err(outcome) => return Ok(outcome),
+    };
+
+    if let Some(suffix) = &options.backup_suffix {
+        fs::copy(target, _with_suffix(target, suffix))?;
+    }
+    _write_atomically(target, replaced.as_bytes())?;
+
+    Ok(Outcome::Rewritten(replacements))
+}
+
+/// The new text of the resolved file `target` and its match count, or the outcome to report if it stays as it is
+///
+/// Real and dry runs both decide through here. When `changes` is given, the
+/// lines that differ are collected during the same pass over the file.
+fn _replace_file(
+    target: &Path,
+    regex: &Regex,
+    template: &str,
+    options: &SearchOptions,
+    changes: Option<&mut Vec<LineChange>>,
+) -> io::Result<Result<(String, usize), Outcome>> {
+    let Some(content) = _read_text(target)? else {
+        return Ok(Err(Outcome::Binary));
+    };
+
+    let (replaced, replacements) = if options.multiline {
+        replace_content(&content, regex, template, true)
+    } else {
+        _replace_lines(&content, regex, template, changes)
+    };
+    if replacements == 0 || replaced == content {
+        return Ok(Err(Outcome::Unchanged));
+    }
+
+    Ok(Ok((replaced, replacements)))
+}
+
+/// Apply `template` to every match in `content`, returning the new text and the match count
+///
+/// Lines are rewritten one at a time with their `\n` or `\r\n` endings kept
+/// as they were, unless `multiline` runs the regex over the whole text.
+pub fn replace_content(
+    content: &str,
+    regex: &Regex,
+    template: &str,
+    multiline: bool,
+) -> (String, usize) {
+    if multiline {
+        let replacements = regex.find_iter(content).count();
+        return (
+            regex.replace_all(content, template).into_owned(),
+            replacements,
+        );
+    }
+
+    _replace_lines(content, regex, template, None)
+}
+
+/// The line-by-line half of [`replace_content`], pushing each line that differs onto `changes`
+///
+/// The match count covers every match, including ones the template replaces
+/// with the same text, while only lines that actually differ are listed.
+fn _replace_lines(
+    content: &str,
+    regex: &Regex,
+    template: &str,
+    mut changes: Option<&mut Vec<LineChange>>,
+) -> (String, usize) {
+    let mut replaced = String::with_capacity(content.len());
+    let mut replacements = 0;
+    for (index, raw) in content.split_inclusive('\n').enumerate() {
+        let line = raw.strip_suffix('\n').unwrap_or(raw);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        replacements += regex.find_iter(line).count();
+        let after = regex.replace_all(line, template);
+        if let Some(changes) = changes.as_deref_mut()
+            && after != line
+        {
+            changes.push(LineChange {
+                line_number: index + 1,
+                before: line.to_string(),
+                after: after.to_string(),
+            });
+        }
+        replaced.push_str(&after);
+        replaced.push_str(&raw[line.len()..]);
+    }
+
+    (replaced, replacements)
+}
+
+/// The contents of `path` as text, or `None` for binary files with NUL bytes or invalid UTF-8
+fn _read_text(path: &Path) -> io::Result<Option<String>> {
+    let bytes = fs::read(path)?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+
+    Ok(String::from_utf8(bytes).ok())
+}
+
+/// `path` with `suffix` appended to its file name, for `--backup`
+fn _with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Replace the contents of `path` through a temporary file in the same directory
+///
+/// The rename is atomic on the same filesystem, so readers see either the old
+/// or the new contents. The original permissions are kept.
+fn _write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.xerg-{}.tmp", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::set_permissions(&temp, fs::metadata(path)?.permissions())?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        fs::remove_file(&temp).ok();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_replace_content_keeps_line_endings() {
+        let regex = Regex::new("o(o?)").unwrap();
+
+        let (replaced, count) = replace_content("foo\r\nbar\nboo", &regex, "0$1", false);
+        assert_eq!(replaced, "f0o\r\nbar\nb0o");
+        assert_eq!(count, 2);
+
+        let regex = Regex::new("(?m)o$").unwrap();
+        let (replaced, count) = replace_content("fo\nbo\n", &regex, "x", true);
+        assert_eq!((replaced.as_str(), count), ("fx\nbx\n", 2));
+    }
+
+    #[test]
+    fn test_rewrite_files_only_touches_matching_text_files() {
+        let temp_dir = TempDir::new("in_place_test").unwrap();
+        let matching = temp_dir.path().join("matching.txt");
+        let other = temp_dir.path().join("other.txt");
+        let binary = temp_dir.path().join("binary.bin");
+        fs::write(&matching, "old value\nkeep\nold again\n").unwrap();
+        fs::write(&other, "nothing here\n").unwrap();
+        fs::write(&binary, b"old\0value").unwrap();
+
+        let options = SearchOptions {
+            backup_suffix: Some(".bak".to_string()),
+            ..SearchOptions::new("old")
+        };
+        let files = vec![matching.clone(), other.clone(), binary.clone()];
+        let stats = rewrite_files(&files, "new", &options);
+
+        assert_eq!(
+            stats,
+            RewriteStats {
+                files: 2,
+                modified: 1,
+                replacements: 2,
+                skipped: 1,
+                errors: 0
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(&matching).unwrap(),
+            "new value\nkeep\nnew again\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("matching.txt.bak")).unwrap(),
+            "old value\nkeep\nold again\n"
+        );
+        assert!(!temp_dir.path().join("other.txt.bak").exists());
+        assert_eq!(fs::read(&binary).unwrap(), b"old\0value");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 4);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rewrite_files_rewrites_each_target_once() {
+        let temp_dir = TempDir::new("in_place_test").unwrap();
+        let target = temp_dir.path().join("target.txt");
+        let link = temp_dir.path().join("link.txt");
+        fs::write(&target, "ab\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        // The template doubles each match, so a second rewrite would show
+        let files = vec![target.clone(), link.clone(), target.clone()];
+        let stats = rewrite_files(&files, "$0$0", &SearchOptions::new("a"));
+
+        assert_eq!(
+            stats,
+            RewriteStats {
+                files: 1,
+                modified: 1,
+                replacements: 1,
+                skipped: 0,
+                errors: 0
+            }
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "aab\n");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_preview_files_reports_changes_without_writing() {
+        let temp_dir = TempDir::new("in_place_test").unwrap();
+        let matching = temp_dir.path().join("matching.txt");
+        let same = temp_dir.path().join("same.txt");
+        fs::write(&matching, "old old\r\nkeep\nold again\n").unwrap();
+        fs::write(&same, "new\n").unwrap();
+
+        let files = vec![matching.clone(), same.clone()];
+        let (stats, previews) = preview_files(&files, "new", &SearchOptions::new("old|new"));
+
+        assert_eq!(
+            stats,
+            RewriteStats {
+                files: 2,
+                modified: 1,
+                replacements: 3,
+                skipped: 0,
+                errors: 0
+            }
+        );
+        assert_eq!(
+            previews,
+            vec![FilePreview {
+                path: matching.clone(),
+                replacements: 3,
+                changes: vec![
+                    LineChange {
+                        line_number: 1,
+                        before: "old old".to_string(),
+                        after: "new new".to_string(),
+                    },
+                    LineChange {
+                        line_number: 3,
+                        before: "old again".to_string(),
+                        after: "new again".to_string(),
+                    },
+                ],
+            }]
+        );
+        assert_eq!(
+            fs::read_to_string(&matching).unwrap(),
+            "old old\r\nkeep\nold again\n"
+        );
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preview_files_resolves_symlinks_like_rewrite() {
+        let temp_dir = TempDir::new("in_place_test").unwrap();
+        let target = temp_dir.path().join("target.txt");
+        let link = temp_dir.path().join("link.txt");
+        let dangling = temp_dir.path().join("dangling.txt");
+        fs::write(&target, "old\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("missing.txt"), &dangling).unwrap();
+
+        let files = vec![link.clone(), dangling.clone(), target.clone()];
+        let (stats, previews) = preview_files(&files, "new", &SearchOptions::new("old"));
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].path, link);
+        assert_eq!((stats.files, stats.modified, stats.errors), (1, 1, 1));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old\n");
+
+        let stats = rewrite_files(&files, "new", &SearchOptions::new("old"));
+        assert_eq!((stats.files, stats.modified, stats.errors), (1, 1, 1));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new\n");
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! # Global Match Limit
+//!
+//! Shared counter behind `--max-matches-total`, which stops a search once
+//! enough matching lines have been found across all files.
+//!
+//! Workers check the limit before opening a file and while reading it. Files
+//! searched in parallel can each find matches before seeing the others', so
+//! the cap is best-effort and may be slightly overshot.
+
+use crate::options::SearchOptions;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct MatchLimit {
+    max: Option<usize>,
+    found: AtomicUsize,
+}
+
+impl MatchLimit {
+    pub fn new(options: &SearchOptions) -> Self {
+        Self {
+            max: options.max_matches_total,
+            found: AtomicUsize::new(0),
+        }
+    }
+
+    /// Record `count` more matching lines
+    pub fn add(&self, count: usize) {
+        if self.max.is_some() {
+            self.found.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether enough matching lines were found to stop searching
+    pub fn reached(&self) -> bool {
+        self.remaining() == Some(0)
+    }
+
+    /// How many more matching lines may be found, or `None` without a limit
+    pub fn remaining(&self) -> Option<usize> {
+        self.max
+            .map(|max| max.saturating_sub(self.found.load(Ordering::Relaxed)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_counts_down_to_reached() {
+        let options = SearchOptions {
+            max_matches_total: Some(3),
+            ..SearchOptions::default()
+        };
+        let limit = MatchLimit::new(&options);
+
+        assert_eq!(limit.remaining(), Some(3));
+        limit.add(2);
+        assert!(!limit.reached());
+        limit.add(2);
+        assert_eq!(limit.remaining(), Some(0));
+        assert!(limit.reached());
+    }
+
+    #[test]
+    fn test_no_limit_is_never_reached() {
+        let limit = MatchLimit::new(&SearchOptions::default());
+
+        limit.add(100);
+        assert_eq!(limit.remaining(), None);
+        assert!(!limit.reached());
+    }
+}
@@ -0,0 +1,90 @@
+//! # Literal Matching
+//!
+//! Plain substring search for patterns without regex syntax, selected by
+//! `--engine` (`auto` by default).
+//!
+//! ## Features
+//!
+//! - **SIMD Search**: Finds the pattern with `memchr`'s `memmem`, skipping the regex engine entirely
+//! - **Automatic Detection**: [`is_literal`] spots patterns that mean the same as a regex and as plain text
+//! - **Regex-Compatible Spans**: Reports the same non-overlapping byte ranges the regex would find
+//!
+//! ## Example
+//!
+//! ```
+//! use xerg::search::literal::{LiteralMatcher, is_literal};
+//!
+//! assert!(is_literal("fn main"));
+//! assert!(!is_literal("fn .*"));
+//!
+//! let matcher = LiteralMatcher::new("ab");
+//! assert_eq!(matcher.find_iter("abcab").collect::<Vec<_>>(), vec![(0, 2), (3, 5)]);
+//! ```
+
+use memchr::memmem;
+
+/// Whether `pattern` matches the same text as a regex and as a plain string
+///
+/// Conservative: any character `regex::escape` would touch counts as syntax,
+/// and the empty pattern stays with the regex, which matches it at every position.
+pub fn is_literal(pattern: &str) -> bool {
+    !pattern.is_empty() && regex::escape(pattern) == pattern
+}
+
+/// Finds every occurrence of one literal string
+#[derive(Debug, Clone)]
+pub struct LiteralMatcher {
+    finder: memmem::Finder<'static>,
+}
+
+impl LiteralMatcher {
+    pub fn new(needle: &str) -> Self {
+        Self {
+            finder: memmem::Finder::new(needle.as_bytes()).into_owned(),
+        }
+    }
+
+    /// Whether `text` contains the needle
+    pub fn is_match(&self, text: &str) -> bool {
+        self.finder.find(text.as_bytes()).is_some()
+    }
+
+    /// Byte ranges of every non-overlapping occurrence in `text`, in order
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let len = self.finder.needle().len();
+        self.finder
+            .find_iter(text.as_bytes())
+            .map(move |start| (start, start + len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_literal_rejects_regex_syntax() {
+        assert!(is_literal("hello world"));
+        assert!(is_literal("héllo_42"));
+        assert!(!is_literal("a.b"));
+        assert!(!is_literal("foo|bar"));
+        assert!(!is_literal(r"\d"));
+        assert!(!is_literal(""));
+    }
+
+    #[test]
+    fn test_find_iter_matches_regex_spans() {
+        let matcher = LiteralMatcher::new("aa");
+        let regex = regex::Regex::new("aa").unwrap();
+        let text = "aaaaa baa é aa";
+
+        let spans: Vec<_> = matcher.find_iter(text).collect();
+        let expected: Vec<_> = regex
+            .find_iter(text)
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        assert_eq!(spans, expected);
+        assert!(matcher.is_match(text));
+        assert!(!matcher.is_match("a b a"));
+    }
+}
@@ -2,15 +2,33 @@
 //!
 //! This module contains all search-related functionality including:
 //! - File system crawling and traversal
+//! - Built-in file type filters by extension
 //! - File reading strategies with adaptive performance
 //! - Core search operations with pattern matching
+//! - Multiline matching over whole file contents
+//! - Context lines shown around matches
+//! - Approximate matching within an edit distance
+//! - Fast substring search for patterns without regex syntax
+//! - Throttled progress reporting for library embedders
+//! - A best-effort match limit shared by all workers
+//! - A deadline that stops searching new files once a timeout passes
+//! - In-place replacement that rewrites matching files
 //!
 //! The search module uses a three-tier file reading system:
 //! - Streaming for small files (<7MB)
 //! - Bulk reading for medium files (7MB-100MB)  
 //! - Memory mapping for large files (>100MB)
 
+pub mod context;
 pub mod crawler;
+pub mod deadline;
 pub mod default;
+pub mod file_types;
+pub mod fuzzy;
+pub mod in_place;
+pub mod limit;
+pub mod literal;
+pub mod multiline;
+pub mod progress;
 pub mod reader;
 pub mod xtreme;
@@ -0,0 +1,176 @@
+//! # Multiline Matching
+//!
+//! Support for `--multiline`, where the pattern runs over a whole file instead
+//! of one line at a time so a match may span line breaks.
+//!
+//! ## Features
+//!
+//! - **Whole-Line Blocks**: Each match is widened to the complete lines it touches
+//! - **Merged Overlaps**: Matches sharing a line are reported together, once
+//! - **Line Numbers**: The starting line is derived from the match's byte offset
+//! - **Match Spans**: Keeps each match's byte range, since matches relying on
+//!   the following line break cannot be found again in the block text alone
+//!
+//! ## Example
+//!
+//! ```
+//! use xerg::search::multiline::match_blocks;
+//! use regex::Regex;
+//!
+//! let regex = Regex::new(r"fn main\(\n\)").unwrap();
+//! let content = "use std::io;\nfn main(\n) {}\n";
+//! let block = match_blocks(content, &regex).next().unwrap();
+//!
+//! assert_eq!(block.line_index, 1);
+//! assert_eq!(block.text(content), "fn main(\n) {}");
+//! ```
+
+use regex::Regex;
+
+/// A run of whole lines covered by one or more matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchBlock {
+    /// 0-based index of the first line in the block
+    pub line_index: usize,
+    /// Byte offset where the first line starts
+    pub start: usize,
+    /// Byte offset of the newline ending the last line, or the end of the content
+    pub end: usize,
+    /// Byte range of each match relative to `start`, cut off at the block text's end
+    pub spans: Vec<(usize, usize)>,
+}
+
+impl MatchBlock {
+    /// The block's lines joined by `\n`, without the final line terminator
+    pub fn text<'a>(&self, content: &'a str) -> &'a str {
+        let text = &content[self.start..self.end];
+        text.strip_suffix('\r').unwrap_or(text)
+    }
+}
+
+/// Find every match of `regex` in `content` and group them into line blocks
+///
+/// A match that starts on a line already covered by the previous block is
+/// merged into it, so no line is reported twice. Blocks are yielded in order
+/// as the content is scanned.
+pub fn match_blocks<'a>(
+    content: &'a str,
+    regex: &'a Regex,
+) -> impl Iterator<Item = MatchBlock> + 'a {
+    let mut matches = regex.find_iter(content).peekable();
+    let mut line_index = 0;
+    let mut counted_to = 0;
+
+    std::iter::from_fn(move || {
+        let first = matches.next()?;
+        let start = content[..first.start()].rfind('\n').map_or(0, |i| i + 1);
+        let mut end = _line_end(content, first.start(), first.end());
+        let mut ranges = vec![first.range()];
+
+        while let Some(next) = matches.next_if(|m| m.start() <= end) {
+            end = end.max(_line_end(content, next.start(), next.end()));
+            ranges.push(next.range());
+        }
+
+        line_index += _count_newlines(&content[counted_to..start]);
+        counted_to = start;
+
+        let block_len = content[start..end]
+            .strip_suffix('\r')
+            .map_or(end - start, str::len);
+        let spans = ranges
+            .into_iter()
+            .map(|range| {
+                let clamp = |offset: usize| (offset - start).min(block_len);
+                (clamp(range.start), clamp(range.end))
+            })
+            .collect();
+
+        Some(MatchBlock {
+            line_index,
+            start,
+            end,
+            spans,
+        })
+    })
+}
+
+/// Offset of the newline ending the last line a match touches
+///
+/// A match ending in `\n` stays on the line that newline terminates.
+fn _line_end(content: &str, match_start: usize, match_end: usize) -> usize {
+    let last = if match_end > match_start {
+        match_end - 1
+    } else {
+        match_start
+    };
+
+    content[last..]
+        .find('\n')
+        .map_or(content.len(), |i| last + i)
+}
+
+fn _count_newlines(text: &str) -> usize {
+    text.bytes().filter(|&b| b == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(pattern: &str, content: &str) -> Vec<(usize, String, usize)> {
+        let regex = Regex::new(pattern).unwrap();
+        match_blocks(content, &regex)
+            .map(|block| {
+                (
+                    block.line_index,
+                    block.text(content).to_string(),
+                    block.spans.len(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_match_spanning_lines_covers_whole_lines() {
+        let content = "one\nfn call(\n  arg,\n) end\nlast\n";
+
+        assert_eq!(
+            blocks(r"call\(\s*arg,\s*\)", content),
+            vec![(1, "fn call(\n  arg,\n) end".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_matches_sharing_a_line_are_merged() {
+        let content = "a b\na c\nc\na\n";
+
+        assert_eq!(
+            blocks(r"b\na|a", content),
+            vec![(0, "a b\na c".to_string(), 2), (3, "a".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_trailing_newline_stays_on_its_line() {
+        let content = "foo\r\nbar\nfoo";
+
+        assert_eq!(
+            blocks(r"foo\r?\n", content),
+            vec![(0, "foo".to_string(), 1)]
+        );
+        assert_eq!(blocks(r"foo", content)[1], (2, "foo".to_string(), 1));
+    }
+
+    #[test]
+    fn test_spans_are_relative_and_cut_at_line_end() {
+        let regex = Regex::new(r"b\r?\n|c").unwrap();
+        let content = "a\nb\r\nc\n";
+        let blocks: Vec<MatchBlock> = match_blocks(content, &regex).collect();
+
+        assert_eq!(blocks[0].spans, vec![(0, 1)]);
+        assert_eq!((blocks[0].start, blocks[0].end), (2, 4));
+        assert_eq!(blocks[1].spans, vec![(0, 1)]);
+        assert_eq!(blocks[1].line_index, 2);
+    }
+}
@@ -0,0 +1,358 @@
+//! # Progress Reporting
+//!
+//! Tracks how many files a search has completed and forwards throttled updates
+//! to the callback registered with [`SearchOptions::on_progress`].
+//!
+//! Workers call [`ProgressReporter::file_done`] as each file finishes. Updates are
+//! serialized behind a mutex, so the callback is never invoked concurrently and
+//! always observes strictly increasing `files_done` counts. The final
+//! `(total, total)` update is delivered from the calling thread by
+//! [`ProgressReporter::finish`].
+//!
+//! The `--progress` status line works without a total: workers bump the
+//! [`ScanProgress`] counter in [`SearchOptions::scan_progress`] and a
+//! [`ProgressTicker`] thread redraws `Scanned N files...` from it, so files can
+//! still be searched while the directory walk runs. Results printed to the same
+//! terminal through a [`ProgressWriter`] clear the line first and keep it
+//! hidden until each of their lines is complete.
+//!
+//! [`SearchOptions::on_progress`]: crate::options::SearchOptions::on_progress
+//! [`SearchOptions::scan_progress`]: crate::options::SearchOptions::scan_progress
+
+use crate::options::{ProgressCallback, SearchOptions};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Minimum delay between two intermediate progress updates
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the `--progress` status line is redrawn
+pub const PROGRESS_TICK: Duration = Duration::from_millis(200);
+
+/// Clears the status line, leaving the cursor at its start
+const CLEAR_LINE: &str = "\r\x1b[K";
+
+/// Files scanned so far, and the `--progress` status line that shows them
+pub struct ScanProgress {
+    scanned: AtomicUsize,
+    display: Mutex<StatusDisplay>,
+}
+
+struct StatusDisplay {
+    out: Box<dyn Write + Send>,
+    /// The status is on screen and must be cleared before other output
+    shown: bool,
+    /// A result line is being written, so the status must not be drawn over it
+    paused: bool,
+}
+
+impl ScanProgress {
+    /// A counter whose status line is drawn on stderr
+    pub fn new() -> Self {
+        Self::with_output(Box::new(io::stderr()))
+    }
+
+    fn with_output(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            scanned: AtomicUsize::new(0),
+            display: Mutex::new(StatusDisplay {
+                out,
+                shown: false,
+                paused: false,
+            }),
+        }
+    }
+
+    /// Count one more file as scanned
+    pub fn file_scanned(&self) {
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Files counted so far
+    pub fn scanned(&self) -> usize {
+        self.scanned.load(Ordering::Relaxed)
+    }
+
+    /// Redraw the status with the current count, unless a result line is being written
+    fn draw(&self) {
+        let Ok(mut display) = self.display.lock() else {
+            return;
+        };
+        if display.paused {
+            return;
+        }
+
+        let status = format!("\rScanned {} files...", self.scanned());
+        display.out.write_all(status.as_bytes()).ok();
+        display.out.flush().ok();
+        display.shown = true;
+    }
+
+    /// Clear the status and keep it off screen until [`ScanProgress::resume`]
+    fn pause(&self) {
+        let Ok(mut display) = self.display.lock() else {
+            return;
+        };
+        if display.shown {
+            display.out.write_all(CLEAR_LINE.as_bytes()).ok();
+            display.out.flush().ok();
+            display.shown = false;
+        }
+        display.paused = true;
+    }
+
+    /// Let the next tick draw the status again
+    fn resume(&self) {
+        if let Ok(mut display) = self.display.lock() {
+            display.paused = false;
+        }
+    }
+}
+
+impl Default for ScanProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Thread redrawing a [`ScanProgress`] status every [`PROGRESS_TICK`]
+pub struct ProgressTicker {
+    stop: mpsc::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl ProgressTicker {
+    pub fn start(progress: Arc<ScanProgress>) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(PROGRESS_TICK) {
+                progress.draw();
+            }
+            progress.pause();
+        });
+
+        Self { stop, handle }
+    }
+
+    /// Stop redrawing and clear the status line, so later output starts clean
+    pub fn stop(self) {
+        self.stop.send(()).ok();
+        self.handle.join().ok();
+    }
+}
+
+/// Writer for results that share the terminal with the `--progress` status
+///
+/// The status is cleared before a line is written and not redrawn until the
+/// line is complete. Without a [`ScanProgress`] it writes straight through.
+pub struct ProgressWriter<'a, W: Write> {
+    inner: W,
+    progress: Option<&'a ScanProgress>,
+}
+
+impl<'a, W: Write> ProgressWriter<'a, W> {
+    pub fn new(inner: W, progress: Option<&'a ScanProgress>) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(progress) = self.progress else {
+            return self.inner.write(buf);
+        };
+
+        progress.pause();
+        let written = self.inner.write(buf)?;
+        // Partial lines keep the status hidden; it must not be drawn mid-line
+        if buf[..written].ends_with(b"\n") {
+            self.inner.flush()?;
+            progress.resume();
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct ReportState {
+    last_time: Option<Instant>,
+    last_done: usize,
+}
+
+pub struct ProgressReporter<'a> {
+    callback: Option<&'a ProgressCallback>,
+    scan_progress: Option<&'a ScanProgress>,
+    total: usize,
+    done: AtomicUsize,
+    state: Mutex<ReportState>,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(options: &'a SearchOptions, total: usize) -> Self {
+        Self {
+            callback: options.progress.as_ref(),
+            scan_progress: options.scan_progress.as_deref(),
+            total,
+            done: AtomicUsize::new(0),
+            state: Mutex::new(ReportState {
+                last_time: None,
+                last_done: 0,
+            }),
+        }
+    }
+
+    /// Record a completed file, reporting it if the throttle interval has passed
+    pub fn file_done(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        if let Some(progress) = self.scan_progress {
+            progress.file_scanned();
+        }
+
+        if self.callback.is_some() {
+            self.report(false);
+        }
+    }
+
+    /// Deliver the final update once every file has been processed
+    pub fn finish(&self) {
+        if self.callback.is_some() {
+            self.report(true);
+        }
+    }
+
+    fn report(&self, force: bool) {
+        let Some(callback) = self.callback else {
+            return;
+        };
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        // Read the counter while holding the lock so reports never go backwards
+        let done = self.done.load(Ordering::Relaxed);
+        let due = state
+            .last_time
+            .is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL);
+
+        if done > state.last_done && (force || due) {
+            callback(done, self.total);
+            state.last_time = Some(Instant::now());
+            state.last_done = done;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    type Calls = Arc<Mutex<Vec<(usize, usize)>>>;
+
+    fn recording_options() -> (SearchOptions, Calls) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&calls);
+        let options = SearchOptions::default().on_progress(Box::new(move |done, total| {
+            sink.lock().unwrap().push((done, total));
+        }));
+        (options, calls)
+    }
+
+    #[test]
+    fn test_reporter_throttles_intermediate_updates() {
+        let (options, calls) = recording_options();
+        let reporter = ProgressReporter::new(&options, 5);
+
+        for _ in 0..5 {
+            reporter.file_done();
+        }
+        reporter.finish();
+
+        // First file reports immediately, the rest fall inside the throttle window
+        assert_eq!(*calls.lock().unwrap(), vec![(1, 5), (5, 5)]);
+    }
+
+    /// Status output that the test can read back after handing it to a [`ScanProgress`]
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedOutput {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_progress_writer_keeps_status_off_result_lines() {
+        let status = SharedOutput::default();
+        let progress = ScanProgress::with_output(Box::new(status.clone()));
+        for _ in 0..3 {
+            progress.file_scanned();
+        }
+        progress.draw();
+
+        let mut out = ProgressWriter::new(Vec::new(), Some(&progress));
+        out.write_all(b"path:1: partial").unwrap();
+        // Not drawn while the result line is unfinished
+        progress.draw();
+        out.write_all(b" line\n").unwrap();
+        progress.draw();
+
+        assert_eq!(out.inner, b"path:1: partial line\n");
+        assert_eq!(
+            status.text(),
+            "\rScanned 3 files...\r\x1b[K\rScanned 3 files..."
+        );
+    }
+
+    #[test]
+    fn test_ticker_redraws_count_and_clears_on_stop() {
+        let status = SharedOutput::default();
+        let progress = Arc::new(ScanProgress::with_output(Box::new(status.clone())));
+        let options = SearchOptions {
+            scan_progress: Some(Arc::clone(&progress)),
+            ..SearchOptions::default()
+        };
+        let reporter = ProgressReporter::new(&options, 10);
+        reporter.file_done();
+        reporter.file_done();
+
+        let ticker = ProgressTicker::start(Arc::clone(&progress));
+        std::thread::sleep(PROGRESS_TICK * 2 + PROGRESS_TICK / 2);
+        ticker.stop();
+
+        let text = status.text();
+        assert!(text.starts_with("\rScanned 2 files..."), "{:?}", text);
+        assert!(text.ends_with(CLEAR_LINE), "{:?}", text);
+    }
+
+    #[test]
+    fn test_reporter_without_callback_is_silent() {
+        let options = SearchOptions::default();
+        let reporter = ProgressReporter::new(&options, 2);
+
+        reporter.file_done();
+        reporter.file_done();
+        reporter.finish();
+
+        assert_eq!(reporter.done.load(Ordering::Relaxed), 2);
+    }
+}
@@ -3,32 +3,498 @@
 //! Shared file reading approach selection logic for optimal performance
 //! across different file sizes and processing contexts.
 
-use std::path::PathBuf;
+use crate::options::SearchOptions;
+use encoding_rs::Encoding;
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufRead, ErrorKind};
+use std::path::{Path, PathBuf};
 
+/// Default for [`SearchOptions::bulk_read_threshold`]
 pub const BULK_READ_SIZE_THRESHOLD: u64 = 7_000_000;
+/// Default for [`SearchOptions::memory_map_threshold`]
 pub const MEMORY_MAP_SIZE_THRESHOLD: u64 = 100_000_000;
 
+/// Name shown in place of a file path when searching standard input
+pub const STDIN_LABEL: &str = "<stdin>";
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FileReader {
-    BulkRead,  // for files between 0B and 7MB
-    MemoryMap, // for files between 7MB and 100MB
-    Streaming, // for files larger than 100MB or multi-file contexts
+    Decode(&'static Encoding), // for `--encoding`, read whole and transcoded to UTF-8
+    BulkRead,                  // for files between 0B and 7MB
+    MemoryMap,                 // for files between 7MB and 100MB
+    Streaming,                 // for files larger than 100MB or multi-file contexts
+    Gzip,                      // for `.gz` files under `--search-zip`, streamed through a decoder
 }
 
 impl FileReader {
-    pub fn select(filepath: &PathBuf, is_single_file: bool) -> Self {
+    /// Pick a reading strategy
+    ///
+    /// `.gz` files are decompressed under `--search-zip`, every other file is
+    /// transcoded when `--encoding` is given, and `--byte-regexp` always streams;
+    /// otherwise the choice depends on size and the thresholds in `options`.
+    /// `--multiline` needs whole contents, so files that would be streamed are
+    /// memory mapped instead. `--mmap` memory maps every single file, while
+    /// `--no-mmap` reads or streams files that would otherwise be memory mapped.
+    /// Under `--debug` the choice is logged to stderr.
+    pub fn select(filepath: &PathBuf, is_single_file: bool, options: &SearchOptions) -> Self {
+        let (reader, reason) = Self::_select(filepath, is_single_file, options);
+        if options.debug {
+            eprintln!("debug: {}: {} ({})", filepath.display(), reader, reason);
+        }
+
+        reader
+    }
+
+    /// Pick a reading strategy along with why it was picked, for `--debug`
+    fn _select(
+        filepath: &PathBuf,
+        is_single_file: bool,
+        options: &SearchOptions,
+    ) -> (Self, SelectReason) {
+        if options.search_zip && is_gzip(filepath) {
+            return (FileReader::Gzip, SelectReason::SearchZip);
+        }
+
+        if let Some(encoding) = options.encoding {
+            return (FileReader::Decode(encoding), SelectReason::Encoding);
+        }
+
+        // Only the line reader keeps the raw bytes of lines that are not valid UTF-8
+        if options.byte_regexp && !options.multiline {
+            return (FileReader::Streaming, SelectReason::ByteRegexp);
+        }
+
+        if options.mmap == Some(true) && (is_single_file || options.multiline) {
+            return (FileReader::MemoryMap, SelectReason::Mmap(true));
+        }
+
+        // Multiline needs whole contents, which are read into memory without mmap
+        if options.multiline && options.mmap == Some(false) {
+            return (FileReader::BulkRead, SelectReason::Mmap(false));
+        }
+
+        let size = std::fs::metadata(filepath).map(|metadata| metadata.len());
+
+        if options.multiline {
+            return match size {
+                Ok(size) if size <= options.bulk_read_threshold => {
+                    (FileReader::BulkRead, SelectReason::Size(size))
+                }
+                Ok(size) => (FileReader::MemoryMap, SelectReason::Size(size)),
+                Err(_) => (FileReader::MemoryMap, SelectReason::UnknownSize),
+            };
+        }
+
         if !is_single_file {
-            return FileReader::Streaming;
+            return (FileReader::Streaming, SelectReason::MultipleFiles);
         }
 
-        const MEMORY_MAP_SIZE_THRESHOLD_MIN: u64 = 1 + BULK_READ_SIZE_THRESHOLD;
-        match std::fs::metadata(filepath) {
-            Ok(metadata) => match metadata.len() {
-                0..=BULK_READ_SIZE_THRESHOLD => FileReader::BulkRead,
-                MEMORY_MAP_SIZE_THRESHOLD_MIN..=MEMORY_MAP_SIZE_THRESHOLD => FileReader::MemoryMap,
-                _ => FileReader::Streaming,
-            },
-            Err(_) => FileReader::Streaming,
+        match size {
+            Ok(size) if size <= options.bulk_read_threshold => {
+                (FileReader::BulkRead, SelectReason::Size(size))
+            }
+            Ok(_) if options.mmap == Some(false) => {
+                (FileReader::Streaming, SelectReason::Mmap(false))
+            }
+            Ok(size) if size <= options.memory_map_threshold => {
+                (FileReader::MemoryMap, SelectReason::Size(size))
+            }
+            Ok(size) => (FileReader::Streaming, SelectReason::Size(size)),
+            Err(_) => (FileReader::Streaming, SelectReason::UnknownSize),
         }
     }
 }
+
+impl fmt::Display for FileReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileReader::Decode(encoding) => write!(f, "Decode({})", encoding.name()),
+            FileReader::BulkRead => write!(f, "BulkRead"),
+            FileReader::MemoryMap => write!(f, "MemoryMap"),
+            FileReader::Streaming => write!(f, "Streaming"),
+            FileReader::Gzip => write!(f, "Gzip"),
+        }
+    }
+}
+
+/// Why [`FileReader::select`] picked a strategy, as logged by `--debug`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SelectReason {
+    SearchZip,
+    Encoding,
+    ByteRegexp,
+    MultipleFiles,
+    /// `--mmap` or `--no-mmap` overrode the size thresholds
+    Mmap(bool),
+    /// The file size compared against the thresholds
+    Size(u64),
+    UnknownSize,
+}
+
+impl fmt::Display for SelectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectReason::SearchZip => write!(f, "gzip file under --search-zip"),
+            SelectReason::Encoding => write!(f, "--encoding"),
+            SelectReason::ByteRegexp => write!(f, "--byte-regexp"),
+            SelectReason::MultipleFiles => write!(f, "one of several files"),
+            SelectReason::Mmap(true) => write!(f, "--mmap"),
+            SelectReason::Mmap(false) => write!(f, "--no-mmap"),
+            SelectReason::Size(size) => write!(f, "{} bytes", size),
+            SelectReason::UnknownSize => write!(f, "size unknown"),
+        }
+    }
+}
+
+/// Whether a path has a `.gz` extension
+pub fn is_gzip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+/// Read a whole file and transcode it to UTF-8
+///
+/// A byte order mark takes precedence over `encoding` and is stripped. The flag
+/// reports whether any bytes were malformed and replaced with U+FFFD.
+pub fn read_decoded(
+    filepath: &Path,
+    encoding: &'static Encoding,
+) -> std::io::Result<(String, bool)> {
+    let bytes = std::fs::read(filepath)?;
+    let (text, _, had_errors) = encoding.decode(&bytes);
+
+    Ok((text.into_owned(), had_errors))
+}
+
+/// Decode bytes as UTF-8, replacing invalid sequences with U+FFFD
+///
+/// The flag reports whether anything was replaced, so callers can skip the
+/// affected lines instead of failing the whole file.
+pub fn decode_lossy(bytes: &[u8]) -> (Cow<'_, str>, bool) {
+    let content = String::from_utf8_lossy(bytes);
+    let lossy = matches!(content, Cow::Owned(_));
+
+    (content, lossy)
+}
+
+/// Decode a line like [`decode_lossy`], moving the byte ranges in `spans` along
+///
+/// Each replaced sequence grows to the three bytes of U+FFFD, so spans over
+/// the raw line would no longer line up with the decoded text.
+pub fn decode_lossy_spans(bytes: &[u8], spans: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut moved = Vec::with_capacity(spans.len());
+    let mut last = 0;
+    for &(start, end) in spans {
+        text.push_str(&String::from_utf8_lossy(&bytes[last..start]));
+        let moved_start = text.len();
+        text.push_str(&String::from_utf8_lossy(&bytes[start..end]));
+        moved.push((moved_start, text.len()));
+        last = end;
+    }
+    text.push_str(&String::from_utf8_lossy(&bytes[last..]));
+
+    (text, moved)
+}
+
+/// Iterate over the lines of `content` with the byte offset where each starts
+///
+/// Lines are split like [`str::lines`], dropping `\n` or `\r\n`, while the
+/// offsets still count the terminator bytes (`--byte-offset`). A final line
+/// break ends the last line without starting an empty one, so line counts
+/// agree with [`reader_lines`].
+pub fn content_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    content.split_inclusive('\n').map(move |raw| {
+        let start = offset;
+        offset += raw.len();
+        let line = raw.strip_suffix('\n').unwrap_or(raw);
+        (start, line.strip_suffix('\r').unwrap_or(line))
+    })
+}
+
+/// Line iterator over a reader that also reports each line's starting byte offset
+///
+/// Created by [`reader_lines`]. Lines that are not valid UTF-8 yield an
+/// `InvalidData` error without losing track of the offset.
+pub struct OffsetLines<R> {
+    reader: R,
+    offset: usize,
+}
+
+/// Iterate over the lines of `reader` like [`BufRead::lines`], with byte offsets
+pub fn reader_lines<R: BufRead>(reader: R) -> OffsetLines<R> {
+    OffsetLines { reader, offset: 0 }
+}
+
+impl<R> OffsetLines<R> {
+    /// Number of bytes consumed from the reader so far
+    pub fn bytes_read(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<R: BufRead> OffsetLines<R> {
+    /// Read the next line as raw bytes, without checking that it is UTF-8
+    pub fn next_bytes(&mut self) -> Option<io::Result<(usize, Vec<u8>)>> {
+        let mut buf = Vec::new();
+        match self.reader.read_until(b'\n', &mut buf) {
+            Ok(0) => None,
+            Ok(read) => {
+                let start = self.offset;
+                self.offset += read;
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+                Some(Ok((start, buf)))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for OffsetLines<R> {
+    type Item = io::Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_bytes().map(|line| {
+            line.and_then(|(start, buf)| {
+                String::from_utf8(buf)
+                    .map(|line| (start, line))
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+            })
+        })
+    }
+}
+
+/// Split files into those within `max_size` bytes and those exceeding it
+///
+/// Files whose metadata cannot be read are kept so the search reports the error.
+pub fn split_by_size(files: &[PathBuf], max_size: Option<u64>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    if max_size.is_none() {
+        return (files.to_vec(), Vec::new());
+    }
+
+    files
+        .iter()
+        .cloned()
+        .partition(|file| !exceeds_size(file, max_size))
+}
+
+/// Whether a file is known to be larger than `max_size` bytes
+pub fn exceeds_size(file: &Path, max_size: Option<u64>) -> bool {
+    max_size
+        .is_some_and(|limit| std::fs::metadata(file).is_ok_and(|metadata| metadata.len() > limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_split_by_size() {
+        let temp_dir = TempDir::new("reader_split_test").unwrap();
+        let small = temp_dir.path().join("small.txt");
+        let large = temp_dir.path().join("large.txt");
+        let missing = temp_dir.path().join("missing.txt");
+        std::fs::write(&small, "tiny").unwrap();
+        std::fs::write(&large, "x".repeat(100)).unwrap();
+
+        let files = vec![small.clone(), large.clone(), missing.clone()];
+
+        let (kept, skipped) = split_by_size(&files, Some(10));
+        assert_eq!(kept, vec![small, missing]);
+        assert_eq!(skipped, vec![large]);
+
+        let (kept, skipped) = split_by_size(&files, None);
+        assert_eq!(kept, files);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_select_gzip_only_with_search_zip() {
+        let temp_dir = TempDir::new("reader_gzip_test").unwrap();
+        let gz = temp_dir.path().join("app.log.gz");
+        std::fs::write(&gz, "not really compressed").unwrap();
+
+        let zip = SearchOptions {
+            search_zip: true,
+            ..SearchOptions::default()
+        };
+
+        assert_eq!(FileReader::select(&gz, true, &zip), FileReader::Gzip);
+        assert_eq!(FileReader::select(&gz, false, &zip), FileReader::Gzip);
+        assert_eq!(
+            FileReader::select(&gz, true, &SearchOptions::default()),
+            FileReader::BulkRead
+        );
+        assert!(!is_gzip(Path::new("archive.tgz")));
+    }
+
+    #[test]
+    fn test_select_honors_size_thresholds() {
+        let temp_dir = TempDir::new("reader_threshold_test").unwrap();
+        let file = temp_dir.path().join("ten.txt");
+        std::fs::write(&file, "0123456789").unwrap();
+
+        let thresholds = |bulk, mmap| SearchOptions {
+            bulk_read_threshold: bulk,
+            memory_map_threshold: mmap,
+            ..SearchOptions::default()
+        };
+
+        assert_eq!(
+            FileReader::select(&file, true, &thresholds(10, 20)),
+            FileReader::BulkRead
+        );
+        assert_eq!(
+            FileReader::select(&file, true, &thresholds(4, 10)),
+            FileReader::MemoryMap
+        );
+        assert_eq!(
+            FileReader::select(&file, true, &thresholds(4, 8)),
+            FileReader::Streaming
+        );
+        assert_eq!(
+            FileReader::select(&file, false, &thresholds(10, 20)),
+            FileReader::Streaming
+        );
+
+        let multiline = |bulk, mmap| SearchOptions {
+            multiline: true,
+            ..thresholds(bulk, mmap)
+        };
+        assert_eq!(
+            FileReader::select(&file, false, &multiline(10, 20)),
+            FileReader::BulkRead
+        );
+        assert_eq!(
+            FileReader::select(&file, true, &multiline(4, 8)),
+            FileReader::MemoryMap
+        );
+    }
+
+    #[test]
+    fn test_select_reports_reason_for_debug() {
+        let temp_dir = TempDir::new("reader_reason_test").unwrap();
+        let file = temp_dir.path().join("ten.txt");
+        std::fs::write(&file, "0123456789").unwrap();
+        let options = SearchOptions::default();
+
+        assert_eq!(
+            FileReader::_select(&file, true, &options),
+            (FileReader::BulkRead, SelectReason::Size(10))
+        );
+        assert_eq!(
+            FileReader::_select(&file, false, &options),
+            (FileReader::Streaming, SelectReason::MultipleFiles)
+        );
+        assert_eq!(
+            FileReader::_select(&temp_dir.path().join("missing.txt"), true, &options),
+            (FileReader::Streaming, SelectReason::UnknownSize)
+        );
+        assert_eq!(SelectReason::Size(10).to_string(), "10 bytes");
+        assert_eq!(
+            FileReader::Decode(encoding_rs::UTF_16LE).to_string(),
+            "Decode(UTF-16LE)"
+        );
+    }
+
+    #[test]
+    fn test_select_mmap_overrides_thresholds() {
+        let temp_dir = TempDir::new("reader_mmap_test").unwrap();
+        let file = temp_dir.path().join("ten.txt");
+        std::fs::write(&file, "0123456789").unwrap();
+
+        let mmap = |mmap, multiline| SearchOptions {
+            mmap: Some(mmap),
+            multiline,
+            bulk_read_threshold: 4,
+            memory_map_threshold: 20,
+            ..SearchOptions::default()
+        };
+
+        assert_eq!(
+            FileReader::select(&file, true, &mmap(true, false)),
+            FileReader::MemoryMap
+        );
+        assert_eq!(
+            FileReader::select(&file, false, &mmap(true, false)),
+            FileReader::Streaming
+        );
+        assert_eq!(
+            FileReader::select(&file, true, &mmap(false, false)),
+            FileReader::Streaming
+        );
+        assert_eq!(
+            FileReader::select(&file, false, &mmap(false, true)),
+            FileReader::BulkRead
+        );
+        assert_eq!(
+            FileReader::_select(&file, true, &mmap(true, true)),
+            (FileReader::MemoryMap, SelectReason::Mmap(true))
+        );
+    }
+
+    #[test]
+    fn test_content_lines_count_line_endings() {
+        let lines: Vec<_> = content_lines("one\r\ntwo\n\nlast").collect();
+        assert_eq!(lines, vec![(0, "one"), (5, "two"), (9, ""), (10, "last")]);
+    }
+
+    #[test]
+    fn test_reader_lines_match_content_lines() {
+        let input = b"one\r\nbad \xff\ntwo\n";
+        let lines: Vec<_> = reader_lines(&input[..])
+            .map(|line| line.map_err(|e| e.kind()))
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                Ok((0, "one".to_string())),
+                Err(ErrorKind::InvalidData),
+                Ok((11, "two".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_lossy_spans_follow_replacements() {
+        let (text, spans) = decode_lossy_spans(b"\xff key \xfe\xff key", &[(2, 5), (9, 12)]);
+        assert_eq!(text, "\u{fffd} key \u{fffd}\u{fffd} key");
+        assert_eq!(&text[spans[0].0..spans[0].1], "key");
+        assert_eq!(&text[spans[1].0..spans[1].1], "key");
+    }
+
+    #[test]
+    fn test_decode_lossy_flags_replacements() {
+        let (content, lossy) = decode_lossy(b"valid\n");
+        assert_eq!(content, "valid\n");
+        assert!(!lossy);
+
+        let (content, lossy) = decode_lossy(b"ok\nbad \xff\n");
+        assert_eq!(content, "ok\nbad \u{fffd}\n");
+        assert!(lossy);
+    }
+
+    #[test]
+    fn test_read_decoded_utf16_with_bom() {
+        let temp_dir = TempDir::new("reader_decode_test").unwrap();
+        let file = temp_dir.path().join("windows.txt");
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "caf\u{e9}\r\nnext".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        std::fs::write(&file, bytes).unwrap();
+
+        // The BOM wins over the requested encoding and is not part of the text
+        let (text, had_errors) = read_decoded(&file, encoding_rs::WINDOWS_1252).unwrap();
+        assert_eq!(text, "caf\u{e9}\r\nnext");
+        assert!(!had_errors);
+    }
+}
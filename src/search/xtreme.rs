@@ -10,197 +10,767 @@
 //! - **Immediate Printing**: Results printed as soon as found
 //! - **Shared Reader**: Uses same FileReader as default mode
 //! - **Statistics Compatible**: Works with `--stats` flag
+//! - **Headings**: Optionally prints each file's matches together under its path (`--heading`)
+//! - **Multiline**: Optionally matches across line breaks, printing each spanned line (`-U`)
+//! - **Any Writer**: [`search_files_to`], [`search_files_iter_to`] and [`search_stdin_to`] write to any `Write` instead of stdout
 //!
 //! ## Performance
 //!
 //! Xtreme mode eliminates messaging overhead by outputting matches immediately
 //! in the standard `grep` format. This provides maximum throughput for large
-//! codebases or when piping results to other tools.
+//! codebases or when piping results to other tools. The pattern is compiled
+//! into a single [`TextHighlighter`] per search and shared by reference with
+//! every worker task, so the regex is never rebuilt per file.
 
-use crate::output::{colors::Color, highlighter::TextHighlighter};
-use crate::search::reader::FileReader;
+use crate::options::{LineRange, SearchOptions};
+use crate::output::{
+    colors::Color,
+    highlighter::TextHighlighter,
+    result::{display_path, relative_path, with_match_count},
+};
+use crate::search::crawler::sort_files;
+use crate::search::deadline::Deadline;
+use crate::search::limit::MatchLimit;
+use crate::search::multiline::match_blocks;
+use crate::search::progress::ProgressReporter;
+use crate::search::reader::{
+    FileReader, STDIN_LABEL, content_lines, decode_lossy, exceeds_size, read_decoded, reader_lines,
+    split_by_size,
+};
+use flate2::read::GzDecoder;
 use memmap2::MmapOptions;
+use rayon::iter::{ParallelBridge, ParallelIterator};
 use rayon::scope;
+use std::fmt::Write as _;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Result};
+use std::io::{BufRead, BufReader, ErrorKind, Result, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-fn _print_match(filepath: &Path, line_number: usize, highlighted_content: &str) {
-    println!(
-        "{}:{}: {}",
-        filepath.display(),
-        line_number,
-        highlighted_content
-    );
+/// Totals of an xtreme search, returned by [`search_files`] and [`search_stdin`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct XtremeStats {
+    /// Files searched
+    pub files: usize,
+    /// Lines read, only counted with `show_stats`
+    pub lines: usize,
+    /// Match occurrences with `show_stats`, otherwise the number of matching lines
+    pub matches: usize,
+    /// Lines that were not valid UTF-8, plus files over `--max-filesize`
+    pub skipped: usize,
+    /// Files that could not be opened or read
+    pub errors: usize,
+    /// Bytes searched, counted after decompression or transcoding
+    pub bytes: u64,
+}
+
+impl XtremeStats {
+    fn add(&mut self, other: XtremeStats) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.matches += other.matches;
+        self.skipped += other.skipped;
+        self.errors += other.errors;
+        self.bytes += other.bytes;
+    }
+
+    /// Throughput in megabytes (10^6 bytes) searched per second
+    pub fn megabytes_per_sec(&self, elapsed_secs: f64) -> f64 {
+        if elapsed_secs > 0.0 {
+            self.bytes as f64 / 1_000_000.0 / elapsed_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Output settings and destination shared by every file of an xtreme search
+struct Printer<'w> {
+    /// Where matches go, stdout unless a `_to` function was called
+    out: Mutex<&'w mut (dyn Write + Send)>,
+    line_number: bool,
+    heading: bool,
+    /// Color the `--heading` path (`--decorations`)
+    decorate: bool,
+    /// Separate paths from what follows with a NUL byte (`--null`)
+    null: bool,
+    /// Shown between path components instead of the platform's (`--path-separator`)
+    path_separator: Option<char>,
+    /// Paths are shown relative to this directory when under it (`--relative`)
+    relative_to: Option<PathBuf>,
+    /// Start each line with the file path (`--with-filename`, `--no-filename`)
+    with_filename: bool,
+    /// Print `path:occurrences` once per file instead of each line (`--count-matches`)
+    count_matches: bool,
+    /// Search whole contents so matches may span lines (`--multiline`)
+    multiline: bool,
+    max_count: Option<usize>,
+    /// Only search these lines of each file (`--lines`)
+    lines: Option<LineRange>,
+    /// Matching lines found across all files, for `--max-matches-total`
+    limit: MatchLimit,
+    /// Stops files from being opened once `--timeout` passes
+    deadline: Option<Deadline>,
+    /// Set once the first `--heading` group is printed, so later groups get a separator
+    group_printed: AtomicBool,
+}
+
+impl<'w> Printer<'w> {
+    fn new(options: &SearchOptions, out: &'w mut (dyn Write + Send)) -> Self {
+        Self {
+            out: Mutex::new(out),
+            line_number: options.line_number,
+            heading: options.heading,
+            decorate: options.decorate,
+            null: options.null,
+            path_separator: options.path_separator,
+            relative_to: options.relative_to.clone(),
+            with_filename: options.with_filename,
+            count_matches: options.count_matches,
+            multiline: options.multiline,
+            max_count: options.max_count,
+            lines: options.lines,
+            limit: MatchLimit::new(options),
+            deadline: options.deadline.clone(),
+            group_printed: AtomicBool::new(false),
+        }
+    }
+
+    /// Write `bytes` to the output in one go, ignoring errors such as a closed pipe
+    fn write(&self, bytes: &[u8]) {
+        self.out.lock().unwrap().write_all(bytes).ok();
+    }
+
+    /// Format `path` for output with `--relative` and `--path-separator` applied
+    fn shown_path<'p>(&self, path: &'p Path) -> std::borrow::Cow<'p, str> {
+        display_path(
+            relative_path(path, self.relative_to.as_deref()),
+            self.path_separator,
+        )
+    }
+}
+
+/// Match output for one file, buffered and written in one go once the file is done
+///
+/// Lines are `path:line:col: content`, or grouped under the path with `--heading`.
+struct FileOutput<'a, 'w> {
+    printer: &'a Printer<'w>,
+    path: &'a Path,
+    buffer: String,
+    /// Write each match as soon as it is found, for unbounded standard input
+    immediate: bool,
+    matched_lines: usize,
+    /// Match occurrences seen so far, printed under `--count-matches`
+    occurrences: usize,
+}
+
+impl<'a, 'w> FileOutput<'a, 'w> {
+    fn new(printer: &'a Printer<'w>, path: &'a Path) -> Self {
+        Self {
+            printer,
+            path,
+            buffer: String::new(),
+            immediate: false,
+            matched_lines: 0,
+            occurrences: 0,
+        }
+    }
+
+    /// Like [`FileOutput::new`], but matches are written out line by line
+    fn immediate(printer: &'a Printer<'w>, path: &'a Path) -> Self {
+        Self {
+            immediate: true,
+            ..Self::new(printer, path)
+        }
+    }
+
+    /// Whether the file has printed as many matching lines as `--max-count` allows,
+    /// or the whole search as many as `--max-matches-total` does
+    fn limit_reached(&self) -> bool {
+        self.printer
+            .max_count
+            .is_some_and(|max| self.matched_lines >= max)
+            || self.printer.limit.reached()
+    }
+
+    /// Whether the 0-based line `index` is past the `--lines` range, so reading can stop
+    fn past_range(&self, index: usize) -> bool {
+        self.printer
+            .lines
+            .is_some_and(|range| range.is_past(index + 1))
+    }
+
+    /// Whether the 0-based line `index` is outside the `--lines` range
+    fn out_of_range(&self, index: usize) -> bool {
+        self.printer
+            .lines
+            .is_some_and(|range| !range.contains(index + 1))
+    }
+
+    /// Record a matching line under `--count-matches` without printing it
+    fn count_match(&mut self, occurrences: usize) {
+        self.matched_lines += 1;
+        self.printer.limit.add(1);
+        self.occurrences += occurrences;
+    }
+
+    /// Print a match, leaving out the line number, column or byte offset when disabled
+    ///
+    /// A `--multiline` match spans several lines; each following line is
+    /// printed with its own line number but no column or byte offset.
+    fn print_match(
+        &mut self,
+        line_number: usize,
+        column: Option<usize>,
+        byte_offset: Option<usize>,
+        content: &str,
+    ) {
+        self.matched_lines += 1;
+        self.printer.limit.add(1);
+        for (offset, line) in content.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if offset == 0 {
+                self.write_line(line_number, column, byte_offset, line);
+            } else {
+                self.write_line(line_number + offset, None, None, line);
+            }
+        }
+
+        if self.immediate && !self.printer.heading {
+            self.flush();
+        }
+    }
+
+    /// Format one output line into the buffer
+    fn write_line(
+        &mut self,
+        line_number: usize,
+        column: Option<usize>,
+        byte_offset: Option<usize>,
+        content: &str,
+    ) {
+        let line_number = self.printer.line_number.then_some(line_number);
+        let location = [line_number, column, byte_offset]
+            .into_iter()
+            .flatten()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        let buffer = &mut self.buffer;
+        if !self.printer.heading && self.printer.with_filename {
+            let path = self.printer.shown_path(self.path);
+            let _ = match (self.printer.null, location.is_empty()) {
+                (false, true) => writeln!(buffer, "{}: {}", path, content),
+                (false, false) => writeln!(buffer, "{}:{}: {}", path, location, content),
+                (true, true) => writeln!(buffer, "{}\0{}", path, content),
+                (true, false) => writeln!(buffer, "{}\0{}: {}", path, location, content),
+            };
+        } else if location.is_empty() {
+            let _ = writeln!(buffer, "{}", content);
+        } else {
+            let _ = writeln!(buffer, "{}: {}", location, content);
+        }
+    }
+
+    /// Write the buffered matches to the output in one go
+    fn flush(&mut self) {
+        self.printer.write(self.buffer.as_bytes());
+        self.buffer.clear();
+    }
+
+    /// Print whatever is still buffered, under the path for `--heading`
+    fn finish(mut self) {
+        if self.printer.count_matches && self.occurrences > 0 && !self.printer.with_filename {
+            let _ = writeln!(self.buffer, "{}", self.occurrences);
+            self.flush();
+            return;
+        }
+        if self.printer.count_matches && self.occurrences > 0 {
+            let separator = if self.printer.null { '\0' } else { ':' };
+            let _ = writeln!(
+                self.buffer,
+                "{}{}{}",
+                self.printer.shown_path(self.path),
+                separator,
+                self.occurrences
+            );
+            self.flush();
+            return;
+        }
+        if self.buffer.is_empty() {
+            return;
+        }
+        if !self.printer.heading || !self.printer.with_filename {
+            self.flush();
+            return;
+        }
+
+        let mut path = self.printer.shown_path(self.path).into_owned();
+        if self.printer.null {
+            path.push('\0');
+        }
+        let heading = if self.printer.decorate {
+            format!("\x1b[{}m{}\x1b[0m", Color::Bold.to_code(), path)
+        } else {
+            path
+        };
+
+        let mut out = self.printer.out.lock().unwrap();
+        if self.printer.group_printed.swap(true, Ordering::Relaxed) {
+            writeln!(out).ok();
+        }
+        write!(out, "{}\n{}", heading, self.buffer).ok();
+    }
 }
 
 /// Process a single line and print if it matches, returning match count
+///
+/// Occurrences are only counted under `--stats` or `--count-matches`; otherwise
+/// a matching line counts once so callers can still tell whether anything matched.
 fn _process_line(
-    filepath: &Path,
+    output: &mut FileOutput,
     line_index: usize,
+    line_offset: usize,
     line: &str,
     highlighter: &TextHighlighter,
     show_stats: bool,
 ) -> usize {
-    if highlighter.regex.is_match(line) {
+    if highlighter.is_match(line) {
+        if output.printer.count_matches {
+            let occurrences = highlighter.match_count(line);
+            output.count_match(occurrences);
+            return occurrences;
+        }
+
         let match_count = if show_stats {
-            highlighter.regex.find_iter(line).count()
+            highlighter.match_count(line)
         } else {
-            0
+            1
         };
 
         let highlighted = highlighter.highlight(line);
-        _print_match(filepath, line_index + 1, &highlighted);
+        let count = highlighter.line_match_count(line);
+        output.print_match(
+            line_index + 1,
+            highlighter.match_column(line),
+            highlighter.match_offset(line, line_offset),
+            &with_match_count(count, &highlighted, None),
+        );
         match_count
     } else {
         0
     }
 }
 
-/// Process a single file with immediate printing using the specified reader
-fn _process_file(
-    filepath: &Path,
+/// Process a line-oriented reader with immediate printing, returning its line and byte totals
+///
+/// Under `--multiline` the reader is drained first and searched as a whole.
+fn _process_reader<R: BufRead>(
+    output: &mut FileOutput,
+    mut reader: R,
     highlighter: &TextHighlighter,
     show_stats: bool,
-    reader: FileReader,
-) -> Result<(usize, usize, usize)> {
-    let skipped_lines = 0;
+) -> XtremeStats {
+    if output.printer.multiline {
+        let mut bytes = Vec::new();
+        if reader.read_to_end(&mut bytes).is_err() {
+            return XtremeStats {
+                skipped: 1,
+                ..XtremeStats::default()
+            };
+        }
+        let (content, lossy) = decode_lossy(&bytes);
+        return _process_content(output, &content, highlighter, show_stats, lossy);
+    }
 
-    let (lines_read, matches_found) = match reader {
-        FileReader::Streaming => {
-            let file = File::open(filepath)?;
-            let reader = BufReader::new(file);
-            let mut lines_read = 0;
-            let mut matches_found = 0;
+    let mut lines_read = 0;
+    let mut matches_found = 0;
+    let mut skipped_lines = 0;
+    let mut lines = reader_lines(reader);
+
+    for (line_index, line_result) in lines.by_ref().enumerate() {
+        if output.limit_reached() || output.past_range(line_index) {
+            break;
+        }
+        if output.out_of_range(line_index) {
+            continue;
+        }
 
-            for (line_index, line_result) in reader.lines().enumerate() {
+        match line_result {
+            Ok((offset, line)) => {
                 if show_stats {
                     lines_read += 1;
                 }
-
-                if let Ok(line) = line_result {
-                    matches_found +=
-                        _process_line(filepath, line_index, &line, highlighter, show_stats);
-                }
-                // Skip invalid UTF-8 lines silently
+                matches_found +=
+                    _process_line(output, line_index, offset, &line, highlighter, show_stats);
             }
+            // Lines that are not valid UTF-8 are skipped, as in the in-memory readers
+            Err(e) if e.kind() == ErrorKind::InvalidData => skipped_lines += 1,
+            // Other errors (e.g. a corrupt gzip stream) repeat forever, so stop reading
+            Err(_) => {
+                skipped_lines += 1;
+                break;
+            }
+        }
+    }
 
-            (lines_read, matches_found)
+    XtremeStats {
+        lines: lines_read,
+        matches: matches_found,
+        skipped: skipped_lines,
+        bytes: lines.bytes_read() as u64,
+        ..XtremeStats::default()
+    }
+}
+
+/// Process in-memory content with immediate printing, returning its line and byte totals
+///
+/// When `lossy` is set the content was decoded with replacements, so lines
+/// containing U+FFFD are skipped instead of searched.
+fn _process_content(
+    output: &mut FileOutput,
+    content: &str,
+    highlighter: &TextHighlighter,
+    show_stats: bool,
+    lossy: bool,
+) -> XtremeStats {
+    if output.printer.multiline {
+        return _process_content_multiline(output, content, highlighter, show_stats, lossy);
+    }
+
+    let mut lines_read = 0;
+    let mut matches_found = 0;
+    let mut skipped_lines = 0;
+
+    for (line_index, (offset, line)) in content_lines(content).enumerate() {
+        if output.limit_reached() || output.past_range(line_index) {
+            break;
+        }
+        if output.out_of_range(line_index) {
+            continue;
+        }
+        if lossy && line.contains(char::REPLACEMENT_CHARACTER) {
+            skipped_lines += 1;
+            continue;
+        }
+        if show_stats {
+            lines_read += 1;
         }
-        FileReader::BulkRead => {
-            let content = std::fs::read_to_string(filepath)?;
-            let mut lines_read = 0;
-            let mut matches_found = 0;
 
-            for (line_index, line) in content.lines().enumerate() {
-                if show_stats {
-                    lines_read += 1;
-                }
+        matches_found += _process_line(output, line_index, offset, line, highlighter, show_stats);
+    }
 
-                matches_found += _process_line(filepath, line_index, line, highlighter, show_stats);
-            }
+    XtremeStats {
+        lines: lines_read,
+        matches: matches_found,
+        skipped: skipped_lines,
+        bytes: content.len() as u64,
+        ..XtremeStats::default()
+    }
+}
+
+/// Process whole content for `--multiline`, printing each block of matching lines once
+///
+/// Blocks touching a line with U+FFFD are left out, as those lines are
+/// skipped in [`_process_content`].
+fn _process_content_multiline(
+    output: &mut FileOutput,
+    content: &str,
+    highlighter: &TextHighlighter,
+    show_stats: bool,
+    lossy: bool,
+) -> XtremeStats {
+    let is_lossy_line = |line: &str| lossy && line.contains(char::REPLACEMENT_CHARACTER);
+    let skipped_lines = content_lines(content)
+        .filter(|(_, line)| is_lossy_line(line))
+        .count();
+    let lines_read = if show_stats {
+        content_lines(content).count() - skipped_lines
+    } else {
+        0
+    };
+    let mut matches_found = 0;
 
-            (lines_read, matches_found)
+    for block in match_blocks(content, &highlighter.regex) {
+        if output.limit_reached() {
+            break;
+        }
+        let text = block.text(content);
+        if is_lossy_line(text) {
+            continue;
         }
-        FileReader::MemoryMap => {
-            let file = File::open(filepath)?;
-            let mmap = unsafe { MmapOptions::new().map(&file)? };
-            let content = std::str::from_utf8(&mmap)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-            let mut lines_read = 0;
-            let mut matches_found = 0;
 
-            for (line_index, line) in content.lines().enumerate() {
-                if show_stats {
-                    lines_read += 1;
-                }
+        let occurrences = block.spans.len();
+        if output.printer.count_matches {
+            output.count_match(occurrences);
+            matches_found += occurrences;
+            continue;
+        }
 
-                matches_found += _process_line(filepath, line_index, line, highlighter, show_stats);
-            }
+        let highlighted = highlighter.highlight_spans(text, &block.spans);
+        let count = highlighter.span_match_count(&block.spans);
+        output.print_match(
+            block.line_index + 1,
+            highlighter.span_column(text, &block.spans),
+            highlighter.span_offset(&block.spans, block.start),
+            &with_match_count(count, &highlighted, None),
+        );
+        matches_found += if show_stats { occurrences } else { 1 };
+    }
+
+    XtremeStats {
+        lines: lines_read,
+        matches: matches_found,
+        skipped: skipped_lines,
+        bytes: content.len() as u64,
+        ..XtremeStats::default()
+    }
+}
 
-            (lines_read, matches_found)
+/// Process a single file with immediate printing using the specified reader
+fn _process_file(
+    filepath: &Path,
+    highlighter: &TextHighlighter,
+    show_stats: bool,
+    reader: FileReader,
+    printer: &Printer,
+) -> Result<XtremeStats> {
+    let mut output = FileOutput::new(printer, filepath);
+
+    let stats = match reader {
+        FileReader::Streaming => {
+            let file = File::open(filepath)?;
+            _process_reader(&mut output, BufReader::new(file), highlighter, show_stats)
+        }
+        FileReader::Gzip => {
+            let file = File::open(filepath)?;
+            let decoder = BufReader::new(GzDecoder::new(file));
+            _process_reader(&mut output, decoder, highlighter, show_stats)
+        }
+        FileReader::Decode(encoding) => {
+            let (content, had_errors) = read_decoded(filepath, encoding)?;
+            _process_content(&mut output, &content, highlighter, show_stats, had_errors)
+        }
+        FileReader::BulkRead => {
+            let bytes = std::fs::read(filepath)?;
+            let (content, lossy) = decode_lossy(&bytes);
+            _process_content(&mut output, &content, highlighter, show_stats, lossy)
+        }
+        FileReader::MemoryMap => {
+            let file = File::open(filepath)?;
+            let mmap = unsafe { MmapOptions::new().map(&file)? };
+            let (content, lossy) = decode_lossy(&mmap);
+            _process_content(&mut output, &content, highlighter, show_stats, lossy)
         }
     };
 
-    Ok((lines_read, matches_found, skipped_lines))
+    output.finish();
+    Ok(XtremeStats { files: 1, ..stats })
+}
+
+/// The highlighter a search builds once and shares across all of its files
+#[cfg(not(test))]
+fn _highlighter(options: &SearchOptions) -> TextHighlighter {
+    TextHighlighter::from_options(options)
+}
+
+/// Like the real `_highlighter`, counting each build on the calling thread
+#[cfg(test)]
+fn _highlighter(options: &SearchOptions) -> TextHighlighter {
+    tests::BUILT.with(|built| built.set(built.get() + 1));
+    TextHighlighter::from_options(options)
 }
 
 /// Search files in xtreme mode with raw output for maximum speed
-pub fn search_files(
+///
+/// Without `options.show_stats` lines are not counted and `matches` holds the
+/// number of matching lines instead, see [`XtremeStats`].
+pub fn search_files(files: &[PathBuf], options: &SearchOptions) -> XtremeStats {
+    let mut stdout = std::io::stdout();
+    search_files_to(&mut stdout, files, options)
+}
+
+/// Like [`search_files`], but writes matches to `out` instead of stdout
+///
+/// Worker threads take turns writing whole buffers, so `out` sees the same
+/// bytes stdout would.
+pub fn search_files_to<W: Write + Send>(
+    out: &mut W,
     files: &[PathBuf],
-    pattern: &str,
-    color: &Color,
-    show_stats: bool,
-) -> (usize, usize, usize, usize) {
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    options: &SearchOptions,
+) -> XtremeStats {
+    let highlighter = _highlighter(options);
+    let printer = Printer::new(options, out);
+    let progress = ProgressReporter::new(options, files.len());
 
-    let highlighter = TextHighlighter::new(pattern, color);
-    let is_single_file = files.len() == 1;
+    // Files over --max-filesize are counted as skipped without being opened
+    let (files, oversized) = split_by_size(files, options.max_filesize);
+    for _ in &oversized {
+        progress.file_done();
+    }
+    let mut files = files;
+    if let Some(sort) = options.sort {
+        sort_files(&mut files, sort, options.sort_reverse);
+    }
+    let files = &files[..];
 
-    // Single-file optimization: bypass thread pool overhead
-    if is_single_file {
-        let file = &files[0];
-        let reader = FileReader::select(file, true);
+    // Small-batch optimization: bypass thread pool overhead for a handful of files.
+    // Sorted output is printed as it is found, so it also has to run in order.
+    if files.len() <= options.sequential_threshold || options.sort.is_some() {
+        let mut totals = XtremeStats {
+            skipped: oversized.len(),
+            ..XtremeStats::default()
+        };
 
-        match _process_file(file, &highlighter, show_stats, reader) {
-            Ok((lines, matches, skipped)) => {
-                return (1, lines, matches, skipped);
-            }
-            Err(err) => {
-                eprintln!("Error reading {}: {}", file.display(), err);
-                return (0, 0, 0, 0);
-            }
+        for file in files {
+            let reader = FileReader::select(file, true, options);
+            totals.add(_search_file(
+                file,
+                &highlighter,
+                options.show_stats,
+                reader,
+                &printer,
+            ));
+            progress.file_done();
         }
+
+        progress.finish();
+        return totals;
     }
 
     // Multi-file processing: use thread pool with streaming reader
-    let total_files = AtomicUsize::new(0);
-    let total_lines = AtomicUsize::new(0);
-    let total_matches = AtomicUsize::new(0);
-    let total_skipped = AtomicUsize::new(0);
+    let totals = Mutex::new(XtremeStats {
+        skipped: oversized.len(),
+        ..XtremeStats::default()
+    });
 
     scope(|s| {
         for file in files {
-            let _pattern = pattern;
             let _file = file.clone();
             let _highlighter = &highlighter;
-            let _total_files = &total_files;
-            let _total_lines = &total_lines;
-            let _total_matches = &total_matches;
-            let _total_skipped = &total_skipped;
+            let _totals = &totals;
+            let _progress = &progress;
+            let _printer = &printer;
 
             s.spawn(move |_| {
-                let reader = FileReader::select(&_file, false);
-                match _process_file(&_file, _highlighter, show_stats, reader) {
-                    Ok((lines, matches, skipped)) => {
-                        _total_files.fetch_add(1, Ordering::Relaxed);
-                        _total_lines.fetch_add(lines, Ordering::Relaxed);
-                        _total_matches.fetch_add(matches, Ordering::Relaxed);
-                        _total_skipped.fetch_add(skipped, Ordering::Relaxed);
-                    }
-                    Err(err) => {
-                        eprintln!("Error reading {}: {}", _file.display(), err);
-                    }
-                }
+                let reader = FileReader::select(&_file, false, options);
+                let stats =
+                    _search_file(&_file, _highlighter, options.show_stats, reader, _printer);
+                _totals.lock().unwrap().add(stats);
+                _progress.file_done();
             });
         }
     });
+    progress.finish();
+
+    totals.into_inner().unwrap()
+}
 
-    (
-        total_files.load(Ordering::Relaxed),
-        total_lines.load(Ordering::Relaxed),
-        total_matches.load(Ordering::Relaxed),
-        total_skipped.load(Ordering::Relaxed),
-    )
+/// Search files as they arrive from `files`, overlapping discovery with searching
+///
+/// Each path is handed to the thread pool as soon as the iterator yields it,
+/// so a slow directory walk no longer delays the first match. The file count
+/// is unknown upfront, so unlike [`search_files`] this never sorts results,
+/// calls the progress callback or picks a reader by file size; the
+/// `--progress` counter, which needs no total, is still kept up to date.
+pub fn search_files_iter<I>(files: I, options: &SearchOptions) -> XtremeStats
+where
+    I: Iterator<Item = PathBuf> + Send,
+{
+    let mut stdout = std::io::stdout();
+    search_files_iter_to(&mut stdout, files, options)
+}
+
+/// Like [`search_files_iter`], but writes matches to `out` instead of stdout
+pub fn search_files_iter_to<W, I>(out: &mut W, files: I, options: &SearchOptions) -> XtremeStats
+where
+    W: Write + Send,
+    I: Iterator<Item = PathBuf> + Send,
+{
+    let highlighter = _highlighter(options);
+    let printer = Printer::new(options, out);
+    let totals = Mutex::new(XtremeStats::default());
+
+    files.par_bridge().for_each(|file| {
+        let stats = if exceeds_size(&file, options.max_filesize) {
+            XtremeStats {
+                skipped: 1,
+                ..XtremeStats::default()
+            }
+        } else {
+            let reader = FileReader::select(&file, false, options);
+            _search_file(&file, &highlighter, options.show_stats, reader, &printer)
+        };
+        totals.lock().unwrap().add(stats);
+        if let Some(progress) = &options.scan_progress {
+            progress.file_scanned();
+        }
+    });
+
+    totals.into_inner().unwrap()
+}
+
+/// Search one file, reporting a failure on stderr and counting it as an error
+fn _search_file(
+    filepath: &Path,
+    highlighter: &TextHighlighter,
+    show_stats: bool,
+    reader: FileReader,
+    printer: &Printer,
+) -> XtremeStats {
+    // Files not yet started when --max-matches-total is reached or --timeout passes are skipped
+    if printer.limit.reached() || printer.deadline.as_ref().is_some_and(Deadline::skip_file) {
+        return XtremeStats::default();
+    }
+
+    match _process_file(filepath, highlighter, show_stats, reader, printer) {
+        Ok(stats) => stats,
+        Err(err) => {
+            eprintln!("Error reading {}: {}", filepath.display(), err);
+            XtremeStats {
+                errors: 1,
+                ..XtremeStats::default()
+            }
+        }
+    }
+}
+
+/// Search standard input in xtreme mode, printing matches as `<stdin>:line: content`
+///
+/// Returns the same totals as [`search_files`], with standard input counted
+/// as a single file.
+pub fn search_stdin(options: &SearchOptions) -> XtremeStats {
+    let mut stdout = std::io::stdout();
+    search_stdin_to(&mut stdout, options)
+}
+
+/// Like [`search_stdin`], but writes matches to `out` instead of stdout
+pub fn search_stdin_to<W: Write + Send>(out: &mut W, options: &SearchOptions) -> XtremeStats {
+    let highlighter = _highlighter(options);
+    let printer = Printer::new(options, out);
+    let mut output = FileOutput::immediate(&printer, Path::new(STDIN_LABEL));
+    let stdin = std::io::stdin();
+    let stats = _process_reader(&mut output, stdin.lock(), &highlighter, options.show_stats);
+    output.finish();
+
+    XtremeStats { files: 1, ..stats }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
     use std::fs::File;
     use std::io::Write;
     use tempdir::TempDir;
 
+    thread_local! {
+        /// Highlighters built by `_highlighter` on this thread
+        pub(super) static BUILT: Cell<usize> = const { Cell::new(0) };
+    }
+
     #[test]
     fn test_search_files_finds_pattern() {
         let temp_dir = TempDir::new("xtreme_test").unwrap();
@@ -212,14 +782,20 @@ mod tests {
         writeln!(file, "another line").unwrap();
 
         let files = vec![test_file.clone()];
-        let (files_processed, lines, matches, skipped) =
-            search_files(&files, "pattern", &Color::Blue, true);
+        let stats = search_files(
+            &files,
+            &SearchOptions {
+                color: Color::Blue,
+                show_stats: true,
+                ..SearchOptions::new("pattern")
+            },
+        );
 
         // Should have processed 1 file, 3 lines, 1 match, 0 skipped
-        assert_eq!(files_processed, 1);
-        assert_eq!(lines, 3);
-        assert_eq!(matches, 1);
-        assert_eq!(skipped, 0);
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.matches, 1);
+        assert_eq!(stats.skipped, 0);
     }
 
     #[test]
@@ -233,15 +809,132 @@ mod tests {
         writeln!(file, "match this too").unwrap();
 
         let files = vec![test_file.clone()];
-        let (files_processed, lines, matches, skipped) =
-            search_files(&files, "match", &Color::Blue, true);
+        let stats = search_files(
+            &files,
+            &SearchOptions {
+                color: Color::Blue,
+                show_stats: true,
+                ..SearchOptions::new("match")
+            },
+        );
 
         // Should have processed 1 file, 3 lines, 2 matches, 0 skipped
         // Note: stats are not printed in the new direct approach, just returned
-        assert_eq!(files_processed, 1);
-        assert_eq!(lines, 3);
-        assert_eq!(matches, 2);
-        assert_eq!(skipped, 0);
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.matches, 2);
+        assert_eq!(stats.skipped, 0);
+    }
+
+    #[test]
+    fn test_stats_unaffected_by_colons_and_spaces_in_names() {
+        let temp_dir = TempDir::new("xtreme_names_test").unwrap();
+        let odd = temp_dir.path().join("notes: lines:9, matches:9.txt");
+        let spaced = temp_dir.path().join("two words.txt");
+        std::fs::write(&odd, "match\nskip\nmatch match\n").unwrap();
+        std::fs::write(&spaced, "match\n").unwrap();
+
+        let files = vec![odd.clone(), spaced];
+        let mut out = Vec::new();
+        let stats = search_files_to(
+            &mut out,
+            &files,
+            &SearchOptions {
+                color: Color::Blue,
+                show_stats: true,
+                use_color: false,
+                ..SearchOptions::new("match")
+            },
+        );
+
+        assert_eq!((stats.files, stats.lines, stats.matches), (2, 4, 4));
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains(&format!("{}:3: match match\n", odd.display())));
+    }
+
+    #[test]
+    fn test_files_with_many_matches_are_not_interleaved() {
+        let temp_dir = TempDir::new("xtreme_interleave_test").unwrap();
+        // Each file prints well over 64KB of matches
+        let content = "match a reasonably long line of text\n".repeat(4000);
+        let files: Vec<PathBuf> = (0..8)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file{}.txt", i));
+                std::fs::write(&path, &content).unwrap();
+                path
+            })
+            .collect();
+
+        let mut out = Vec::new();
+        let options = SearchOptions {
+            use_color: false,
+            ..SearchOptions::new("match")
+        };
+        // Several workers even on a single core, so files really run side by side
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let stats = pool.install(|| search_files_to(&mut out, &files, &options));
+        assert_eq!(stats.matches, 8 * 4000);
+
+        let output = String::from_utf8(out).unwrap();
+        let mut paths: Vec<&str> = output
+            .lines()
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        paths.dedup();
+        assert_eq!(paths.len(), files.len(), "a file's lines were split up");
+    }
+
+    /// Records every write separately, to see how often the output lock is taken
+    struct WriteLog(Vec<Vec<u8>>);
+
+    impl Write for WriteLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_large_file_output_is_written_in_one_go() {
+        let temp_dir = TempDir::new("xtreme_interleave_test").unwrap();
+        // Well over 64KB of output per file
+        let content = "match a reasonably long line of text\n".repeat(4000);
+        let files: Vec<PathBuf> = (0..2)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file{}.txt", i));
+                std::fs::write(&path, &content).unwrap();
+                path
+            })
+            .collect();
+
+        let mut log = WriteLog(Vec::new());
+        let options = SearchOptions {
+            use_color: false,
+            ..SearchOptions::new("match")
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        pool.install(|| search_files_to(&mut log, &files, &options));
+
+        // One write per file: the lock is taken once, after the file is scanned,
+        // so neither worker waits on the other while reading
+        assert_eq!(log.0.len(), files.len());
+        for chunk in &log.0 {
+            let chunk = String::from_utf8_lossy(chunk);
+            assert!(chunk.len() > 64 * 1024);
+            let first = chunk.split(':').next().unwrap();
+            assert!(chunk.lines().all(|line| line.starts_with(first)));
+            assert_eq!(chunk.lines().count(), 4000);
+        }
     }
 
     #[test]
@@ -254,14 +947,20 @@ mod tests {
         writeln!(file, "another line").unwrap();
 
         let files = vec![test_file.clone()];
-        let (files_processed, lines, matches, skipped) =
-            search_files(&files, "pattern", &Color::Blue, true);
+        let stats = search_files(
+            &files,
+            &SearchOptions {
+                color: Color::Blue,
+                show_stats: true,
+                ..SearchOptions::new("pattern")
+            },
+        );
 
         // Should have processed 1 file, 2 lines, no matches, 0 skipped
-        assert_eq!(files_processed, 1);
-        assert_eq!(lines, 2);
-        assert_eq!(matches, 0);
-        assert_eq!(skipped, 0);
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.lines, 2);
+        assert_eq!(stats.matches, 0);
+        assert_eq!(stats.skipped, 0);
     }
 
     #[test]
@@ -277,24 +976,232 @@ mod tests {
         let files = vec![test_file.clone()];
 
         // Test email regex pattern
-        let (files_processed, lines, matches, skipped) =
-            search_files(&files, r"\w+@\w+\.\w+", &Color::Blue, true);
+        let stats = search_files(
+            &files,
+            &SearchOptions {
+                color: Color::Blue,
+                show_stats: true,
+                ..SearchOptions::new(r"\w+@\w+\.\w+")
+            },
+        );
 
         // Should have 2 matches (both email lines)
-        assert_eq!(files_processed, 1);
-        assert_eq!(lines, 3);
-        assert_eq!(matches, 2);
-        assert_eq!(skipped, 0);
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.matches, 2);
+        assert_eq!(stats.skipped, 0);
 
         // Test word boundary regex
         let files2 = vec![test_file];
-        let (files_processed2, lines2, matches2, skipped2) =
-            search_files(&files2, r"\bAdmin\b", &Color::Red, true);
+        let stats = search_files(
+            &files2,
+            &SearchOptions {
+                show_stats: true,
+                ..SearchOptions::new(r"\bAdmin\b")
+            },
+        );
 
         // Should match only the "Admin:" line, not "admin@test.org"
-        assert_eq!(files_processed2, 1);
-        assert_eq!(lines2, 3);
-        assert_eq!(matches2, 1);
-        assert_eq!(skipped2, 0);
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.matches, 1);
+        assert_eq!(stats.skipped, 0);
+    }
+
+    #[test]
+    fn test_sequential_batch_matches_pooled_totals() {
+        let temp_dir = TempDir::new("xtreme_batch_test").unwrap();
+        let files: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("batch{}.txt", i));
+                let mut file = File::create(&path).unwrap();
+                writeln!(file, "match {}", i).unwrap();
+                writeln!(file, "nothing here").unwrap();
+                path
+            })
+            .collect();
+
+        let sequential = SearchOptions {
+            sequential_threshold: 8,
+            show_stats: true,
+            ..SearchOptions::new("match")
+        };
+        let pooled = SearchOptions {
+            sequential_threshold: 0,
+            show_stats: true,
+            ..SearchOptions::new("match")
+        };
+
+        let sequential_totals = search_files(&files, &sequential);
+        let pooled_totals = search_files(&files, &pooled);
+
+        assert_eq!(
+            (
+                sequential_totals.files,
+                sequential_totals.lines,
+                sequential_totals.matches,
+                sequential_totals.skipped
+            ),
+            (3, 6, 3, 0)
+        );
+        assert_eq!(sequential_totals, pooled_totals);
+    }
+
+    #[test]
+    fn test_pattern_is_compiled_once_per_search() {
+        let temp_dir = TempDir::new("xtreme_compile_test").unwrap();
+        let files: Vec<PathBuf> = (0..12)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("compile{}.txt", i));
+                writeln!(File::create(&path).unwrap(), "compiled once").unwrap();
+                path
+            })
+            .collect();
+        // Counted per thread, so searches run by other tests are not counted
+        let built = || BUILT.with(Cell::get);
+
+        for threshold in [0, files.len()] {
+            let options = SearchOptions {
+                sequential_threshold: threshold,
+                show_stats: true,
+                ..SearchOptions::new("compiled (once)")
+            };
+            let before = built();
+            let totals = search_files_to(&mut Vec::new(), &files, &options);
+            assert_eq!(totals.matches, 12);
+            assert_eq!(built(), before + 1);
+
+            let mut out = Vec::new();
+            search_files_iter_to(&mut out, files.clone().into_iter(), &options);
+            assert_eq!(built(), before + 2);
+        }
+    }
+
+    #[test]
+    fn test_last_line_without_newline_matches_in_every_reader() {
+        let temp_dir = TempDir::new("xtreme_eof_test").unwrap();
+        let highlighter = TextHighlighter::new("match", &Color::Red);
+
+        // A trailing newline ends the last line rather than starting another
+        for content in ["first line\nlast match", "first line\nlast match\n"] {
+            let test_file = temp_dir.path().join("eof.txt");
+            std::fs::write(&test_file, content).unwrap();
+
+            for reader in [
+                FileReader::Streaming,
+                FileReader::BulkRead,
+                FileReader::MemoryMap,
+            ] {
+                let result = _process_file(
+                    &test_file,
+                    &highlighter,
+                    true,
+                    reader,
+                    &Printer::new(&SearchOptions::default(), &mut std::io::sink()),
+                )
+                .unwrap();
+                assert_eq!(
+                    (result.lines, result.matches, result.skipped),
+                    (2, 1, 0),
+                    "{:?} with reader {:?}",
+                    content,
+                    reader
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_files_iter_matches_search_files() {
+        let temp_dir = TempDir::new("xtreme_iter_test").unwrap();
+        let files: Vec<PathBuf> = (0..6)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("iter{}.txt", i));
+                std::fs::write(&path, "match\nother\n".repeat(i + 1)).unwrap();
+                path
+            })
+            .collect();
+
+        let options = SearchOptions {
+            max_filesize: Some(50),
+            show_stats: true,
+            ..SearchOptions::new("match")
+        };
+        let eager = search_files(&files, &options);
+        let lazy = search_files_iter(files.into_iter(), &options);
+
+        assert_eq!(lazy, eager);
+        assert_eq!((lazy.files, lazy.skipped), (4, 2));
+    }
+
+    #[test]
+    fn test_search_files_counts_missing_file_as_error() {
+        let temp_dir = TempDir::new("xtreme_error_test").unwrap();
+        let present = temp_dir.path().join("present.txt");
+        std::fs::write(&present, "match\n").unwrap();
+        let files = vec![present, temp_dir.path().join("missing.txt")];
+
+        for threshold in [0, 8] {
+            let options = SearchOptions {
+                sequential_threshold: threshold,
+                show_stats: true,
+                ..SearchOptions::new("match")
+            };
+            let stats = search_files(&files, &options);
+
+            assert_eq!(stats.errors, 1);
+            assert_eq!(stats.files, 1);
+            assert_eq!(stats.matches, 1);
+        }
+    }
+
+    #[test]
+    fn test_search_files_counts_bytes_for_throughput() {
+        let temp_dir = TempDir::new("xtreme_bytes_test").unwrap();
+        let files: Vec<PathBuf> = (0..2)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("bytes{}.txt", i));
+                std::fs::write(&path, "match\r\nother\n").unwrap();
+                path
+            })
+            .collect();
+
+        for threshold in [0, 8] {
+            let options = SearchOptions {
+                sequential_threshold: threshold,
+                show_stats: true,
+                ..SearchOptions::new("match")
+            };
+            let stats = search_files(&files, &options);
+            assert_eq!(stats.bytes, 26);
+        }
+
+        let stats = XtremeStats {
+            bytes: 3_000_000,
+            ..XtremeStats::default()
+        };
+        assert_eq!(stats.megabytes_per_sec(2.0), 1.5);
+        assert_eq!(stats.megabytes_per_sec(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_search_files_counts_oversized_files_as_skipped() {
+        let temp_dir = TempDir::new("xtreme_max_filesize_test").unwrap();
+        let small = temp_dir.path().join("small.txt");
+        let large = temp_dir.path().join("large.txt");
+        std::fs::write(&small, "match\n").unwrap();
+        std::fs::write(&large, "match\n".repeat(100)).unwrap();
+
+        let options = SearchOptions {
+            max_filesize: Some(64),
+            show_stats: true,
+            ..SearchOptions::new("match")
+        };
+        let totals = search_files(&[small, large], &options);
+
+        assert_eq!(
+            (totals.files, totals.lines, totals.matches, totals.skipped),
+            (1, 1, 1, 1)
+        );
     }
 }
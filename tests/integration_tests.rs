@@ -1,15 +1,23 @@
 use std::fs::{self, File};
 use std::io::Write;
-use std::process::Command;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
 use tempdir::TempDir;
 use xerg::output::colors::Color;
 use xerg::output::highlighter::TextHighlighter;
 
 /// Helper function to run xerg command and capture output
 fn run_xerg(args: &[&str]) -> (String, String, i32) {
+    run_xerg_with_env(args, &[])
+}
+
+/// Like `run_xerg`, with extra environment variables set for the process
+fn run_xerg_with_env(args: &[&str], env: &[(&str, &str)]) -> (String, String, i32) {
     let output = Command::new("cargo")
-        .args(&["run", "--quiet", "--"])
+        .args(["run", "--quiet", "--"])
         .args(args)
+        .envs(env.iter().copied())
         .output()
         .expect("Failed to execute xerg");
 
@@ -20,6 +28,32 @@ fn run_xerg(args: &[&str]) -> (String, String, i32) {
     (stdout, stderr, exit_code)
 }
 
+/// Helper function to run xerg with `input` piped into standard input
+fn run_xerg_with_stdin(args: &[&str], input: &str) -> (String, String, i32) {
+    let mut child = Command::new("cargo")
+        .args(["run", "--quiet", "--"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute xerg");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let exit_code = output.status.code().unwrap_or(-1);
+
+    (stdout, stderr, exit_code)
+}
+
 /// Helper function to create test files in a temporary directory
 fn create_test_files(temp_dir: &TempDir) -> std::path::PathBuf {
     let test_dir = temp_dir.path().join("test_files");
@@ -54,7 +88,8 @@ fn test_basic_search() {
     let temp_dir = TempDir::new("integration_test").unwrap();
     let test_dir = create_test_files(&temp_dir);
 
-    let (stdout, stderr, exit_code) = run_xerg(&["Hello", test_dir.to_str().unwrap()]);
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["Hello", test_dir.to_str().unwrap(), "--color", "always"]);
 
     assert_eq!(exit_code, 0);
     assert!(stderr.is_empty());
@@ -82,11 +117,10 @@ fn test_no_matches() {
 
     let (stdout, stderr, exit_code) = run_xerg(&["NonexistentPattern", test_dir.to_str().unwrap()]);
 
-    assert_eq!(exit_code, 0);
+    assert_eq!(exit_code, 1);
     assert!(stderr.is_empty());
-    // Should show file headers but no matches
-    assert!(stdout.contains("---"));
-    assert!(!stdout.contains("NonexistentPattern"));
+    // Files without matches print no header either
+    assert!(stdout.is_empty());
 }
 
 #[test]
@@ -95,7 +129,8 @@ fn test_single_file_search() {
     let test_dir = create_test_files(&temp_dir);
     let file_path = test_dir.join("file1.txt");
 
-    let (stdout, stderr, exit_code) = run_xerg(&["test", file_path.to_str().unwrap()]);
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["test", file_path.to_str().unwrap(), "--color", "always"]);
 
     assert_eq!(exit_code, 0);
     assert!(stderr.is_empty());
@@ -137,7 +172,11 @@ fn test_invalid_color_warning() {
     ]);
 
     assert_eq!(exit_code, 0);
-    assert!(stderr.contains("Warning: Invalid color name 'invalidcolor'"));
+    assert!(stderr.contains("Warning: invalid color 'invalidcolor'; valid colors are red,"));
+    assert!(stderr.contains("Defaulting to Red."));
+
+    let (_, stderr, _) = run_xerg(&["Hello", test_dir.to_str().unwrap(), "--color", "read"]);
+    assert!(stderr.contains("did you mean 'red'?"), "{}", stderr);
 
     // Should still highlight with default color (Red)
     let highlighter = TextHighlighter::new("Hello", &Color::Red);
@@ -149,7 +188,7 @@ fn test_invalid_color_warning() {
 fn test_nonexistent_directory() {
     let (stdout, stderr, exit_code) = run_xerg(&["pattern", "/nonexistent/directory"]);
 
-    assert_eq!(exit_code, 1);
+    assert_eq!(exit_code, 2);
     assert!(stderr.contains("error: file or directory does not exist"));
     assert!(stdout.is_empty());
 }
@@ -181,7 +220,8 @@ fn test_literal_patterns() {
     let test_dir = create_test_files(&temp_dir);
 
     // Test with a literal pattern that will match
-    let (stdout, stderr, exit_code) = run_xerg(&["fn main", test_dir.to_str().unwrap()]);
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["fn main", test_dir.to_str().unwrap(), "--color", "always"]);
 
     assert_eq!(exit_code, 0);
     assert!(stderr.is_empty());
@@ -201,11 +241,11 @@ fn test_case_sensitivity() {
     // Test lowercase search - should find no matches since we search for "hello" but files contain "Hello"
     let (stdout, stderr, exit_code) = run_xerg(&["hello", test_dir.to_str().unwrap()]);
 
-    assert_eq!(exit_code, 0);
+    assert_eq!(exit_code, 1);
     assert!(stderr.is_empty());
-    // Should not match "Hello" (case sensitive) - only file headers should be shown
-    assert!(stdout.contains("---")); // File headers are shown
-    assert!(!stdout.contains("Hello world")); // But no content matches
+    // Should not match "Hello" (case sensitive), so nothing is printed
+    assert!(!stdout.contains("---")); // No headers for files without matches
+    assert!(!stdout.contains("Hello world")); // And no content matches
 }
 
 #[test]
@@ -216,11 +256,25 @@ fn test_missing_pattern_error() {
     // Try to run with just a path (no pattern)
     let (stdout, stderr, exit_code) = run_xerg(&[test_dir.to_str().unwrap()]);
 
-    assert_eq!(exit_code, 1);
+    assert_eq!(exit_code, 2);
     assert!(stderr.contains("Pattern missing"));
     assert!(stdout.is_empty());
 }
 
+#[test]
+fn test_double_dash_marks_pattern_that_names_a_path() {
+    // `src` exists in the working directory, so alone it reads as a forgotten pattern
+    let (stdout, stderr, exit_code) = run_xerg(&["--max-depth", "1", "src"]);
+    assert_eq!(exit_code, 2);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("hint: to search for 'src' itself, put it after '--': xerg -- src"));
+
+    let (stdout, stderr, exit_code) = run_xerg(&["--max-depth", "1", "--", "src"]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("Cargo.toml"));
+}
+
 #[test]
 fn test_xtreme_mode() {
     let temp_dir = TempDir::new("integration_test").unwrap();
@@ -259,3 +313,1909 @@ fn test_xtreme_mode_with_stats() {
     assert!(stdout.contains("matches:"));
     assert!(stdout.contains("time:"));
 }
+
+#[test]
+fn test_line_regexp_flag() {
+    let (stdout, _, exit_code) = run_xerg_with_stdin(
+        &["--no-color", "--line-regexp", "done"],
+        "done\ndone now\nnot done\n",
+    );
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, "--- <stdin> ---\n    1:  done\n");
+
+    let (_, _, exit_code) =
+        run_xerg_with_stdin(&["--line-regexp", "-F", "done"], "done now\nnot done\n");
+    assert_eq!(exit_code, 1);
+}
+
+#[test]
+fn test_word_regexp_flag() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("word_files");
+    fs::create_dir(&test_dir).unwrap();
+
+    let mut file = File::create(test_dir.join("log.txt")).unwrap();
+    writeln!(file, "write to log").unwrap();
+    writeln!(file, "browse the catalog").unwrap();
+    writeln!(file, "login required").unwrap();
+
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["-w", "log", test_dir.to_str().unwrap(), "--xtreme"]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains(":1: "));
+    assert!(!stdout.contains("catalog"));
+    assert!(!stdout.contains("login"));
+}
+
+#[test]
+fn test_fixed_strings_flag() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("literal.txt");
+
+    let mut file = File::create(&test_file).unwrap();
+    writeln!(file, "version a.b").unwrap();
+    writeln!(file, "version axb").unwrap();
+
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["-F", "a.b", test_file.to_str().unwrap(), "--xtreme", "-H"]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains(":1: "));
+    assert!(!stdout.contains("axb"));
+}
+
+#[test]
+fn test_pattern_file_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let pattern_file = temp_dir.path().join("patterns.txt");
+    fs::write(&pattern_file, "Rust\n\nPython\n").unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&[
+        "-f",
+        pattern_file.to_str().unwrap(),
+        test_dir.to_str().unwrap(),
+        "--xtreme",
+    ]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("file2.rs"));
+    assert!(stdout.contains("nested.py"));
+    assert!(!stdout.contains("file1.txt"));
+}
+
+#[test]
+fn test_pattern_file_missing() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let missing = temp_dir.path().join("missing.txt");
+
+    let (stdout, stderr, exit_code) = run_xerg(&["-f", missing.to_str().unwrap()]);
+
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("failed to read pattern file"));
+    assert!(stdout.is_empty());
+}
+
+#[test]
+fn test_group_by_content_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("dup_files");
+    fs::create_dir(&test_dir).unwrap();
+
+    fs::write(test_dir.join("a.txt"), "shared line\nunique a\n").unwrap();
+    fs::write(test_dir.join("b.txt"), "other\nshared line\n").unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&[
+        "shared",
+        test_dir.to_str().unwrap(),
+        "--group-by-content",
+        "--color",
+        "always",
+    ]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+
+    let highlighter = TextHighlighter::new("shared", &Color::Red);
+    let expected = highlighter.highlight("shared line");
+    assert_eq!(stdout.matches(&expected).count(), 1);
+    assert!(stdout.contains("a.txt:1"));
+    assert!(stdout.contains("b.txt:2"));
+}
+
+#[test]
+fn test_multiple_regexp_patterns() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+
+    let (stdout, stderr, exit_code) = run_xerg(&[
+        "-e",
+        "world",
+        "-e",
+        "Python",
+        test_dir.to_str().unwrap(),
+        "--xtreme",
+        "--stats",
+    ]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("file1.txt"));
+    assert!(stdout.contains("nested.py"));
+    assert!(!stdout.contains("file2.rs"));
+    assert!(stdout.contains("matches:2"));
+}
+
+#[test]
+fn test_json_output() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("json.txt");
+    fs::write(&test_file, "no hit\nfoo and foo\n").unwrap();
+
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["foo", test_file.to_str().unwrap(), "--json", "--stats"]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(!stdout.contains('\x1b'));
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(r#""line":2,"text":"foo and foo""#));
+    assert!(lines[0].ends_with(r#""submatches":[{"start":0,"end":3},{"start":8,"end":11}]}"#));
+    assert!(lines[1].starts_with(r#"{"summary":{"files":1,"lines":2,"matches":2,"#));
+}
+
+#[test]
+fn test_color_never_strips_escape_codes() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+
+    for args in [["--color", "never"], ["--no-color", "--stats"]] {
+        let mut full_args = vec!["Hello", test_dir.to_str().unwrap()];
+        full_args.extend(args);
+        let (stdout, stderr, exit_code) = run_xerg(&full_args);
+
+        assert_eq!(exit_code, 0);
+        assert!(stderr.is_empty());
+        assert!(stdout.contains("Hello world"));
+        assert!(!stdout.contains('\x1b'));
+    }
+}
+
+#[test]
+fn test_color_auto_when_piped() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+
+    // Integration tests capture stdout, so auto mode must not emit colors
+    let (stdout, _, exit_code) = run_xerg(&["Hello", test_dir.to_str().unwrap()]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("Hello world"));
+    assert!(!stdout.contains('\x1b'));
+}
+
+#[test]
+fn test_xtreme_last_line_without_trailing_newline() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("eof_files");
+    fs::create_dir(&test_dir).unwrap();
+
+    // Enough files to go past the sequential small-batch path
+    for i in 0..6 {
+        fs::write(test_dir.join(format!("other{}.txt", i)), "nothing\n").unwrap();
+    }
+    let eof_file = test_dir.join("eof.txt");
+    fs::write(&eof_file, "first line\nlast match").unwrap();
+
+    let (single, stderr, exit_code) = run_xerg(&["match", eof_file.to_str().unwrap(), "-x", "-H"]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+
+    let expected = format!("{}:2: last match\n", eof_file.display());
+    assert_eq!(single, expected);
+
+    let (multi, stderr, exit_code) = run_xerg(&["match", test_dir.to_str().unwrap(), "-x"]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert_eq!(multi, expected);
+}
+
+#[test]
+fn test_rgb_color_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+
+    let args = [
+        "Hello",
+        test_dir.to_str().unwrap(),
+        "--color",
+        "rgb:255,128,0",
+    ];
+    let (stdout, stderr, exit_code) = run_xerg_with_env(&args, &[("COLORTERM", "truecolor")]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+
+    let highlighter = TextHighlighter::new("Hello", &Color::Rgb(255, 128, 0));
+    assert!(stdout.contains(&highlighter.highlight("Hello world")));
+    assert!(stdout.contains("\x1b[38;2;255;128;0mHello"));
+
+    // Terminals without truecolor get the nearest 256-color code instead
+    let (stdout, _, _) = run_xerg_with_env(&args, &[("COLORTERM", "")]);
+    assert!(stdout.contains("\x1b[38;5;208mHello"), "{}", stdout);
+}
+
+#[test]
+fn test_no_color_env_keeps_explicit_color() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+
+    // NO_COLOR only changes the auto default; an explicit --color always wins
+    let (stdout, _, exit_code) = run_xerg_with_env(
+        &["--color", "always", "Hello", test_dir.to_str().unwrap()],
+        &[("NO_COLOR", "1")],
+    );
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("\x1b[31mHello"), "{}", stdout);
+}
+
+#[test]
+fn test_threads_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+
+    let (stdout, stderr, exit_code) = run_xerg(&["Hello", test_dir.to_str().unwrap(), "-j", "2"]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("Hello world"));
+
+    let (_, stderr, exit_code) = run_xerg(&["Hello", test_dir.to_str().unwrap(), "-j", "many"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("'many' is not a valid thread count"));
+}
+
+#[test]
+fn test_max_depth_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("depth");
+    fs::create_dir_all(test_dir.join("nested")).unwrap();
+    fs::write(test_dir.join("top.txt"), "needle top\n").unwrap();
+    fs::write(test_dir.join("nested").join("inner.txt"), "needle inner\n").unwrap();
+
+    let (stdout, stderr, exit_code) =
+        run_xerg(&["needle", test_dir.to_str().unwrap(), "--max-depth", "1"]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("needle top"));
+    assert!(!stdout.contains("needle inner"));
+}
+
+#[test]
+fn test_no_recursive_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&["Hello", dir, "--no-recursive"]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("file1.txt"));
+    assert!(!stdout.contains("nested.py"));
+
+    // The later of --recursive and --no-recursive wins
+    let (stdout, _, _) = run_xerg(&["Hello", dir, "--no-recursive", "--recursive"]);
+    assert!(stdout.contains("nested.py"));
+
+    let (_, stderr, exit_code) = run_xerg(&["Hello", dir, "--no-recursive", "--max-depth", "2"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_note_when_filters_leave_no_files() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&["Hello", dir, "--type", "go", "--verbose"]);
+    assert_eq!(exit_code, 1);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains(&format!(
+        "note: no files to search under {} (4 filtered out)",
+        dir
+    )));
+
+    // The check walks the tree again, so it is left to --verbose
+    let (_, stderr, exit_code) = run_xerg(&["Hello", dir, "--type", "go"]);
+    assert_eq!(exit_code, 1);
+    assert!(!stderr.contains("note:"));
+
+    let (_, stderr, exit_code) = run_xerg(&["Nothing", dir, "--verbose"]);
+    assert_eq!(exit_code, 1);
+    assert!(!stderr.contains("note:"));
+}
+
+#[test]
+fn test_gitignore_respected_by_default() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("ignored");
+    fs::create_dir_all(test_dir.join("target")).unwrap();
+    fs::write(test_dir.join(".gitignore"), "target/\n").unwrap();
+    fs::write(test_dir.join("kept.txt"), "needle kept\n").unwrap();
+    fs::write(test_dir.join("target").join("built.txt"), "needle built\n").unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&["needle", test_dir.to_str().unwrap()]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("needle kept"));
+    assert!(!stdout.contains("needle built"));
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", test_dir.to_str().unwrap(), "--no-ignore"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("needle built"));
+}
+
+#[test]
+fn test_hidden_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("dotfiles");
+    fs::create_dir_all(test_dir.join(".github")).unwrap();
+    fs::write(test_dir.join(".env"), "needle env\n").unwrap();
+    fs::write(test_dir.join(".github").join("ci.yml"), "needle ci\n").unwrap();
+    fs::write(test_dir.join("visible.txt"), "needle visible\n").unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", test_dir.to_str().unwrap()]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("needle visible"));
+    assert!(!stdout.contains("needle env"));
+    assert!(!stdout.contains("needle ci"));
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", test_dir.to_str().unwrap(), "--hidden"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("needle env"));
+    assert!(stdout.contains("needle ci"));
+}
+
+#[test]
+fn test_glob_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("globbed");
+    fs::create_dir_all(test_dir.join("tests")).unwrap();
+    fs::write(test_dir.join("lib.rs"), "needle lib\n").unwrap();
+    fs::write(test_dir.join("notes.md"), "needle notes\n").unwrap();
+    fs::write(test_dir.join("tests").join("it.rs"), "needle test\n").unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&[
+        "needle",
+        test_dir.to_str().unwrap(),
+        "--glob",
+        "*.rs",
+        "-g",
+        "!tests/*",
+    ]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("needle lib"));
+    assert!(!stdout.contains("needle notes"));
+    assert!(!stdout.contains("needle test"));
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", test_dir.to_str().unwrap(), "-g", "a["]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("error: invalid glob"));
+}
+
+#[test]
+fn test_type_options() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("typed");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("lib.rs"), "needle rust\n").unwrap();
+    fs::write(test_dir.join("app.py"), "needle python\n").unwrap();
+    fs::write(test_dir.join("notes.md"), "needle notes\n").unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&[
+        "needle",
+        test_dir.to_str().unwrap(),
+        "-t",
+        "rust",
+        "-t",
+        "py",
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("needle rust"));
+    assert!(stdout.contains("needle python"));
+    assert!(!stdout.contains("needle notes"));
+
+    let (stdout, _, exit_code) =
+        run_xerg(&["needle", test_dir.to_str().unwrap(), "--type-not", "md"]);
+    assert_eq!(exit_code, 0);
+    assert!(!stdout.contains("needle notes"));
+    assert!(stdout.contains("needle rust"));
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", test_dir.to_str().unwrap(), "-t", "cobol"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("unknown file type 'cobol'"));
+}
+
+#[test]
+fn test_type_list_option() {
+    let (stdout, stderr, exit_code) = run_xerg(&["--type-list"]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("rust: rs"));
+    assert!(stdout.contains("py: py, pyi"));
+}
+
+#[test]
+fn test_max_filesize_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("sizes");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("small.txt"), "needle small\n").unwrap();
+    fs::write(test_dir.join("large.txt"), "needle large\n".repeat(200)).unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&[
+        "needle",
+        test_dir.to_str().unwrap(),
+        "--max-filesize",
+        "1K",
+        "--stats",
+    ]);
+
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("needle small"));
+    assert!(!stdout.contains("needle large"));
+    assert!(stdout.contains("files:1; lines:1; matches:1; skipped:1;"));
+
+    let (_, stderr, exit_code) = run_xerg(&[
+        "needle",
+        test_dir.to_str().unwrap(),
+        "--max-filesize",
+        "huge",
+    ]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("'huge' is not a valid size"));
+}
+
+#[test]
+fn test_verbose_lists_skipped_files() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("verbose");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("small.txt"), "needle small\n").unwrap();
+    fs::write(test_dir.join("large.txt"), "needle large\n".repeat(200)).unwrap();
+    fs::write(test_dir.join("bad.txt"), b"needle \xff\nneedle ok\n").unwrap();
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["needle", dir, "--max-filesize", "1K", "--verbose"]].concat();
+        let (stdout, stderr, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("needle small"));
+        assert!(!stdout.contains("skipped"));
+        let bad = test_dir.join("bad.txt");
+        let large = test_dir.join("large.txt");
+        assert_eq!(
+            stderr,
+            format!(
+                "skipped:\n  {}: 1 line skipped as invalid UTF-8\n  {}: larger than --max-filesize (1024 bytes)\n",
+                bad.display(),
+                large.display()
+            )
+        );
+    }
+
+    let (_, stderr, _) = run_xerg(&["needle", dir, "--max-filesize", "1K"]);
+    assert!(stderr.is_empty());
+}
+
+#[test]
+fn test_summary_json_report() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["Hello", dir, "--summary-json", "--relative"]].concat();
+        let (stdout, stderr, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        assert!(stderr.is_empty());
+        assert_eq!(stdout.lines().count(), 1);
+        assert!(stdout.starts_with(
+            r#"{"files":[{"path":"empty.txt","matches":0,"lines":0,"skipped":0},{"path":"file1.txt","matches":1,"#
+        ));
+        assert!(
+            stdout.contains(r#"{"path":"subdir/nested.py","matches":1,"lines":2,"skipped":0}"#)
+        );
+        assert!(stdout.contains(r#""totals":{"files":4,"#));
+        assert!(stdout.contains(r#""matches":3,"skipped":0,"errors":0},"elapsed_ms":"#));
+    }
+
+    let (stdout, _, exit_code) = run_xerg(&["Nothing", dir, "--summary-json"]);
+    assert_eq!(exit_code, 1);
+    assert!(stdout.contains(r#""matches":0,"skipped":0,"errors":0}"#));
+
+    let (_, stderr, exit_code) = run_xerg(&["Hello", dir, "--summary-json", "--json"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_search_piped_stdin() {
+    let input = "first needle\nno match\nneedle and needle\n";
+
+    let (stdout, stderr, exit_code) = run_xerg_with_stdin(&["needle", "--stats"], input);
+    assert_eq!(exit_code, 0);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("<stdin>"));
+    assert!(stdout.contains("first needle"));
+    assert!(stdout.contains("needle and needle"));
+    assert!(!stdout.contains("no match"));
+    assert!(stdout.contains("files:1; lines:3; matches:3; skipped:0;"));
+
+    let (stdout, _, exit_code) = run_xerg_with_stdin(&["-x", "needle", "--stats"], input);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("<stdin>:1: first needle"));
+    assert!(stdout.contains("<stdin>:3: needle and needle"));
+    assert!(stdout.contains("files:1, lines:3, matches:3, skipped:0"));
+}
+
+#[test]
+fn test_replace_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("replace.txt");
+    fs::write(&test_file, "call foo12 and foo3\nnothing here\n").unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&[
+        r"foo(\d+)",
+        test_file.to_str().unwrap(),
+        "--replace",
+        "bar$1",
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("call bar12 and bar3"));
+    assert!(!stdout.contains("foo"));
+    assert!(!stdout.contains("nothing here"));
+    assert!(!stdout.contains("\x1b["));
+
+    let (stdout, _, exit_code) = run_xerg(&[
+        "-x",
+        "-H",
+        r"foo(\d+)",
+        test_file.to_str().unwrap(),
+        "-r",
+        "<$1>",
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains(":1: call <12> and <3>"));
+}
+
+#[test]
+fn test_exit_codes() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (_, _, exit_code) = run_xerg(&[mode, &["Hello", dir]].concat());
+        assert_eq!(exit_code, 0);
+
+        let (_, _, exit_code) = run_xerg(&[mode, &["NoSuchText", dir]].concat());
+        assert_eq!(exit_code, 1);
+    }
+
+    let (_, _, exit_code) = run_xerg(&["NoSuchText", dir, "--json"]);
+    assert_eq!(exit_code, 1);
+
+    let (stdout, stderr, exit_code) = run_xerg(&["foo(", dir]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("error: invalid pattern"));
+    assert!(stdout.is_empty());
+}
+
+#[test]
+fn test_column_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("columns.txt");
+    fs::write(&test_file, "no hit\n  needle then needle\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", path, "--column"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("  2:3:    needle then needle"));
+
+    let (stdout, _, exit_code) = run_xerg(&["-x", "-H", "needle", path, "--column"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains(&format!("{}:2:3:   needle then needle", path)));
+}
+
+#[test]
+fn test_show_line_match_count_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("density.txt");
+    fs::write(&test_file, "no hit\nneedle needle needle\none needle\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let args = ["--no-color", "needle", path, "--show-line-match-count"];
+    let (stdout, _, exit_code) = run_xerg(&args);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("    2:  (3x)  needle needle needle\n"));
+    assert!(stdout.contains("    3:  (1x)  one needle\n"));
+
+    let (stdout, _, _) = run_xerg(&[&["-x"][..], &args].concat());
+    assert!(
+        stdout.contains("2: (3x)  needle needle needle\n"),
+        "{}",
+        stdout
+    );
+
+    let (stdout, _, _) = run_xerg(&["--no-color", "needle", path]);
+    assert!(!stdout.contains("(3x)"));
+}
+
+#[test]
+fn test_no_line_number_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("snippet.rs");
+    fs::write(&test_file, "fn main() {\n    let needle = 1;\n}\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", path, "-N", "--stats"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("\n    let needle = 1;\n"));
+    assert!(!stdout.contains("2:"));
+    assert!(stdout.contains("files:1; lines:3; matches:1;"));
+
+    let (stdout, _, exit_code) = run_xerg(&["-x", "-H", "needle", path, "--no-line-number"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains(&format!("{}:     let needle = 1;", path)));
+
+    let (stdout, _, _) = run_xerg(&["-x", "-H", "needle", path, "-N", "--line-number"]);
+    assert!(stdout.contains(&format!("{}:2:     let needle = 1;", path)));
+}
+
+#[test]
+fn test_xtreme_heading_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("heading");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("one.txt"), "a needle\nb\nneedle c\n").unwrap();
+    fs::write(test_dir.join("two.txt"), "needle two\n").unwrap();
+    let one = test_dir.join("one.txt");
+
+    let (stdout, _, exit_code) =
+        run_xerg(&["-x", "--heading", "needle", test_dir.to_str().unwrap()]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains(&format!("{}\n1: a needle\n3: needle c\n", one.display())));
+    assert!(stdout.contains("1: needle two"));
+    assert_eq!(stdout.matches(one.to_str().unwrap()).count(), 1);
+    assert_eq!(stdout.matches("\n\n").count(), 1);
+}
+
+#[test]
+fn test_max_count_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("log.txt");
+    fs::write(&test_file, "error 1\nok\nerror 2\nerror 3\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["error", path, "-m", "2", "--stats"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("error 2"));
+    assert!(!stdout.contains("error 3"));
+    assert!(stdout.contains("files:1; lines:4; matches:3;"));
+
+    let (stdout, _, exit_code) = run_xerg(&["-x", "-H", "error", path, "--max-count", "1"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains(":1: error 1"));
+    assert!(!stdout.contains("error 2"));
+}
+
+#[test]
+fn test_sort_path_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("sorted");
+    fs::create_dir(&test_dir).unwrap();
+    for name in ["d.txt", "b.txt", "e.txt", "a.txt", "c.txt", "f.txt"] {
+        fs::write(test_dir.join(name), format!("needle in {}\n", name)).unwrap();
+    }
+    let dir = test_dir.to_str().unwrap();
+    let expected = ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt", "f.txt"];
+
+    for mode in [&[][..], &["-x"][..], &["--json"][..]] {
+        let (stdout, _, exit_code) = run_xerg(&[mode, &["needle", dir, "--sort", "path"]].concat());
+        assert_eq!(exit_code, 0);
+
+        let order: Vec<&str> = stdout
+            .lines()
+            .filter_map(|line| {
+                expected
+                    .iter()
+                    .find(|name| line.contains(&format!("needle in {}", name)))
+            })
+            .copied()
+            .collect();
+        assert_eq!(order, expected, "mode {:?}", mode);
+    }
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", dir, "--sort", "size"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("unknown sort key 'size'"));
+}
+
+#[test]
+fn test_sort_modified_and_sortr_options() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("modified");
+    fs::create_dir(&test_dir).unwrap();
+    // Names run against age, so path and modified order differ
+    let oldest_first = ["c.txt", "a.txt", "b.txt"];
+    for (age, name) in oldest_first.iter().enumerate() {
+        let path = test_dir.join(name);
+        fs::write(&path, format!("needle in {}\n", name)).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 * (age as u64 + 1));
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+    let dir = test_dir.to_str().unwrap();
+
+    let order = |stdout: &str| -> Vec<String> {
+        stdout
+            .lines()
+            .filter_map(|line| line.split("needle in ").nth(1))
+            .map(str::to_string)
+            .collect()
+    };
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (stdout, _, _) = run_xerg(&[mode, &["needle", dir, "--sort", "modified"]].concat());
+        assert_eq!(order(&stdout), oldest_first, "mode {:?}", mode);
+
+        let (stdout, _, _) = run_xerg(&[mode, &["needle", dir, "--sortr", "modified"]].concat());
+        assert_eq!(
+            order(&stdout),
+            ["b.txt", "a.txt", "c.txt"],
+            "mode {:?}",
+            mode
+        );
+
+        let (stdout, _, _) = run_xerg(&[mode, &["needle", dir, "--sortr", "path"]].concat());
+        assert_eq!(
+            order(&stdout),
+            ["c.txt", "b.txt", "a.txt"],
+            "mode {:?}",
+            mode
+        );
+    }
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", dir, "--sort", "path", "--sortr", "path"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_search_zip_option() {
+    use flate2::{Compression, write::GzEncoder};
+
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("logs");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("current.log"), "ERROR plain\n").unwrap();
+    fs::write(test_dir.join("broken.log.gz"), "not gzip data\n").unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"ok\nERROR rotated\n").unwrap();
+    fs::write(test_dir.join("old.log.gz"), encoder.finish().unwrap()).unwrap();
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (stdout, _, exit_code) = run_xerg(&[mode, &["-z", "ERROR", dir]].concat());
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("ERROR plain"), "mode {:?}", mode);
+        assert!(stdout.contains("ERROR rotated"), "mode {:?}", mode);
+    }
+
+    let (stdout, _, _) = run_xerg(&["ERROR", dir]);
+    assert!(stdout.contains("ERROR plain"));
+    assert!(!stdout.contains("ERROR rotated"));
+}
+
+#[test]
+fn test_encoding_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("encoded");
+    fs::create_dir(&test_dir).unwrap();
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    for unit in "first\r\nneedle utf16\r\n".encode_utf16() {
+        utf16.extend_from_slice(&unit.to_le_bytes());
+    }
+    fs::write(test_dir.join("utf16.txt"), utf16).unwrap();
+    fs::write(test_dir.join("latin1.txt"), b"needle caf\xe9\n").unwrap();
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", dir, "--encoding", "latin1", "--stats"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("needle utf16"));
+    assert!(stdout.contains("needle caf\u{e9}"));
+    assert!(stdout.contains("files:2; lines:3; matches:2; skipped:0;"));
+
+    let (stdout, _, exit_code) = run_xerg(&["-x", "needle", dir, "--encoding", "utf-8", "--stats"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("needle utf16"));
+    assert!(!stdout.contains("needle caf"));
+    assert!(stdout.contains("skipped:1"));
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", dir, "--encoding", "klingon"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("unknown encoding 'klingon'"));
+}
+
+#[test]
+fn test_invalid_utf8_lines_are_skipped() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("mixed.txt");
+    fs::write(&test_file, b"good match\n\xff\xfe bad match\nmatch again\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (stdout, _, exit_code) = run_xerg(&[mode, &["match", path, "--stats"]].concat());
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("good match"), "mode {:?}", mode);
+        assert!(stdout.contains("match again"), "mode {:?}", mode);
+        assert!(!stdout.contains("bad match"), "mode {:?}", mode);
+        assert!(stdout.contains("skipped:1"), "mode {:?}", mode);
+    }
+}
+
+#[test]
+fn test_byte_regexp_searches_invalid_utf8_lines() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("mixed.txt");
+    fs::write(&test_file, b"good match\n\xff\xfe bad match\nmatch again\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["match", path, "--byte-regexp", "--stats"]);
+    assert_eq!(exit_code, 0);
+    assert!(
+        stdout.contains("  2:  \u{fffd}\u{fffd} bad match"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("lines:3; matches:3; skipped:0;"),
+        "{}",
+        stdout
+    );
+
+    let (_, stderr, exit_code) = run_xerg(&["match", path, "--byte-regexp", "-U"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_byte_offset_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("offsets.txt");
+    fs::write(&test_file, "one\r\nx needle\nneedle\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", path, "-b", "--color", "never"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("  2:7:  x needle"));
+    assert!(stdout.contains("  3:14:  needle"));
+
+    let (stdout, _, _) = run_xerg(&["-x", "-H", "needle", path, "-b", "--color", "never"]);
+    assert!(stdout.contains(&format!("{}:2:7: x needle", path)));
+    assert!(stdout.contains(&format!("{}:3:14: needle", path)));
+
+    let (stdout, _, _) = run_xerg(&["needle", path, "-b", "--json"]);
+    assert!(stdout.contains(r#""byte_offset":7}"#));
+    assert!(stdout.contains(r#""byte_offset":14}"#));
+}
+
+#[test]
+fn test_stats_format_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("stats.txt");
+    fs::write(&test_file, "needle one\nhay\nneedle two\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["needle", path, "--stats", "--color", "never"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("\nresult: files:1; lines:3; matches:2;"));
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (stdout, _, exit_code) =
+            run_xerg(&[mode, &["needle", path, "--stats-format", "json"]].concat());
+        assert_eq!(exit_code, 0);
+        let summary = stdout.lines().last().unwrap();
+        assert!(
+            summary.starts_with(r#"{"summary":{"files":1,"lines":3,"matches":2,"skipped":0,"#),
+            "mode {:?}: {}",
+            mode,
+            summary
+        );
+        assert!(!summary.contains('\x1b'));
+    }
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", path, "--stats-format", "yaml"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("unknown stats format 'yaml'"));
+}
+
+#[test]
+fn test_files_lists_without_searching() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--files", dir, "-t", "rust"]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, format!("{}\n", test_dir.join("file2.rs").display()));
+
+    let (stdout, _, exit_code) = run_xerg(&["--files", dir, "--null"]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout.matches('\0').count(), 4);
+    assert!(!stdout.contains('\n'));
+
+    let (stdout, _, exit_code) = run_xerg(&["--files", dir, "-g", "*.none"]);
+    assert_eq!(exit_code, 1);
+    assert!(stdout.is_empty());
+}
+
+#[test]
+fn test_debug_logs_reader_per_file() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let single = test_dir.join("file1.txt");
+
+    let (stdout, stderr, exit_code) = run_xerg(&["Hello", single.to_str().unwrap(), "--debug"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("Hello world"));
+    assert!(
+        stderr.contains(&format!("debug: {}: BulkRead (", single.display())),
+        "{}",
+        stderr
+    );
+
+    let (_, stderr, _) = run_xerg(&["-x", "Hello", test_dir.to_str().unwrap(), "--debug"]);
+    assert_eq!(stderr.matches("debug: ").count(), 4, "{}", stderr);
+    assert!(stderr.contains("Streaming (one of several files)"));
+
+    let (_, stderr, _) = run_xerg(&["Hello", single.to_str().unwrap()]);
+    assert!(!stderr.contains("debug:"));
+}
+
+#[test]
+fn test_mmap_flags_override_reader() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let single = test_dir.join("file1.txt");
+    let path = single.to_str().unwrap();
+
+    let (stdout, stderr, exit_code) = run_xerg(&["Hello", path, "--mmap", "--debug"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("Hello world"));
+    assert!(stderr.contains("MemoryMap (--mmap)"), "{}", stderr);
+
+    let (_, stderr, _) = run_xerg(&["Hello", path, "--mmap", "--no-mmap", "--debug"]);
+    assert!(stderr.contains(": BulkRead ("), "{}", stderr);
+}
+
+#[test]
+fn test_total_prints_bare_count() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (stdout, _, exit_code) = run_xerg(&[mode, &["Hello|l", dir, "--total"]].concat());
+        assert_eq!(exit_code, 0);
+        assert!(stdout.trim().parse::<usize>().unwrap() > 3, "{}", stdout);
+        assert_eq!(stdout.lines().count(), 1);
+    }
+
+    let (stdout, _, exit_code) = run_xerg(&["Hello", dir, "--total"]);
+    assert_eq!((stdout.as_str(), exit_code), ("3\n", 0));
+
+    let (stdout, _, exit_code) = run_xerg(&["nomatch", dir, "--total"]);
+    assert_eq!((stdout.as_str(), exit_code), ("0\n", 1));
+
+    let (_, stderr, exit_code) = run_xerg(&["Hello", dir, "--total", "--stats"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_timeout_reports_partial_results() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["Hello", dir, "--timeout", "0", "--stats"]].concat();
+        let (stdout, stderr, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 124, "mode {:?}", mode);
+        assert!(stderr.contains("warning: search timed out after 0s; results are partial"));
+        assert!(stdout.contains("files:0"), "{}", stdout);
+    }
+
+    let (stdout, _, exit_code) = run_xerg(&["Hello", dir, "--timeout", "60"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("Hello world"));
+
+    let (_, stderr, exit_code) = run_xerg(&["Hello", dir, "--timeout", "soon"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("'soon' is not a valid timeout"));
+}
+
+#[test]
+fn test_multiple_paths() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let file1 = test_dir.join("file1.txt");
+    let subdir = test_dir.join("subdir");
+
+    let (stdout, _, exit_code) = run_xerg(&[
+        "Hello",
+        file1.to_str().unwrap(),
+        subdir.to_str().unwrap(),
+        test_dir.to_str().unwrap(),
+        "--stats",
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("Hello world"));
+    assert!(stdout.contains("Hello Python"));
+    assert!(stdout.contains("Hello Rust"));
+    // file1.txt and subdir/ are also under test_dir, but are searched once
+    assert!(stdout.contains("files:4;"), "{}", stdout);
+
+    // Several explicit files keep their path in xtreme mode
+    let (stdout, _, exit_code) = run_xerg(&[
+        "-x",
+        "-e",
+        "Hello",
+        file1.to_str().unwrap(),
+        test_dir.join("file2.rs").to_str().unwrap(),
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(
+        stdout.contains(&format!("{}:1: ", file1.display())),
+        "{}",
+        stdout
+    );
+
+    let (_, stderr, exit_code) = run_xerg(&["Hello", file1.to_str().unwrap(), "/nonexistent"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("does not exist: /nonexistent"));
+}
+
+#[test]
+fn test_null_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("null.txt");
+    fs::write(&test_file, "needle here\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["-x", "-H", "needle", path, "-Z", "--color", "never"]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, format!("{}\u{0}1: needle here\n", path));
+
+    let (stdout, _, _) = run_xerg(&[
+        "-x", "-H", "needle", path, "--null", "-N", "--color", "never",
+    ]);
+    assert_eq!(stdout, format!("{}\0needle here\n", path));
+
+    let (stdout, _, _) = run_xerg(&[
+        "needle",
+        path,
+        "-Z",
+        "--group-by-content",
+        "--color",
+        "never",
+    ]);
+    assert!(stdout.contains(&format!("{}\u{0}1", path)));
+
+    let (stdout, _, _) = run_xerg(&["needle", path, "-Z", "--color", "never"]);
+    assert!(stdout.contains(&format!("--- {}\0 ---", path)));
+}
+
+#[test]
+fn test_count_matches_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("counts");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("many.txt"), "foo foo\nbar\nfoo\n").unwrap();
+    fs::write(test_dir.join("none.txt"), "bar\n").unwrap();
+    let dir = test_dir.to_str().unwrap();
+    let expected = format!("{}:3\n", test_dir.join("many.txt").display());
+
+    for mode in [&[][..], &["-x"][..]] {
+        let (stdout, _, exit_code) = run_xerg(&[mode, &["--count-matches", "foo", dir]].concat());
+        assert_eq!(exit_code, 0);
+        assert_eq!(stdout, expected, "mode {:?}", mode);
+
+        let (stdout, _, exit_code) =
+            run_xerg(&[mode, &["--count-matches", "missing", dir]].concat());
+        assert_eq!(exit_code, 1);
+        assert!(stdout.is_empty(), "mode {:?}", mode);
+    }
+}
+
+#[test]
+fn test_multiline_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("multiline.rs");
+    fs::write(&test_file, "use std::io;\nfn main(\n    x: u32,\n) {}\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", r"main\(\s*x", path]);
+    assert_eq!(exit_code, 1);
+    assert!(!stdout.contains("fn main("));
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "-x", "-H", "-U", r"main\(\s*x", path]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(
+        stdout,
+        format!("{}:2: fn main(\n{}:3:     x: u32,\n", path, path)
+    );
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "--multiline", r"main\(\s*x", path]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("  2:  fn main(\n"));
+    assert!(stdout.contains("  3:      x: u32,\n"));
+    assert!(!stdout.contains("use std::io"));
+
+    let (stdout, _, _) = run_xerg(&["--no-color", "-x", "-H", "-U", r"main.*\{", path]);
+    assert!(stdout.is_empty());
+    let (stdout, _, _) = run_xerg(&[
+        "--no-color",
+        "-x",
+        "-H",
+        "-U",
+        "--multiline-dotall",
+        r"main.*\{",
+        path,
+    ]);
+    assert_eq!(stdout.lines().count(), 3);
+}
+
+#[test]
+fn test_stats_by_type_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("types");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("a.rs"), "foo\nfoo foo\n").unwrap();
+    fs::write(test_dir.join("b.md"), "foo\n").unwrap();
+    fs::write(test_dir.join("Makefile"), "foo\n").unwrap();
+    fs::write(test_dir.join("c.txt"), "bar\n").unwrap();
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "--stats-by-type", "foo", dir]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("result: files:4;"));
+    assert!(
+        stdout.ends_with("types: .rs: 3 matches, (none): 1 matches, .md: 1 matches\n"),
+        "{}",
+        stdout
+    );
+
+    let (stdout, _, _) = run_xerg(&["--stats-by-type", "--stats-format", "json", "foo", dir]);
+    assert!(stdout.ends_with(
+        "{\"types\":[{\"type\":\".rs\",\"matches\":3},{\"type\":\"(none)\",\"matches\":1},{\"type\":\".md\",\"matches\":1}]}\n"
+    ));
+}
+
+#[test]
+fn test_decorations_independent_of_match_color() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("decorations.txt");
+    fs::write(&test_file, "a foo line\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, _) = run_xerg(&["--color", "always", "--decorations", "never", "foo", path]);
+    assert_eq!(
+        stdout,
+        format!("--- {} ---\n    1:  a \x1b[31mfoo\x1b[0m line\n", path)
+    );
+
+    let (stdout, _, _) = run_xerg(&["--no-color", "--decorations", "always", "foo", path]);
+    assert!(stdout.contains(&format!("\x1b[1;38;5;245m--- {}\x1b[0m ---", path)));
+    assert!(stdout.contains("a foo line"));
+    assert!(!stdout.contains("\x1b[31m"));
+
+    let (_, stderr, exit_code) = run_xerg(&["--decorations", "sometimes", "foo", path]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("--decorations"));
+}
+
+#[test]
+fn test_ui_color_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("ui_color.txt");
+    fs::write(&test_file, "a foo line\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let args = [
+        "--decorations",
+        "always",
+        "--ui-color",
+        "cyan",
+        "--stats",
+        "foo",
+        path,
+    ];
+    let (stdout, _, _) = run_xerg(&args);
+    assert!(stdout.contains(&format!("\x1b[1;36m--- {}\x1b[0m (1 match) ---", path)));
+    assert!(stdout.contains("\x1b[1;36m  1:\x1b[0m"), "{}", stdout);
+    assert!(stdout.contains("\x1b[2;36mlines: 1"));
+    assert!(!stdout.contains("245"));
+
+    let (stdout, _, _) = run_xerg(&["--decorations", "always", "--ui-color", "33", "foo", path]);
+    assert!(stdout.contains("\x1b[1;38;5;33m--- "));
+
+    let (_, stderr, exit_code) = run_xerg(&["--ui-color", "grey", "foo", path]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("error: invalid --ui-color: invalid color 'grey'"));
+}
+
+#[test]
+fn test_stats_show_match_count_in_headers() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("counted.txt");
+    fs::write(&test_file, "foo\nbar\nfoo foo\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "--stats", "foo", path]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.starts_with(&format!("--- {} (3 matches) ---\n", path)));
+
+    let (stdout, _, _) = run_xerg(&["--no-color", "foo", path]);
+    assert!(stdout.starts_with(&format!("--- {} ---\n", path)));
+}
+
+#[test]
+fn test_no_headers_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("headers");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(test_dir.join("hit.txt"), "foo\n").unwrap();
+    fs::write(test_dir.join("miss.txt"), "bar\n").unwrap();
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, _, _) = run_xerg(&["--no-color", "--stats", "foo", dir]);
+    assert!(stdout.contains("hit.txt (1 match) ---"));
+    assert!(!stdout.contains("miss.txt"));
+    assert_eq!(stdout.matches("lines: 1, matches: 1").count(), 1);
+    assert!(stdout.contains("result: files:2;"));
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "--no-headers", "foo", dir]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, "    1:  foo\n");
+}
+
+#[test]
+fn test_stdin_header_waits_for_first_match() {
+    let (stdout, _, exit_code) = run_xerg_with_stdin(&["--no-color", "foo"], "bar\nbaz\n");
+    assert_eq!(exit_code, 1);
+    assert!(stdout.is_empty(), "{}", stdout);
+
+    let (stdout, _, exit_code) = run_xerg_with_stdin(&["--no-color", "foo"], "bar\nfoo\n");
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, "--- <stdin> ---\n    2:  foo\n");
+}
+
+#[test]
+fn test_empty_pattern_matches_every_line_without_highlighting() {
+    let (stdout, _, exit_code) = run_xerg_with_stdin(
+        &["--color", "always", "--decorations", "never", ""],
+        "foo\nbar\n",
+    );
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, "--- <stdin> ---\n    1:  foo\n    2:  bar\n");
+}
+
+#[test]
+fn test_files_from_searches_only_listed_files() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let listed = temp_dir.path().join("listed.txt");
+    let unlisted = temp_dir.path().join("unlisted.txt");
+    let missing = temp_dir.path().join("missing.txt");
+    fs::write(&listed, "foo\n").unwrap();
+    fs::write(&unlisted, "foo\n").unwrap();
+    let list = format!("{}\n{}\n", missing.display(), listed.display());
+
+    let (stdout, stderr, exit_code) =
+        run_xerg_with_stdin(&["--no-color", "--files-from", "-", "foo"], &list);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("listed.txt ---"));
+    assert!(!stdout.contains("unlisted.txt"));
+    assert!(stderr.contains("missing.txt"), "{}", stderr);
+
+    let list_file = temp_dir.path().join("files.lst");
+    fs::write(&list_file, format!("{}\n", listed.display())).unwrap();
+    let (stdout, _, exit_code) = run_xerg(&[
+        "--xtreme",
+        "--files-from",
+        list_file.to_str().unwrap(),
+        "foo",
+    ]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, format!("{}:1: foo\n", listed.display()));
+
+    let (_, stderr, exit_code) = run_xerg(&[
+        "--files-from",
+        list_file.to_str().unwrap(),
+        "foo",
+        temp_dir.path().to_str().unwrap(),
+    ]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("--files-from"));
+}
+
+#[test]
+fn test_path_separator_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let nested = temp_dir.path().join("nested");
+    fs::create_dir(&nested).unwrap();
+    fs::write(nested.join("file.txt"), "foo\n").unwrap();
+    let expected = nested.join("file.txt").to_str().unwrap().replace('/', "|");
+
+    let (stdout, _, exit_code) = run_xerg(&[
+        "--no-color",
+        "--path-separator",
+        "|",
+        "foo",
+        temp_dir.path().to_str().unwrap(),
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(
+        stdout.contains(&format!("--- {} ---", expected)),
+        "{}",
+        stdout
+    );
+
+    let (stdout, _, _) = run_xerg(&[
+        "--xtreme",
+        "--path-separator",
+        "|",
+        "foo",
+        temp_dir.path().to_str().unwrap(),
+    ]);
+    assert_eq!(stdout, format!("{}:1: foo\n", expected));
+
+    let (_, stderr, exit_code) = run_xerg(&["--path-separator", "ab", "foo"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("path separator"));
+}
+
+#[test]
+fn test_trim_option() {
+    let (stdout, _, exit_code) = run_xerg_with_stdin(
+        &["--no-color", "--trim", "--column", "call"],
+        "fn main() {\n        call();\n}\n",
+    );
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, "--- <stdin> ---\n    2:1:  call();\n");
+
+    let (stdout, _, _) =
+        run_xerg_with_stdin(&["-x", "--trim", "call"], "fn main() {\n\t\tcall();\n}\n");
+    assert_eq!(stdout, "<stdin>:2: call();\n");
+}
+
+#[test]
+fn test_include_exclude_options() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let sub_dir = temp_dir.path().join("sub");
+    fs::create_dir(&sub_dir).unwrap();
+    fs::write(temp_dir.path().join("Cargo.toml"), "version\n").unwrap();
+    fs::write(sub_dir.join("Cargo.lock"), "version\n").unwrap();
+    fs::write(sub_dir.join("notes.md"), "version\n").unwrap();
+    let dir = temp_dir.path().to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&[
+        "-x",
+        "--include",
+        "*.toml",
+        "--include",
+        "Cargo.lock",
+        "--exclude",
+        "Cargo.lock",
+        "version",
+        dir,
+    ]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("Cargo.toml"));
+    assert!(!stdout.contains("Cargo.lock"));
+    assert!(!stdout.contains("notes.md"));
+
+    let (_, stderr, exit_code) = run_xerg(&["--include", "a[", "version", dir]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("--include"));
+}
+
+#[test]
+fn test_max_matches_total_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        fs::write(temp_dir.path().join(name), "hit\nhit\nhit\n").unwrap();
+    }
+    let dir = temp_dir.path().to_str().unwrap();
+
+    // A handful of files is searched in order, so the cap is exact here
+    let (stdout, _, exit_code) = run_xerg(&["-x", "--max-matches-total", "4", "hit", dir]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout.lines().count(), 4);
+
+    let (stdout, _, _) = run_xerg(&["--no-color", "--max-matches-total", "4", "hit", dir]);
+    assert_eq!(stdout.matches(":  hit").count(), 4);
+    assert!(!stdout.contains("c.txt"));
+
+    let (stdout, _, _) = run_xerg_with_stdin(
+        &["--no-color", "--max-matches-total", "1", "hit"],
+        "hit\nhit\n",
+    );
+    assert_eq!(stdout, "--- <stdin> ---\n    1:  hit\n");
+}
+
+#[test]
+fn test_stats_summary_with_no_files() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let dir = temp_dir.path().to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "--stats", "foo", dir]);
+    assert_eq!(exit_code, 1);
+    assert!(stdout.starts_with("result: files:0; lines:0; matches:0;"));
+    assert!(stdout.contains("time:"));
+}
+
+#[test]
+fn test_filename_toggle_options() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let file = temp_dir.path().join("single.txt");
+    fs::write(&file, "skip\nneedle\n").unwrap();
+    let path = file.to_str().unwrap();
+    let dir = temp_dir.path().to_str().unwrap();
+
+    // A single explicit file leaves the path off, a directory search keeps it
+    let (stdout, _, exit_code) = run_xerg(&["-x", "needle", path]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(stdout, "2: needle\n");
+
+    let (stdout, _, _) = run_xerg(&["-x", "needle", dir]);
+    assert_eq!(stdout, format!("{}:2: needle\n", path));
+
+    let (stdout, _, _) = run_xerg(&["-x", "-H", "needle", path]);
+    assert_eq!(stdout, format!("{}:2: needle\n", path));
+
+    let (stdout, _, _) = run_xerg(&["-x", "-h", "needle", dir]);
+    assert_eq!(stdout, "2: needle\n");
+
+    let (stdout, _, exit_code) = run_xerg(&["--help"]);
+    assert_eq!(exit_code, 0);
+    assert!(stdout.contains("--no-filename"));
+}
+
+#[test]
+fn test_in_place_rewrites_matching_files() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+    let rust_file = test_dir.join("file2.rs");
+    let original = fs::read_to_string(&rust_file).unwrap();
+
+    let args = [
+        "Hello (\\w+)",
+        dir,
+        "-r",
+        "Bye $1",
+        "--in-place",
+        "--backup",
+        ".orig",
+    ];
+    let (stdout, _, exit_code) = run_xerg(&args);
+    assert_eq!(exit_code, 0);
+    assert_eq!(
+        stdout,
+        "result: files:4; modified:3; replacements:3; skipped:0; errors:0;\n"
+    );
+    assert!(
+        fs::read_to_string(&rust_file)
+            .unwrap()
+            .contains("Bye Rust!")
+    );
+    assert_eq!(
+        fs::read_to_string(test_dir.join("file2.rs.orig")).unwrap(),
+        original
+    );
+    assert!(!test_dir.join("empty.txt.orig").exists());
+
+    let (stdout, _, exit_code) = run_xerg(&[
+        "Hello",
+        rust_file.to_str().unwrap(),
+        "-r",
+        "x",
+        "--in-place",
+    ]);
+    assert_eq!(exit_code, 1);
+    assert!(stdout.contains("modified:0"));
+
+    let (_, stderr, exit_code) = run_xerg(&["Bye", dir, "--in-place"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("required arguments"));
+}
+
+#[test]
+fn test_path_arguments_expand_variables() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let env = [("XERG_TEST_DIR", test_dir.to_str().unwrap())];
+
+    for path in ["$XERG_TEST_DIR/file1.txt", "${XERG_TEST_DIR}/file1.txt"] {
+        let (stdout, _, exit_code) = run_xerg_with_env(&["--no-color", "Hello", path], &env);
+        assert_eq!(exit_code, 0, "{}", path);
+        assert!(stdout.contains("Hello world"));
+    }
+
+    let (_, stderr, exit_code) = run_xerg_with_env(&["Hello", "$XERG_TEST_DIR/missing.txt"], &env);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("error: file or directory does not exist: $XERG_TEST_DIR/missing.txt"));
+
+    let (_, _, exit_code) = run_xerg(&["Hello", "$XERG_UNSET_TEST_DIR/file1.txt"]);
+    assert_eq!(exit_code, 2);
+}
+
+#[test]
+fn test_max_count_keeps_stats_exact() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("hits.txt");
+    fs::write(&test_file, "hit 1\nhit 2\nhit 3\nhit 4\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["--no-color", "--stats", "-m", "1", "hit", path]].concat();
+        let (stdout, _, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("hit 1"));
+        assert!(!stdout.contains("hit 2"));
+        assert!(stdout.contains("lines: 4, matches: 4,"), "{}", stdout);
+    }
+
+    let args = [
+        "--no-color",
+        "--stats",
+        "-m",
+        "1",
+        "--max-count-stops-scan",
+        "hit",
+        path,
+    ];
+    let (stdout, _, _) = run_xerg(&args);
+    assert!(!stdout.contains("hit 2"));
+    assert!(stdout.contains("lines: 1, matches: 1,"), "{}", stdout);
+}
+
+#[test]
+fn test_context_lines_and_separator() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("context.txt");
+    fs::write(&test_file, "a\nb\nhit 1\nc\nd\ne\nf\nhit 2\ng\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "-C", "1", "hit", path]);
+    assert_eq!(exit_code, 0);
+    let lines: Vec<&str> = stdout.lines().skip(1).map(str::trim).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "2-  b",
+            "3:  hit 1",
+            "4-  c",
+            "--",
+            "7-  f",
+            "8:  hit 2",
+            "9-  g"
+        ]
+    );
+
+    // -A and -B take precedence over -C
+    let args = [
+        "--no-color",
+        "-C",
+        "1",
+        "-A",
+        "0",
+        "--context-separator",
+        "::",
+        "hit",
+        path,
+    ];
+    let (stdout, _, _) = run_xerg(&args);
+    let lines: Vec<&str> = stdout.lines().skip(1).map(str::trim).collect();
+    assert_eq!(
+        lines,
+        vec!["2-  b", "3:  hit 1", "::", "7-  f", "8:  hit 2"]
+    );
+
+    let args = [
+        "--no-color",
+        "-A",
+        "1",
+        "--no-context-separator",
+        "hit",
+        path,
+    ];
+    let (stdout, _, _) = run_xerg(&args);
+    let lines: Vec<&str> = stdout.lines().skip(1).map(str::trim).collect();
+    assert_eq!(lines, vec!["3:  hit 1", "4-  c", "8:  hit 2", "9-  g"]);
+}
+
+#[test]
+fn test_dry_run_previews_replacements_without_writing() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let rust_file = test_dir.join("file2.rs");
+    let rust_path = rust_file.to_str().unwrap();
+    let original = fs::read_to_string(&rust_file).unwrap();
+
+    let (stdout, _, exit_code) =
+        run_xerg(&["Hello (\\w+)", rust_path, "-r", "Bye $1", "--dry-run"]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(
+        stdout,
+        format!(
+            "{}: 1 replacement\n@@ -2 +2 @@\n-    println!(\"Hello Rust!\");\n+    println!(\"Bye Rust!\");\nresult: files:1; modified:1; replacements:1; skipped:0; errors:0;\n",
+            rust_path
+        )
+    );
+    assert_eq!(fs::read_to_string(&rust_file).unwrap(), original);
+
+    // A template adding a line break shifts the new line numbers after it
+    let file = test_dir.join("file1.txt");
+    let (stdout, _, _) = run_xerg(&["^\\w+ ", file.to_str().unwrap(), "-r", "$0\n", "--dry-run"]);
+    assert!(
+        stdout.contains(
+            ": 3 replacements\n@@ -1 +1,2 @@\n-Hello world\n+Hello \n+world\n@@ -2 +3,2 @@"
+        )
+    );
+    assert!(stdout.contains(
+        "@@ -3 +5,2 @@\n-It contains some sample text\n+It \n+contains some sample text\n"
+    ));
+
+    let (stdout, _, exit_code) = run_xerg(&["Missing", rust_path, "-r", "x", "--dry-run"]);
+    assert_eq!(exit_code, 1);
+    assert_eq!(
+        stdout,
+        "result: files:1; modified:0; replacements:0; skipped:0; errors:0;\n"
+    );
+}
+
+#[test]
+fn test_timing_lists_every_searched_file() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["--no-color", "--timing", "Hello", dir]].concat();
+        let (stdout, _, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        let (results, timings) = stdout.split_once("timing:\n").expect(&stdout);
+        assert!(results.contains("Hello world"));
+        assert_eq!(timings.lines().count(), 4, "{}", timings);
+        assert!(timings.lines().all(|line| line.contains("ms  ")));
+        assert!(timings.contains("nested.py"));
+    }
+
+    let (_, stderr, exit_code) = run_xerg(&["--timing", "--json", "Hello", dir]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_relative_paths_strip_search_root() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+    let nested = Path::new("subdir").join("nested.py");
+
+    let (stdout, _, exit_code) = run_xerg(&["--no-color", "--relative", "Python", dir]);
+    assert_eq!(exit_code, 0);
+    assert!(
+        stdout.starts_with(&format!("--- {} ---\n", nested.display())),
+        "{}",
+        stdout
+    );
+
+    let (stdout, _, _) = run_xerg(&["-x", "--no-color", "--relative", "Python", dir]);
+    assert!(
+        stdout.starts_with(&format!("{}:2: ", nested.display())),
+        "{}",
+        stdout
+    );
+
+    let (stdout, _, _) = run_xerg(&[
+        "-x",
+        "--no-color",
+        "--relative",
+        "--absolute",
+        "Python",
+        dir,
+    ]);
+    assert!(stdout.starts_with(&format!("{}:2: ", test_dir.join(&nested).display())));
+}
+
+#[test]
+fn test_fuzzy_matches_within_edit_distance() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("fuzzy.txt");
+    fs::write(&test_file, "open the database\ndatum\nclose the databse\n").unwrap();
+    let path = test_file.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["--no-color", "--fuzzy", "1", "databse", path]].concat();
+        let (stdout, _, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("open the database"), "{}", stdout);
+        assert!(stdout.contains("close the databse"));
+        assert!(!stdout.contains("datum"));
+    }
+
+    let (stdout, _, exit_code) = run_xerg(&["--fuzzy", "0", "databse", path, "--total"]);
+    assert_eq!((stdout.as_str(), exit_code), ("1\n", 0));
+
+    let (_, stderr, exit_code) = run_xerg(&["--fuzzy", "many", "databse", path]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("'many' is not a valid edit distance"));
+}
+
+#[test]
+fn test_engine_option() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("engine.txt");
+    fs::write(
+        &test_file,
+        "version 1.2
+version 102
+",
+    )
+    .unwrap();
+    let path = test_file.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["1.2", path, "--engine", "literal", "--total"]].concat();
+        assert_eq!(run_xerg(&args).0, "1\n");
+        let args = [mode, &["1.2", path, "--engine", "regex", "--total"]].concat();
+        assert_eq!(run_xerg(&args).0, "2\n");
+        let args = [mode, &["version", path, "--total"]].concat();
+        assert_eq!(run_xerg(&args).0, "2\n");
+    }
+
+    let (_, stderr, exit_code) = run_xerg(&["1.2", path, "--engine", "pcre"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("error: unknown engine 'pcre'"));
+}
+
+#[test]
+fn test_max_columns_truncates_long_lines() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("minified.js");
+    let long_line = format!("{}needle{}\na needle\n", "x".repeat(2000), "y".repeat(1000));
+    fs::write(&test_file, long_line).unwrap();
+    let path = test_file.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["--no-color", "needle", path, "--max-columns", "10"]].concat();
+        let (stdout, _, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        assert!(
+            stdout.contains("[... omitted 1998 bytes ...]xxneedleyy[... omitted 998 bytes ...]"),
+            "{}",
+            stdout
+        );
+        assert!(stdout.contains("a needle"));
+        assert!(stdout.len() < 300);
+    }
+
+    let (_, stderr, exit_code) = run_xerg(&["needle", path, "--max-columns", "wide"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("'wide' is not a valid column count"));
+}
+
+#[test]
+fn test_lines_option_limits_searched_lines() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_file = temp_dir.path().join("numbered.txt");
+    let content: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+    fs::write(&test_file, content).unwrap();
+    let path = test_file.to_str().unwrap();
+
+    for mode in [&[][..], &["-x"][..]] {
+        let args = [mode, &["--no-color", "line", path, "--lines", "5:7"]].concat();
+        let (stdout, _, exit_code) = run_xerg(&args);
+        assert_eq!(exit_code, 0);
+        assert!(
+            stdout.contains("line 5") && stdout.contains("line 7"),
+            "{}",
+            stdout
+        );
+        assert!(!stdout.contains("line 4") && !stdout.contains("line 8"));
+    }
+
+    let (stdout, _, _) = run_xerg(&["-x", "line", path, "--lines", "19:"]);
+    assert_eq!(stdout, "19: line 19\n20: line 20\n");
+
+    let (stdout, _, _) = run_xerg(&["-x", "line", path, "--lines", ":1"]);
+    assert_eq!(stdout, "1: line 1\n");
+
+    let (stdout, _, exit_code) = run_xerg(&["line", path, "--lines", "30:40"]);
+    assert_eq!((stdout.as_str(), exit_code), ("", 1));
+
+    let (_, stderr, exit_code) = run_xerg(&["line", path, "--lines", "9"]);
+    assert_eq!(exit_code, 2);
+    assert!(stderr.contains("invalid line range '9'"));
+}
+
+#[test]
+fn test_name_only_matches_paths() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = create_test_files(&temp_dir);
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["--name-only", "--relative", r"\.(rs|py)$", dir]);
+    assert_eq!(exit_code, 0);
+    let mut names: Vec<&str> = stdout.lines().collect();
+    names.sort();
+    let nested = Path::new("subdir").join("nested.py");
+    assert_eq!(names, vec!["file2.rs", nested.to_str().unwrap()]);
+
+    let (stdout, _, _) = run_xerg(&["--name-only", "--color", "always", "nested", dir]);
+    assert!(stdout.contains("\x1b[31mnested\x1b[0m.py"), "{}", stdout);
+
+    // Only the part below the searched directory is matched
+    let (stdout, _, exit_code) = run_xerg(&["--name-only", "test_files", dir]);
+    assert_eq!((stdout.as_str(), exit_code), ("", 1));
+}
+
+#[test]
+fn test_xtreme_stats_with_colons_and_spaces_in_file_names() {
+    let temp_dir = TempDir::new("integration_test").unwrap();
+    let test_dir = temp_dir.path().join("odd names");
+    fs::create_dir(&test_dir).unwrap();
+    fs::write(
+        test_dir.join("a: lines:7, matches:7.txt"),
+        "hit\nmiss\nhit hit\n",
+    )
+    .unwrap();
+    fs::write(test_dir.join("b c.txt"), "hit\n").unwrap();
+    let dir = test_dir.to_str().unwrap();
+
+    let (stdout, _, exit_code) = run_xerg(&["-x", "--no-color", "--stats", "hit", dir]);
+    assert_eq!(exit_code, 0);
+    assert!(
+        stdout.contains("a: lines:7, matches:7.txt:3: hit hit"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("# Summary: files:2, lines:4, matches:4, skipped:0, errors:0,"),
+        "{}",
+        stdout
+    );
+}